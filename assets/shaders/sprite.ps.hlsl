@@ -1,20 +1,13 @@
 struct Input
 {
-	// float2 texCoords	: TEXCOORD0;
-	// float4 color		: COLOR0;
+	float2 texCoords	: TEXCOORD0;
+	float4 color		: COLOR0;
 };
 
-// Texture2D Tex : register(t0);
-
-// SamplerState TexSampler : register(s0)
-// {
-// 	Filter = MIN_MAG_MIP_LINEAR;
-// 	AddressU = Wrap;
-// 	AddressV = Wrap;
-// };
+Texture2D Tex : register(t0);
+SamplerState TexSampler : register(s0);
 
 float4 main(Input input) : SV_Target
 {
-	// return Tex.Sample(TexSampler, input.TexCoords) * input.Color;
-    return float4(1, 0, 0, 1);
-}
\ No newline at end of file
+	return Tex.Sample(TexSampler, input.texCoords) * input.color;
+}
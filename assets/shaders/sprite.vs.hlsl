@@ -1,24 +1,24 @@
-// cbuffer PerFrameConstants : register(b0)
-// { 
-// 	column_major matrix Projection;
-// };
+cbuffer PerFrameConstants : register(b0)
+{ 
+	column_major matrix Projection;
+};
 
-// cbuffer PerObjectConstants : register(b1)
-// {
-// 	column_major matrix World;
-// };
+cbuffer PerObjectConstants : register(b1)
+{
+	column_major matrix World;
+};
 
 struct Input
 {
-	float4 position		: POSITION;
-	// float2 texCoords	: TEXCOORD0;
-	// float4 color		: COLOR0;
+	float2 position		: POSITION;
+	float2 texCoords	: TEXCOORD0;
+	float4 color		: COLOR0;
 };
 
 struct Output
 {
-	// float2 texCoords	: TEXCOORD0;
-	// float4 color		: COLOR0;
+	float2 texCoords	: TEXCOORD0;
+	float4 color		: COLOR0;
 	float4 position		: SV_Position;
 };
 
@@ -26,13 +26,11 @@ Output main(Input input)
 {
 	Output output;
 
-	//float4 position = mul(World, input.Position);
-	//output.Position = mul(Projection, position);
-
-	//output.Color = input.Color;
-	//output.TexCoords = input.TexCoords;
+	float4 position = mul(World, float4(input.position, 0, 1));
+	output.position = mul(Projection, position);
 
-	output.position = input.position;
+	output.color = input.color;
+	output.texCoords = input.texCoords;
 
 	return output;
-}
\ No newline at end of file
+}
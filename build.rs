@@ -1,82 +1,73 @@
 include!("src/platform/error.rs");
-use std::{env::set_current_dir, ffi::OsStr, fs, os::windows::prelude::OsStrExt, path::Path, ptr::null};
-use winapi::{
-    shared::winerror::{E_FAIL, S_OK},
-    um::d3dcompiler::{D3DCompileFromFile, D3DCOMPILE_DEBUG, D3DCOMPILE_ENABLE_STRICTNESS},
-};
 
-fn main() {
-    on_panic(|_| {});
-    set_current_dir("assets/").unwrap();
-
-    unsafe {
-        vertex_shader("shaders/sprite.vs.hlsl");
-        pixel_shader("shaders/sprite.ps.hlsl");
-    }
-}
+#[path = "src/log.rs"]
+#[allow(dead_code)]
+mod log;
 
-unsafe fn vertex_shader(path: &str) {
-    println!("Compiling vertex shader '{path}'.");
-    shader(path, b"vs_5_0\0");
-}
+// The asset compilers are shared with the crate, so that they can be tested and reused at runtime. The modules
+// that are only used at runtime, such as the asset manager, are omitted.
+#[path = "src/assets"]
+#[allow(dead_code)]
+mod assets {
+    pub mod archive;
+    pub mod checksum;
+    #[cfg(windows)]
+    pub mod d3d_compiler;
+    pub mod deflate;
+    mod error;
+    pub mod font;
+    pub mod hlsl;
+    pub mod image;
+    pub mod inflate;
+    pub mod level;
+    pub mod pipeline;
+    mod reader;
+    pub mod shader;
+    pub mod shader_compiler;
+    pub mod sound;
+    pub mod texture;
 
-unsafe fn pixel_shader(path: &str) {
-    println!("Compiling pixel shader '{path}'.");
-    shader(path, b"ps_5_0\0");
+    pub use error::DecodeError;
 }
 
-unsafe fn shader(path: &str, target: &[u8]) {
-    let mut shader_blob = null_mut();
-    let mut error_blob = null_mut();
+use assets::shader_compiler::{self, PrecompiledShaders, ShaderCompiler, SoftwareShaders};
+use log::{Entry, Level, Sink, StderrSink};
+use std::{env, path::Path};
 
-    let hr = D3DCompileFromFile(
-        OsStr::new(path).encode_wide().chain([0]).collect::<Vec<_>>().as_ptr(),
-        null(),
-        null_mut(),
-        b"main\0".as_ptr() as *const _,
-        target.as_ptr() as _,
-        if cfg!(debug_assertions) {
-            D3DCOMPILE_DEBUG | D3DCOMPILE_ENABLE_STRICTNESS
-        } else {
-            D3DCOMPILE_ENABLE_STRICTNESS
-        },
-        0,
-        &mut shader_blob,
-        &mut error_blob,
-    );
+/// Reports warnings and errors as Cargo warnings, as Cargo only shows the build script's output if it fails.
+struct CargoWarnings;
 
-    if hr == E_FAIL || !error_blob.is_null() {
-        let text = (*error_blob).GetBufferPointer() as *mut u8;
-        let size = (*error_blob).GetBufferSize();
-        let error = String::from_utf8_unchecked(Vec::from_raw_parts(text, size, size));
-        panic!("{error}")
-    } else if hr != S_OK {
-        handle_hresult_error(hr, "Failed to compile shader.");
+impl Sink for CargoWarnings {
+    fn write(&mut self, entry: &Entry) {
+        if entry.level >= Level::Warning {
+            println!("cargo:warning={}", entry.message);
+        }
     }
+}
 
-    write_file(
-        path,
-        std::slice::from_raw_parts((*shader_blob).GetBufferPointer() as *const u8, (*shader_blob).GetBufferSize()),
-    );
+fn main() {
+    log::logger().add_sink(StderrSink);
+    log::logger().add_sink(CargoWarnings);
+    on_panic(|_, _| {});
 
-    (*shader_blob).Release();
-}
+    println!("cargo:rerun-if-changed=assets");
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=src/assets");
+    println!("cargo:rerun-if-changed=src/assets.rs");
 
-fn write_file<C: AsRef<[u8]>>(path: &str, content: C) {
-    let out_dir = {
-        if cfg!(debug_assertions) {
-            "debug/"
-        } else {
-            "release/"
-        }
+    let out_dir = if cfg!(debug_assertions) { "debug" } else { "release" };
+    let target = Path::new("target/assets").join(out_dir);
+
+    // The shaders are compiled for the graphics backend of the target platform. When cross-compiling for Windows, the
+    // D3D shader compiler is not available, so the precompiled shaders are loaded instead. None are committed, so this
+    // fails with an error naming the missing files until they have been created on Windows.
+    let shader_compiler: Box<dyn ShaderCompiler> = match env::var("CARGO_CFG_TARGET_OS").as_deref() {
+        Ok("windows") if !cfg!(windows) => Box::new(PrecompiledShaders),
+        Ok("windows") => Box::new(shader_compiler::for_platform()),
+        _ => Box::new(SoftwareShaders),
     };
-    let asset_path = format!("../target/assets/{out_dir}{path}");
-    let file_path = Path::new(&asset_path);
-    let directory = file_path.parent().unwrap();
 
-    if !directory.is_dir() {
-        fs::create_dir_all(directory).unwrap_or_else(|e| panic!("Failed to create directory '{directory:?}': {e}."));
+    if let Err(e) = assets::pipeline::run(Path::new("assets"), &target, &*shader_compiler) {
+        panic!("{e}");
     }
-
-    fs::write(file_path, content).unwrap_or_else(|e| panic!("Failed to write file '{asset_path}': {e}."));
 }
@@ -0,0 +1,47 @@
+//! Compiles the shaders listed in the asset manifest with the D3D shader compiler and writes their byte code next to
+//! their HLSL source, so that the game can be built for Windows on platforms without the compiler. Run it from the
+//! repository's root directory with `cargo run --example precompile_shaders` after changing a shader, and commit
+//! the updated `.compiled` files.
+
+use lwar::{
+    error,
+    log::{self, Category, StderrSink},
+};
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    log::logger().add_sink(StderrSink);
+
+    match precompile() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            error!(Category::Assets, "{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(windows)]
+fn precompile() -> Result<(), String> {
+    use lwar::{
+        assets::{d3d_compiler::D3DCompiler, shader_compiler},
+        info,
+    };
+    use std::path::Path;
+
+    let changed = shader_compiler::precompile(Path::new("assets"), &D3DCompiler)?;
+    for path in &changed {
+        info!(Category::Assets, "Updated '{}'.", path.display());
+    }
+
+    if changed.is_empty() {
+        info!(Category::Assets, "The precompiled shaders are up to date.");
+    }
+
+    Ok(())
+}
+
+#[cfg(not(windows))]
+fn precompile() -> Result<(), String> {
+    Err("The shaders can only be precompiled on Windows, where the D3D shader compiler is available.".to_string())
+}
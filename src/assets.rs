@@ -0,0 +1,21 @@
+pub mod archive;
+pub mod checksum;
+#[cfg(windows)]
+pub mod d3d_compiler;
+pub mod deflate;
+mod error;
+pub mod font;
+pub mod hlsl;
+pub mod hot_reload;
+pub mod image;
+pub mod inflate;
+pub mod level;
+pub mod manager;
+pub mod pipeline;
+mod reader;
+pub mod shader;
+pub mod shader_compiler;
+pub mod sound;
+pub mod texture;
+
+pub use error::DecodeError;
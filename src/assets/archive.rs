@@ -0,0 +1,229 @@
+use super::{checksum::crc32, deflate, inflate, reader::Reader, DecodeError};
+use std::borrow::Cow;
+
+/// The name of the archive within the asset directory.
+pub const FILE_NAME: &str = "assets.pak";
+
+/// Identifies asset archives and the version of their format.
+const MAGIC: &[u8; 4] = b"PAK1";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Compression {
+    None,
+    Deflate,
+}
+
+/// An entry of the archive's table of contents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Entry {
+    id: String,
+    compression: Compression,
+    /// The range of the archive that contains the entry's (possibly compressed) data.
+    offset: usize,
+    stored_size: usize,
+    /// The size and checksum of the uncompressed data.
+    size: usize,
+    checksum: u32,
+}
+
+/// An archive of assets that are looked up by their id, which is the path of the compiled asset. Archives start with
+/// a header containing the number of entries followed by the table of contents, which stores the id, compression,
+/// location, size and checksum of each entry. The data of the entries follows the table of contents.
+pub struct Archive<D> {
+    data: D,
+    /// The entries ordered by their ids.
+    entries: Vec<Entry>,
+}
+
+impl<D: AsRef<[u8]>> Archive<D> {
+    /// Reads the table of contents of an archive; the entries are only decompressed and verified when they are read.
+    pub fn new(data: D) -> Result<Archive<D>, DecodeError> {
+        let mut reader = Reader::new(data.as_ref(), "archive");
+        if &reader.array()? != MAGIC {
+            return Err(reader.error("Invalid signature."));
+        }
+
+        let entry_count = reader.u32_le()? as usize;
+        // Each entry occupies at least 31 bytes; checking this up front avoids allocating huge vectors for corrupt data.
+        if entry_count > reader.remaining() / 31 {
+            return Err(reader.error("Unexpected end of data."));
+        }
+
+        let mut entries = Vec::with_capacity(entry_count);
+        for _ in 0..entry_count {
+            let id_length = reader.u16_le()? as usize;
+            let id = String::from_utf8(reader.bytes(id_length)?.to_vec()).map_err(|_| reader.error("Invalid entry id."))?;
+            let compression = match reader.u8()? {
+                0 => Compression::None,
+                1 => Compression::Deflate,
+                _ => return Err(reader.error("Unsupported compression method.")),
+            };
+
+            let entry = Entry {
+                id,
+                compression,
+                offset: reader.u64_le()? as usize,
+                stored_size: reader.u64_le()? as usize,
+                size: reader.u64_le()? as usize,
+                checksum: reader.u32_le()?,
+            };
+
+            if entry
+                .offset
+                .checked_add(entry.stored_size)
+                .is_none_or(|end| end > data.as_ref().len())
+            {
+                return Err(reader.error("Entry exceeds the archive."));
+            }
+
+            entries.push(entry);
+        }
+
+        entries.sort_by(|a, b| a.id.cmp(&b.id));
+        if entries.windows(2).any(|pair| pair[0].id == pair[1].id) {
+            return Err(reader.error("Duplicate entry id."));
+        }
+
+        Ok(Archive { data, entries })
+    }
+
+    /// The ids of all entries in ascending order.
+    pub fn ids(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|entry| entry.id.as_str())
+    }
+
+    /// Reads the entry with the given id, if there is one. Uncompressed entries are returned without being copied.
+    pub fn read(&self, id: &str) -> Option<Result<Cow<'_, [u8]>, DecodeError>> {
+        let index = self.entries.binary_search_by(|entry| entry.id.as_str().cmp(id)).ok()?;
+        let entry = &self.entries[index];
+        let stored = &self.data.as_ref()[entry.offset..entry.offset + entry.stored_size];
+        let error = |message| DecodeError {
+            format: "archive",
+            message,
+        };
+
+        let data = match entry.compression {
+            Compression::None => Cow::Borrowed(stored),
+            Compression::Deflate => match inflate::inflate(stored) {
+                Ok(data) => Cow::Owned(data),
+                Err(e) => return Some(Err(e)),
+            },
+        };
+
+        if data.len() != entry.size {
+            return Some(Err(error("Size mismatch.")));
+        }
+
+        if crc32(data.iter()) != entry.checksum {
+            return Some(Err(error("Checksum mismatch.")));
+        }
+
+        Some(Ok(data))
+    }
+}
+
+/// Creates an archive containing the given entries, which are compressed if that reduces their size.
+pub fn write<'a>(entries: impl IntoIterator<Item = (&'a str, &'a [u8])>) -> Vec<u8> {
+    let entries: Vec<_> = entries
+        .into_iter()
+        .map(|(id, data)| {
+            let compressed = deflate::compress(data);
+            if compressed.len() < data.len() {
+                (id, Compression::Deflate, Cow::Owned(compressed), data)
+            } else {
+                (id, Compression::None, Cow::Borrowed(data), data)
+            }
+        })
+        .collect();
+
+    let table_size: usize = entries.iter().map(|(id, ..)| 31 + id.len()).sum();
+    let mut offset = 8 + table_size;
+
+    let mut archive = MAGIC.to_vec();
+    archive.extend_from_slice(&(entries.len() as u32).to_le_bytes());
+    for (id, compression, stored, data) in &entries {
+        archive.extend_from_slice(&(id.len() as u16).to_le_bytes());
+        archive.extend_from_slice(id.as_bytes());
+        archive.push(*compression as u8);
+        archive.extend_from_slice(&(offset as u64).to_le_bytes());
+        archive.extend_from_slice(&(stored.len() as u64).to_le_bytes());
+        archive.extend_from_slice(&(data.len() as u64).to_le_bytes());
+        archive.extend_from_slice(&crc32(data.iter()).to_le_bytes());
+        offset += stored.len();
+    }
+
+    for (_, _, stored, _) in &entries {
+        archive.extend_from_slice(stored);
+    }
+
+    archive
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn archive() -> Vec<u8> {
+        let text = b"compressible ".repeat(100);
+        write([
+            ("textures/ship.texture", &text[..]),
+            ("levels/arena.level", &[1, 2, 3][..]),
+            ("empty", &[][..]),
+        ])
+    }
+
+    #[test]
+    fn entries_are_looked_up_by_id() {
+        let archive_data = archive();
+        let archive = Archive::new(&archive_data).unwrap();
+
+        assert_eq!(
+            archive.ids().collect::<Vec<_>>(),
+            ["empty", "levels/arena.level", "textures/ship.texture"]
+        );
+        assert_eq!(archive.read("levels/arena.level"), Some(Ok(Cow::Borrowed(&[1, 2, 3][..]))));
+        assert_eq!(archive.read("empty"), Some(Ok(Cow::Borrowed(&[][..]))));
+        assert_eq!(archive.read("missing"), None);
+
+        let text = archive.read("textures/ship.texture").unwrap().unwrap();
+        assert!(matches!(text, Cow::Owned(_)), "Compressible entries should be compressed.");
+        assert_eq!(text, b"compressible ".repeat(100));
+        assert!(archive_data.len() < 1000);
+    }
+
+    #[test]
+    fn corrupt_entries_are_detected() {
+        let mut data = archive();
+        let last = data.len() - 1;
+        data[last] ^= 1;
+
+        let archive = Archive::new(data).unwrap();
+        let corrupt = archive.read("levels/arena.level").unwrap();
+        assert_eq!(corrupt.map_err(|error| error.message), Err("Checksum mismatch."));
+        assert!(archive.read("textures/ship.texture").unwrap().is_ok());
+    }
+
+    #[test]
+    fn corrupt_tables_of_contents_are_rejected() {
+        let data = archive();
+        assert_eq!(
+            Archive::new(&data[..20]).err().map(|error| error.message),
+            Some("Unexpected end of data.")
+        );
+        assert_eq!(
+            Archive::new(&data[..data.len() - 1]).err().map(|error| error.message),
+            Some("Entry exceeds the archive.")
+        );
+        assert_eq!(
+            Archive::new(b"PAK0").err().map(|error| error.message),
+            Some("Invalid signature.")
+        );
+
+        let duplicates = write([("a", &[1][..]), ("a", &[2][..])]);
+        assert_eq!(
+            Archive::new(&duplicates).err().map(|error| error.message),
+            Some("Duplicate entry id.")
+        );
+    }
+}
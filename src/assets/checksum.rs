@@ -0,0 +1,21 @@
+/// Computes the CRC-32 checksum used by PNG chunks and zip archives.
+pub fn crc32<'a>(data: impl IntoIterator<Item = &'a u8>) -> u32 {
+    !data.into_iter().fold(!0, |crc, &byte| {
+        (0..8).fold(crc ^ byte as u32, |crc, _| {
+            if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB88320
+            } else {
+                crc >> 1
+            }
+        })
+    })
+}
+
+/// Computes the Adler-32 checksum that terminates zlib streams.
+pub fn adler32(data: &[u8]) -> u32 {
+    let (a, b) = data.iter().fold((1, 0), |(a, b), &byte| {
+        let a = (a + byte as u32) % 65521;
+        (a, (b + a) % 65521)
+    });
+    (b << 16) | a
+}
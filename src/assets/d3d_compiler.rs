@@ -0,0 +1,81 @@
+use super::{pipeline::AssetKind, shader_compiler::ShaderCompiler};
+use crate::{log::Category, warn};
+use std::{
+    ffi::OsStr,
+    io,
+    os::windows::prelude::OsStrExt,
+    path::Path,
+    ptr::{null, null_mut},
+    slice,
+};
+use winapi::{
+    shared::winerror::FAILED,
+    um::d3dcompiler::{D3DCompileFromFile, D3DCOMPILE_ENABLE_STRICTNESS},
+};
+
+/// Compiles HLSL shaders using the D3D shader compiler, which is only available on Windows.
+pub struct D3DCompiler;
+
+impl ShaderCompiler for D3DCompiler {
+    fn compile(&self, path: &Path, kind: AssetKind) -> Result<Vec<u8>, String> {
+        unsafe { compile(path, kind) }
+    }
+}
+
+unsafe fn compile(path: &Path, kind: AssetKind) -> Result<Vec<u8>, String> {
+    let target: &[u8] = match kind {
+        AssetKind::VertexShader => b"vs_5_0\0",
+        AssetKind::PixelShader => b"ps_5_0\0",
+        _ => unreachable!(),
+    };
+
+    let mut shader_blob = null_mut();
+    let mut error_blob = null_mut();
+
+    let hr = D3DCompileFromFile(
+        OsStr::new(path).encode_wide().chain([0]).collect::<Vec<_>>().as_ptr(),
+        null(),
+        null_mut(),
+        b"main\0".as_ptr() as *const _,
+        target.as_ptr() as _,
+        // The flags do not depend on the build profile, so that the precompiled shaders are the same for all builds.
+        D3DCOMPILE_ENABLE_STRICTNESS,
+        0,
+        &mut shader_blob,
+        &mut error_blob,
+    );
+
+    // The compiler's messages are also returned if the shader compiles with warnings.
+    let messages = if error_blob.is_null() {
+        String::new()
+    } else {
+        let text = (*error_blob).GetBufferPointer() as *const u8;
+        let size = (*error_blob).GetBufferSize();
+        let messages = String::from_utf8_lossy(slice::from_raw_parts(text, size))
+            .trim_end_matches('\0')
+            .trim_end()
+            .to_string();
+        (*error_blob).Release();
+        messages
+    };
+
+    if FAILED(hr) {
+        if !shader_blob.is_null() {
+            (*shader_blob).Release();
+        }
+
+        return Err(match messages.is_empty() {
+            true => io::Error::from_raw_os_error(hr).to_string(),
+            false => messages,
+        });
+    }
+
+    if !messages.is_empty() {
+        warn!(Category::Assets, "{}: {messages}", path.display());
+    }
+
+    let byte_code =
+        slice::from_raw_parts((*shader_blob).GetBufferPointer() as *const u8, (*shader_blob).GetBufferSize()).to_vec();
+    (*shader_blob).Release();
+    Ok(byte_code)
+}
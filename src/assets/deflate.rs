@@ -0,0 +1,155 @@
+use super::inflate::{DISTANCE_BASES, DISTANCE_EXTRA_BITS, LENGTH_BASES, LENGTH_EXTRA_BITS};
+
+const WINDOW_SIZE: usize = 32768;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+const HASH_BITS: u32 = 15;
+/// The maximum number of earlier positions that are checked for the longest match, trading compression ratio for
+/// speed.
+const MAX_CHAIN_LENGTH: usize = 128;
+
+/// Compresses data into raw deflate data (RFC 1951) that consists of a single block using the fixed Huffman code.
+/// Repetitions are found greedily by looking up earlier occurrences of the next three bytes in a hash chain.
+pub fn compress(data: &[u8]) -> Vec<u8> {
+    let mut bits = BitWriter::default();
+    bits.write(1, 1); // the final block
+    bits.write(1, 2); // the fixed Huffman code
+
+    // `head` maps the hash of three bytes to the most recent position they occurred at and `previous` links each
+    // position to the previous occurrence of the same hash within the window, with positions offset by one so that
+    // zero means none.
+    let mut head = vec![0usize; 1 << HASH_BITS];
+    let mut previous = vec![0usize; WINDOW_SIZE];
+    let insert = |head: &mut [usize], previous: &mut [usize], position: usize| {
+        if position + MIN_MATCH <= data.len() {
+            let hash = hash(&data[position..position + MIN_MATCH]);
+            previous[position % WINDOW_SIZE] = head[hash];
+            head[hash] = position + 1;
+        }
+    };
+
+    let mut position = 0;
+    while position < data.len() {
+        let (mut length, mut distance) = (0, 0);
+        if position + MIN_MATCH <= data.len() {
+            let max_length = MAX_MATCH.min(data.len() - position);
+            let mut candidate = head[hash(&data[position..position + MIN_MATCH])];
+            for _ in 0..MAX_CHAIN_LENGTH {
+                if candidate == 0 || position - (candidate - 1) > WINDOW_SIZE {
+                    break;
+                }
+
+                let start = candidate - 1;
+                let matched = data[start..start + max_length]
+                    .iter()
+                    .zip(&data[position..position + max_length])
+                    .take_while(|(a, b)| a == b)
+                    .count();
+
+                if matched > length {
+                    (length, distance) = (matched, position - start);
+                    if matched == max_length {
+                        break;
+                    }
+                }
+
+                candidate = previous[start % WINDOW_SIZE];
+            }
+        }
+
+        if length >= MIN_MATCH {
+            let index = LENGTH_BASES.iter().rposition(|&base| base as usize <= length).unwrap();
+            write_literal_code(&mut bits, 257 + index);
+            bits.write((length - LENGTH_BASES[index] as usize) as u32, LENGTH_EXTRA_BITS[index]);
+
+            let index = DISTANCE_BASES.iter().rposition(|&base| base as usize <= distance).unwrap();
+            bits.write_code(index as u32, 5);
+            bits.write((distance - DISTANCE_BASES[index] as usize) as u32, DISTANCE_EXTRA_BITS[index]);
+
+            for offset in 0..length {
+                insert(&mut head, &mut previous, position + offset);
+            }
+            position += length;
+        } else {
+            write_literal_code(&mut bits, data[position] as usize);
+            insert(&mut head, &mut previous, position);
+            position += 1;
+        }
+    }
+
+    write_literal_code(&mut bits, 256);
+    bits.finish()
+}
+
+fn hash(bytes: &[u8]) -> usize {
+    let value = (bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | bytes[2] as u32;
+    (value.wrapping_mul(0x9E3779B1) >> (32 - HASH_BITS)) as usize
+}
+
+/// Writes a symbol of the literal/length alphabet using the fixed Huffman code.
+fn write_literal_code(bits: &mut BitWriter, symbol: usize) {
+    let symbol = symbol as u32;
+    match symbol {
+        0..=143 => bits.write_code(0x30 + symbol, 8),
+        144..=255 => bits.write_code(0x190 + symbol - 144, 9),
+        256..=279 => bits.write_code(symbol - 256, 7),
+        _ => bits.write_code(0xC0 + symbol - 280, 8),
+    }
+}
+
+/// Writes bits starting with the least significant bit of each byte.
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    bit_buffer: u32,
+    bit_count: u8,
+}
+
+impl BitWriter {
+    fn write(&mut self, value: u32, count: u8) {
+        self.bit_buffer |= value << self.bit_count;
+        self.bit_count += count;
+        while self.bit_count >= 8 {
+            self.bytes.push(self.bit_buffer as u8);
+            self.bit_buffer >>= 8;
+            self.bit_count -= 8;
+        }
+    }
+
+    /// Writes a Huffman code, which, unlike all other values, is stored starting with its most significant bit.
+    fn write_code(&mut self, code: u32, length: u8) {
+        self.write(code.reverse_bits() >> (32 - length), length);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bit_count > 0 {
+            self.bytes.push(self.bit_buffer as u8);
+        }
+        self.bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::inflate::inflate, *};
+
+    #[test]
+    fn compressed_data_can_be_inflated() {
+        let repetitive = b"abcabcabc".repeat(1000);
+        let long_runs = [vec![0; 70000], vec![1; 300], vec![0; 5]].concat();
+        let noise = (0..100000u32)
+            .map(|i| (i.wrapping_mul(2654435761) >> 24) as u8)
+            .collect::<Vec<_>>();
+        let image = include_bytes!("../../tests/images/rgba8.png").to_vec();
+
+        for data in [vec![], vec![42], b"ab".to_vec(), repetitive, long_runs, noise, image] {
+            assert_eq!(inflate(&compress(&data)), Ok(data));
+        }
+    }
+
+    #[test]
+    fn repetitions_are_compressed() {
+        assert!(compress(&b"abcabcabc".repeat(1000)).len() < 100);
+        assert!(compress(&[0; 70000]).len() < 1000);
+    }
+}
@@ -0,0 +1,16 @@
+use std::fmt;
+
+/// Describes why a file could not be decoded. Decoders never panic on malformed input but return this error instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodeError {
+    pub format: &'static str,
+    pub message: &'static str,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid {} data: {}", self.format, self.message)
+    }
+}
+
+impl std::error::Error for DecodeError {}
@@ -0,0 +1,327 @@
+use super::{image, reader::Reader, DecodeError};
+
+/// Identifies compiled fonts and the version of their format.
+pub(super) const MAGIC: &[u8; 4] = b"FNT1";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Glyph {
+    pub character: char,
+    /// The area of the font's texture that contains the glyph.
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+    pub height: u16,
+    /// The offset of the glyph's area relative to the pen position.
+    pub offset: [i16; 2],
+    /// The distance the pen position advances after the glyph has been drawn.
+    pub advance: i16,
+}
+
+/// Adjusts the advance of the first character when it is followed by the second one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Kerning {
+    pub first: char,
+    pub second: char,
+    pub amount: i16,
+}
+
+/// A compiled bitmap font, whose texture stores the coverage of the glyphs as a single 8 bit channel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Font<'a> {
+    pub line_height: u16,
+    /// The distance from the top of a line to the baseline of its characters.
+    pub baseline: u16,
+    pub glyphs: Vec<Glyph>,
+    pub kernings: Vec<Kerning>,
+    pub texture_width: u32,
+    pub texture_height: u32,
+    pub texture: &'a [u8],
+}
+
+/// Compiles a font described in the text format of the AngelCode bitmap font generator into the runtime font
+/// format. Only fonts with a single texture page are supported; the page is loaded via `load_page`, which is
+/// invoked with the file name specified by the descriptor. The glyphs' coverage is taken from the page's alpha
+/// channel or, if the page is opaque, from its red channel.
+pub fn compile(descriptor: &str, mut load_page: impl FnMut(&str) -> Result<Vec<u8>, String>) -> Result<Vec<u8>, String> {
+    let (mut common, mut page, mut glyphs, mut kernings) = (None, None, vec![], vec![]);
+
+    for (line_number, line) in descriptor.lines().enumerate() {
+        let error = |message: &str| format!("Line {}: {message}", line_number + 1);
+        let (tag, attributes) = parse_line(line).map_err(error)?;
+        let attribute = |name: &str| -> Result<&str, String> {
+            attributes
+                .iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| *value)
+                .ok_or_else(|| error(&format!("Missing attribute '{name}'.")))
+        };
+        let number = |name: &str| -> Result<i32, String> {
+            let value = attribute(name)?;
+            value
+                .parse()
+                .map_err(|_| error(&format!("Invalid value '{value}' of attribute '{name}'.")))
+        };
+        let character = |name: &str| -> Result<char, String> {
+            char::from_u32(number(name)? as u32).ok_or_else(|| error(&format!("Invalid character '{name}'.")))
+        };
+
+        match tag {
+            "common" => {
+                if number("pages")? != 1 {
+                    return Err(error("Only fonts with a single page are supported."));
+                }
+
+                common = Some((number("lineHeight")? as u16, number("base")? as u16));
+            }
+            "page" => page = Some(attribute("file")?.to_string()),
+            "char" => {
+                if number("page")? != 0 {
+                    return Err(error("Only fonts with a single page are supported."));
+                }
+
+                glyphs.push(Glyph {
+                    character: character("id")?,
+                    x: number("x")? as u16,
+                    y: number("y")? as u16,
+                    width: number("width")? as u16,
+                    height: number("height")? as u16,
+                    offset: [number("xoffset")? as i16, number("yoffset")? as i16],
+                    advance: number("xadvance")? as i16,
+                });
+            }
+            "kerning" => kernings.push(Kerning {
+                first: character("first")?,
+                second: character("second")?,
+                amount: number("amount")? as i16,
+            }),
+            _ => (),
+        }
+    }
+
+    let (line_height, baseline) = common.ok_or("The font has no common information.")?;
+    let page = page.ok_or("The font has no page.")?;
+    let image = image::decode(&load_page(&page)?).map_err(|error| format!("Page '{page}': {error}"))?;
+
+    for glyph in &glyphs {
+        if glyph.x as u32 + glyph.width as u32 > image.width || glyph.y as u32 + glyph.height as u32 > image.height {
+            return Err(format!("Glyph '{}' lies outside of page '{page}'.", glyph.character));
+        }
+    }
+
+    let opaque = image.pixels.chunks_exact(4).all(|pixel| pixel[3] == 255);
+    let channel = if opaque { 0 } else { 3 };
+
+    let mut font = MAGIC.to_vec();
+    font.extend_from_slice(&line_height.to_le_bytes());
+    font.extend_from_slice(&baseline.to_le_bytes());
+    font.extend_from_slice(&(glyphs.len() as u32).to_le_bytes());
+    for glyph in &glyphs {
+        font.extend_from_slice(&(glyph.character as u32).to_le_bytes());
+        for value in [glyph.x, glyph.y, glyph.width, glyph.height] {
+            font.extend_from_slice(&value.to_le_bytes());
+        }
+        for value in [glyph.offset[0], glyph.offset[1], glyph.advance] {
+            font.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+
+    font.extend_from_slice(&(kernings.len() as u32).to_le_bytes());
+    for kerning in &kernings {
+        font.extend_from_slice(&(kerning.first as u32).to_le_bytes());
+        font.extend_from_slice(&(kerning.second as u32).to_le_bytes());
+        font.extend_from_slice(&kerning.amount.to_le_bytes());
+    }
+
+    font.extend_from_slice(&image.width.to_le_bytes());
+    font.extend_from_slice(&image.height.to_le_bytes());
+    font.extend(image.pixels.chunks_exact(4).map(|pixel| pixel[channel]));
+    Ok(font)
+}
+
+pub fn load(data: &[u8]) -> Result<Font<'_>, DecodeError> {
+    let mut reader = Reader::new(data, "font");
+    if &reader.array()? != MAGIC {
+        return Err(reader.error("Invalid signature."));
+    }
+
+    let line_height = reader.u16_le()?;
+    let baseline = reader.u16_le()?;
+
+    let glyph_count = reader.u32_le()? as usize;
+    // Each glyph occupies 18 bytes; checking this up front avoids allocating huge vectors for corrupt data.
+    if glyph_count > reader.remaining() / 18 {
+        return Err(reader.error("Unexpected end of data."));
+    }
+
+    let mut glyphs = Vec::with_capacity(glyph_count);
+    for _ in 0..glyph_count {
+        glyphs.push(Glyph {
+            character: read_char(&mut reader)?,
+            x: reader.u16_le()?,
+            y: reader.u16_le()?,
+            width: reader.u16_le()?,
+            height: reader.u16_le()?,
+            offset: [reader.i16_le()?, reader.i16_le()?],
+            advance: reader.i16_le()?,
+        });
+    }
+
+    let kerning_count = reader.u32_le()? as usize;
+    if kerning_count > reader.remaining() / 10 {
+        return Err(reader.error("Unexpected end of data."));
+    }
+
+    let mut kernings = Vec::with_capacity(kerning_count);
+    for _ in 0..kerning_count {
+        kernings.push(Kerning {
+            first: read_char(&mut reader)?,
+            second: read_char(&mut reader)?,
+            amount: reader.i16_le()?,
+        });
+    }
+
+    let texture_width = reader.u32_le()?;
+    let texture_height = reader.u32_le()?;
+    let texture = reader.bytes(texture_width as usize * texture_height as usize)?;
+
+    Ok(Font {
+        line_height,
+        baseline,
+        glyphs,
+        kernings,
+        texture_width,
+        texture_height,
+        texture,
+    })
+}
+
+fn read_char(reader: &mut Reader) -> Result<char, DecodeError> {
+    char::from_u32(reader.u32_le()?).ok_or(reader.error("Invalid character."))
+}
+
+/// The key-value pairs of a line of a font descriptor.
+type Attributes<'a> = Vec<(&'a str, &'a str)>;
+
+/// Splits a line of the form `tag key=value key="quoted value" ...` into its tag and attributes.
+fn parse_line(line: &str) -> Result<(&str, Attributes<'_>), &'static str> {
+    let line = line.trim();
+    let (tag, mut rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+
+    let mut attributes = vec![];
+    loop {
+        rest = rest.trim_start();
+        if rest.is_empty() {
+            return Ok((tag, attributes));
+        }
+
+        let (key, value) = rest.split_once('=').ok_or("Expected an attribute.")?;
+        let (value, remainder) = match value.strip_prefix('"') {
+            Some(quoted) => quoted.split_once('"').ok_or("Unterminated string.")?,
+            None => value.split_once(char::is_whitespace).unwrap_or((value, "")),
+        };
+
+        attributes.push((key, value));
+        rest = remainder;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DESCRIPTOR: &str = "info face=\"Some Font\" size=12 bold=0 padding=0,0,0,0\n\
+        common lineHeight=14 base=11 scaleW=4 scaleH=2 pages=1 packed=0\n\
+        page id=0 file=\"font page.tga\"\n\
+        chars count=2\n\
+        char id=65   x=0 y=0 width=2 height=2 xoffset=0 yoffset=1 xadvance=3 page=0 chnl=15\n\
+        char id=228  x=2 y=0 width=2 height=1 xoffset=-1 yoffset=2 xadvance=2 page=0 chnl=15\n\
+        kernings count=1\n\
+        kerning first=65 second=228 amount=-1\n";
+
+    /// A 4x2 TGA image with the given alpha values, stored from top to bottom.
+    fn page(alpha: [u8; 8]) -> Vec<u8> {
+        let mut tga = vec![0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 4, 0, 2, 0, 32, 0x28];
+        tga.extend(alpha.iter().flat_map(|&alpha| [255, 255, 255, alpha]));
+        tga
+    }
+
+    #[test]
+    fn fonts_are_compiled() {
+        let mut pages = vec![];
+        let compiled = compile(DESCRIPTOR, |file| {
+            pages.push(file.to_string());
+            Ok(page([0, 10, 20, 30, 40, 50, 60, 70]))
+        })
+        .unwrap();
+
+        assert_eq!(pages, ["font page.tga"]);
+        assert_eq!(
+            load(&compiled),
+            Ok(Font {
+                line_height: 14,
+                baseline: 11,
+                glyphs: vec![
+                    Glyph {
+                        character: 'A',
+                        x: 0,
+                        y: 0,
+                        width: 2,
+                        height: 2,
+                        offset: [0, 1],
+                        advance: 3
+                    },
+                    Glyph {
+                        character: 'ä',
+                        x: 2,
+                        y: 0,
+                        width: 2,
+                        height: 1,
+                        offset: [-1, 2],
+                        advance: 2
+                    },
+                ],
+                kernings: vec![Kerning {
+                    first: 'A',
+                    second: 'ä',
+                    amount: -1
+                }],
+                texture_width: 4,
+                texture_height: 2,
+                texture: &[0, 10, 20, 30, 40, 50, 60, 70],
+            })
+        );
+        assert!(load(&compiled[..compiled.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn invalid_fonts_are_rejected() {
+        let valid_page = |_: &str| Ok(page([255; 8]));
+        for (descriptor, message) in [
+            ("", "The font has no common information."),
+            ("common lineHeight=1 base=1 pages=1", "The font has no page."),
+            (
+                "common lineHeight=1 base=1 pages=2",
+                "Line 1: Only fonts with a single page are supported.",
+            ),
+            ("common lineHeight=1 pages=1", "Line 1: Missing attribute 'base'."),
+            (
+                "common lineHeight=x base=1 pages=1",
+                "Line 1: Invalid value 'x' of attribute 'lineHeight'.",
+            ),
+            ("page id=0 file=\"a.png", "Line 1: Unterminated string."),
+            ("info face", "Line 1: Expected an attribute."),
+            (
+                "common lineHeight=1 base=1 pages=1\npage file=a\n\
+                 char id=65 x=3 y=0 width=2 height=2 xoffset=0 yoffset=0 xadvance=0 page=0",
+                "Glyph 'A' lies outside of page 'a'.",
+            ),
+        ] {
+            assert_eq!(compile(descriptor, valid_page), Err(message.to_string()), "{descriptor}");
+        }
+
+        let missing_page = compile("common lineHeight=1 base=1 pages=1\npage file=a", |_| {
+            Err("Missing.".to_string())
+        });
+        assert_eq!(missing_page, Err("Missing.".to_string()));
+    }
+}
@@ -0,0 +1,622 @@
+use super::shader::{ConstantBufferLayout, ConstantBufferMember, Reflection, ResourceBinding, ScalarType, ShaderInput};
+
+/// The name of the entry point of all shaders.
+const ENTRY_POINT: &str = "main";
+
+/// Keywords that may precede the type of a declaration, parameter or member without affecting the reflection data,
+/// except for the matrix packing orders, which are checked separately.
+const MODIFIERS: &[&str] = &[
+    "static",
+    "const",
+    "uniform",
+    "extern",
+    "volatile",
+    "precise",
+    "in",
+    "linear",
+    "centroid",
+    "nointerpolation",
+    "noperspective",
+    "sample",
+    "row_major",
+    "column_major",
+];
+
+/// Extracts the inputs of the entry point and the constant buffers, textures and samplers from a shader's HLSL source.
+/// Only the declarations at the top level are inspected, which covers the subset of HLSL used by the game's shaders:
+/// Structs, constant buffers, textures and samplers with explicit registers, and functions. Preprocessor directives are
+/// ignored, so declarations in included files are not found.
+pub fn reflect(source: &str) -> Result<Reflection, String> {
+    let tokens = tokenize(source);
+    let mut parser = Parser {
+        tokens: &tokens,
+        position: 0,
+        structs: vec![],
+        reflection: Reflection::default(),
+        has_entry_point: false,
+    };
+
+    while parser.peek().is_some() {
+        parser.declaration()?;
+    }
+
+    if !parser.has_entry_point {
+        return Err(format!("The shader has no '{ENTRY_POINT}' function."));
+    }
+
+    Ok(parser.reflection)
+}
+
+struct Token<'a> {
+    line: usize,
+    text: &'a str,
+}
+
+/// Splits the source into identifiers, numbers and single punctuation characters, removing comments and preprocessor
+/// directives.
+fn tokenize(source: &str) -> Vec<Token<'_>> {
+    let mut tokens = vec![];
+    let mut in_comment = false;
+    for (index, line) in source.lines().enumerate() {
+        let mut rest = line;
+        if !in_comment && rest.trim_start().starts_with('#') {
+            continue;
+        }
+
+        loop {
+            if in_comment {
+                match rest.find("*/") {
+                    Some(end) => {
+                        rest = &rest[end + 2..];
+                        in_comment = false;
+                    }
+                    None => break,
+                }
+            }
+
+            rest = rest.trim_start();
+            if rest.is_empty() || rest.starts_with("//") {
+                break;
+            }
+
+            if let Some(comment) = rest.strip_prefix("/*") {
+                rest = comment;
+                in_comment = true;
+                continue;
+            }
+
+            let length = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len())
+                .max(rest.chars().next().map_or(0, char::len_utf8));
+            tokens.push(Token {
+                line: index + 1,
+                text: &rest[..length],
+            });
+            rest = &rest[length..];
+        }
+    }
+
+    tokens
+}
+
+/// A type whose size and layout are known: Scalars have one row and column and vectors have one row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Type {
+    scalar: ScalarType,
+    rows: u32,
+    columns: u32,
+}
+
+impl Type {
+    fn parse(name: &str) -> Option<Type> {
+        match name {
+            "matrix" => return Type::parse("float4x4"),
+            "vector" => return Type::parse("float4"),
+            _ => {}
+        }
+
+        let (scalar, dimensions) = [
+            ("float", ScalarType::Float),
+            ("half", ScalarType::Float),
+            ("int", ScalarType::Int),
+            ("uint", ScalarType::Uint),
+            ("dword", ScalarType::Uint),
+            ("bool", ScalarType::Bool),
+        ]
+        .iter()
+        .find_map(|(prefix, scalar)| name.strip_prefix(prefix).map(|dimensions| (*scalar, dimensions)))?;
+
+        let dimension = |value: &str| value.parse().ok().filter(|value| (1..=4).contains(value));
+        let (rows, columns) = match dimensions.split_once('x') {
+            _ if dimensions.is_empty() => (1, 1),
+            None => (1, dimension(dimensions)?),
+            Some((rows, columns)) => (dimension(rows)?, dimension(columns)?),
+        };
+
+        Some(Type { scalar, rows, columns })
+    }
+}
+
+struct Member<'a> {
+    line: usize,
+    type_name: &'a str,
+    name: &'a str,
+    array_length: Option<u32>,
+    row_major: bool,
+    /// The semantic or register the member is bound to.
+    binding: Option<&'a str>,
+}
+
+struct Parser<'a> {
+    tokens: &'a [Token<'a>],
+    position: usize,
+    structs: Vec<(&'a str, Vec<Member<'a>>)>,
+    reflection: Reflection,
+    has_entry_point: bool,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&'a str> {
+        self.tokens.get(self.position).map(|token| token.text)
+    }
+
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.position)
+            .or(self.tokens.last())
+            .map_or(1, |token| token.line)
+    }
+
+    fn error<T>(&self, message: impl Into<String>) -> Result<T, String> {
+        Err(format!("Line {}: {}", self.line(), message.into()))
+    }
+
+    fn next(&mut self) -> Result<&'a str, String> {
+        match self.peek() {
+            Some(text) => {
+                self.position += 1;
+                Ok(text)
+            }
+            None => self.error("Unexpected end of the shader."),
+        }
+    }
+
+    fn accept(&mut self, text: &str) -> bool {
+        let accepted = self.peek() == Some(text);
+        if accepted {
+            self.position += 1;
+        }
+        accepted
+    }
+
+    fn expect(&mut self, text: &str) -> Result<(), String> {
+        match self.peek() {
+            Some(actual) if actual == text => {
+                self.position += 1;
+                Ok(())
+            }
+            Some(actual) => self.error(format!("Expected '{text}', but found '{actual}'.")),
+            None => self.error(format!("Expected '{text}'.")),
+        }
+    }
+
+    fn identifier(&mut self) -> Result<&'a str, String> {
+        match self.peek() {
+            Some(text) if text.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') => self.next(),
+            Some(text) => self.error(format!("Expected an identifier, but found '{text}'.")),
+            None => self.error("Expected an identifier."),
+        }
+    }
+
+    /// Skips tokens up to and including the given closing token, skipping nested blocks.
+    fn skip_past(&mut self, closing: &str) -> Result<(), String> {
+        loop {
+            match self.next()? {
+                text if text == closing => return Ok(()),
+                "{" => self.skip_past("}")?,
+                "(" => self.skip_past(")")?,
+                "[" => self.skip_past("]")?,
+                _ => {}
+            }
+        }
+    }
+
+    /// Skips the modifiers in front of a type and returns whether the `row_major` modifier was among them.
+    fn modifiers(&mut self) -> bool {
+        let mut row_major = false;
+        while let Some(modifier) = self.peek().filter(|text| MODIFIERS.contains(text)) {
+            row_major = (row_major || modifier == "row_major") && modifier != "column_major";
+            self.position += 1;
+        }
+        row_major
+    }
+
+    fn declaration(&mut self) -> Result<(), String> {
+        if self.accept(";") {
+            return Ok(());
+        }
+
+        if self.accept("struct") {
+            let name = self.identifier()?;
+            let members = self.members()?;
+            self.expect(";")?;
+            self.structs.push((name, members));
+            return Ok(());
+        }
+
+        if self.accept("cbuffer") {
+            return self.constant_buffer();
+        }
+
+        let is_static = self.peek() == Some("static");
+        self.modifiers();
+        let type_name = self.identifier()?;
+        if self.accept("<") {
+            self.skip_past(">")?;
+        }
+
+        let line = self.line();
+        let name = self.identifier()?;
+        if self.accept("(") {
+            return self.function(name);
+        }
+
+        if self.accept("[") {
+            self.skip_past("]")?;
+        }
+
+        let register = match self.accept(":") {
+            true => Some(self.register()?),
+            false => None,
+        };
+
+        match self.peek() {
+            Some("=") => self.skip_past(";")?,
+            _ => self.expect(";")?,
+        }
+
+        let (bindings, register_type) = if type_name.starts_with("Texture") {
+            (&mut self.reflection.textures, 't')
+        } else if type_name.starts_with("Sampler") {
+            (&mut self.reflection.samplers, 's')
+        } else if is_static {
+            return Ok(());
+        } else {
+            return Err(format!(
+                "Line {line}: Global variable '{name}' must be declared in a constant buffer or be static."
+            ));
+        };
+
+        match register {
+            Some((prefix, slot)) if prefix == register_type => {
+                bindings.push(ResourceBinding {
+                    name: name.to_string(),
+                    slot,
+                });
+                Ok(())
+            }
+            _ => Err(format!(
+                "Line {line}: '{name}' must be bound to a register of the form '{register_type}<slot>'."
+            )),
+        }
+    }
+
+    /// Parses `register(<type><slot>)` and returns the register type and slot.
+    fn register(&mut self) -> Result<(char, u32), String> {
+        if self.identifier()? != "register" {
+            return self.error("Expected a register.");
+        }
+
+        self.expect("(")?;
+        let register = self.identifier()?;
+        self.skip_past(")")?;
+
+        let mut characters = register.chars();
+        let register_type = characters.next().unwrap().to_ascii_lowercase();
+        match characters.as_str().parse() {
+            Ok(slot) => Ok((register_type, slot)),
+            Err(_) => self.error(format!("Invalid register '{register}'.")),
+        }
+    }
+
+    /// Parses the members of a struct or constant buffer, including the braces.
+    fn members(&mut self) -> Result<Vec<Member<'a>>, String> {
+        self.expect("{")?;
+        let mut members = vec![];
+        while !self.accept("}") {
+            let row_major = self.modifiers();
+            let line = self.line();
+            let type_name = self.identifier()?;
+            let name = self.identifier()?;
+            let array_length = match self.accept("[") {
+                true => {
+                    let length = self.next()?;
+                    self.expect("]")?;
+                    match length.parse() {
+                        Ok(length) if length > 0 => Some(length),
+                        _ => return self.error(format!("Invalid array length '{length}'.")),
+                    }
+                }
+                false => None,
+            };
+
+            let binding = match self.accept(":") {
+                true => Some(self.identifier()?),
+                false => None,
+            };
+
+            self.expect(";")?;
+            members.push(Member {
+                line,
+                type_name,
+                name,
+                array_length,
+                row_major,
+                binding,
+            });
+        }
+
+        Ok(members)
+    }
+
+    /// Parses a constant buffer and lays out its members according to the HLSL packing rules: Members are packed into
+    /// 16 byte registers without crossing register boundaries, while matrices and arrays start at a new register and
+    /// each array element occupies whole registers.
+    fn constant_buffer(&mut self) -> Result<(), String> {
+        let name = self.identifier()?;
+        let register = match self.accept(":") {
+            true => Some(self.register()?),
+            false => None,
+        };
+
+        let slot = match register {
+            Some(('b', slot)) => slot,
+            _ => {
+                return self.error(format!(
+                    "Constant buffer '{name}' must be bound to a register of the form 'b<slot>'."
+                ))
+            }
+        };
+
+        let mut offset = 0;
+        let mut members = vec![];
+        for member in self.members()? {
+            let Some(member_type) = Type::parse(member.type_name) else {
+                return Err(format!("Line {}: Unsupported type '{}'.", member.line, member.type_name));
+            };
+
+            if member.binding.is_some() {
+                return Err(format!("Line {}: Explicit offsets are not supported.", member.line));
+            }
+
+            let (registers, last_register_size) = match member_type {
+                Type { rows: 1, columns, .. } => (1, columns * 4),
+                Type { rows, columns, .. } if member.row_major => (rows, columns * 4),
+                Type { rows, columns, .. } => (columns, rows * 4),
+            };
+
+            let element_size = (registers - 1) * 16 + last_register_size;
+            let size = (member.array_length.unwrap_or(1) - 1) * registers * 16 + element_size;
+            if registers > 1 || member.array_length.is_some() || offset % 16 + size > 16 {
+                offset = u32::next_multiple_of(offset, 16);
+            }
+
+            members.push(ConstantBufferMember {
+                name: member.name.to_string(),
+                offset,
+                size,
+            });
+            offset += size;
+        }
+
+        self.accept(";");
+        self.reflection.constant_buffers.push(ConstantBufferLayout {
+            name: name.to_string(),
+            slot,
+            size: offset.next_multiple_of(16),
+            members,
+        });
+
+        Ok(())
+    }
+
+    /// Parses a function, recording the parameters of the entry point as the shader's inputs.
+    fn function(&mut self, name: &'a str) -> Result<(), String> {
+        let is_entry_point = name == ENTRY_POINT;
+        let mut inputs = vec![];
+        while !self.accept(")") {
+            let is_output = matches!(self.peek(), Some("out" | "inout"));
+            if is_output {
+                self.position += 1;
+            }
+
+            self.modifiers();
+            let line = self.line();
+            let type_name = self.identifier()?;
+            let parameter = self.identifier()?;
+            let semantic = match self.accept(":") {
+                true => Some(self.identifier()?),
+                false => None,
+            };
+
+            while !matches!(self.peek(), Some("," | ")")) {
+                self.next()?;
+            }
+            self.accept(",");
+
+            if !is_entry_point || is_output {
+                continue;
+            }
+
+            match semantic {
+                Some(semantic) => inputs.extend(input(line, type_name, parameter, semantic)?),
+                None => {
+                    let Some((_, members)) = self.structs.iter().find(|(name, _)| *name == type_name) else {
+                        return Err(format!("Line {line}: Parameter '{parameter}' has no semantic."));
+                    };
+
+                    for member in members {
+                        let Some(semantic) = member.binding else {
+                            return Err(format!("Line {}: Member '{}' has no semantic.", member.line, member.name));
+                        };
+                        inputs.extend(input(member.line, member.type_name, member.name, semantic)?);
+                    }
+                }
+            }
+        }
+
+        if self.accept(":") {
+            self.identifier()?;
+        }
+
+        if !self.accept(";") {
+            self.expect("{")?;
+            self.skip_past("}")?;
+        }
+
+        if is_entry_point {
+            if self.has_entry_point {
+                return self.error(format!("The '{ENTRY_POINT}' function must not be overloaded."));
+            }
+
+            self.has_entry_point = true;
+            self.reflection.inputs = inputs;
+        }
+
+        Ok(())
+    }
+}
+
+/// Creates the input of an entry point parameter or struct member, unless it is a system value.
+fn input(line: usize, type_name: &str, name: &str, semantic: &str) -> Result<Option<ShaderInput>, String> {
+    let semantic = semantic.to_ascii_uppercase();
+    if semantic.starts_with("SV_") {
+        return Ok(None);
+    }
+
+    let input_type = Type::parse(type_name)
+        .filter(|input_type| input_type.rows == 1)
+        .ok_or_else(|| format!("Line {line}: Input '{name}' has the unsupported type '{type_name}'."))?;
+
+    let digits = semantic.len() - semantic.chars().rev().take_while(char::is_ascii_digit).count();
+    Ok(Some(ShaderInput {
+        semantic_index: semantic[digits..].parse().unwrap_or(0),
+        semantic: semantic[..digits].to_string(),
+        scalar_type: input_type.scalar,
+        components: input_type.columns as u8,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(semantic: &str, semantic_index: u32, components: u8) -> ShaderInput {
+        ShaderInput {
+            semantic: semantic.to_string(),
+            semantic_index,
+            scalar_type: ScalarType::Float,
+            components,
+        }
+    }
+
+    fn members(constant_buffer: &ConstantBufferLayout) -> Vec<(&str, u32, u32)> {
+        constant_buffer
+            .members
+            .iter()
+            .map(|member| (member.name.as_str(), member.offset, member.size))
+            .collect()
+    }
+
+    #[test]
+    fn sprite_shaders_are_reflected() {
+        let vertex_shader = reflect(include_str!("../../assets/shaders/sprite.vs.hlsl")).unwrap();
+        assert_eq!(
+            vertex_shader.inputs,
+            [input("POSITION", 0, 2), input("TEXCOORD", 0, 2), input("COLOR", 0, 4)]
+        );
+        assert_eq!(vertex_shader.constant_buffer_slot("PerFrameConstants", 64), Ok(0));
+        assert_eq!(vertex_shader.constant_buffer_slot("PerObjectConstants", 64), Ok(1));
+        assert!(vertex_shader.textures.is_empty() && vertex_shader.samplers.is_empty());
+
+        let pixel_shader = reflect(include_str!("../../assets/shaders/sprite.ps.hlsl")).unwrap();
+        assert_eq!(pixel_shader.inputs, [input("TEXCOORD", 0, 2), input("COLOR", 0, 4)]);
+        assert!(pixel_shader.constant_buffers.is_empty());
+        assert_eq!(pixel_shader.texture_slot("Tex"), Ok(0));
+        assert_eq!(pixel_shader.sampler_slot("TexSampler"), Ok(0));
+    }
+
+    #[test]
+    fn constant_buffers_are_packed() {
+        let source = "
+            cbuffer Constants : register(b3) {
+                float3 Direction; float Intensity; // fits into the first register
+                float2 Offset; float3 Color;       // the vector would cross a register boundary
+                row_major float2x3 Rows; float3x2 Columns;
+                float Weights[3]; bool Enabled;
+            };
+            /* unrelated declarations */
+            static const float Pi = 3.14159;
+            float4 helper(float4 value) { return value * Pi; }
+            float4 main(float4 position : SV_Position, in float2 uv : TexCoord3) : SV_Target { return helper(position); }
+        ";
+
+        let reflection = reflect(source).unwrap();
+        assert_eq!(reflection.inputs, [input("TEXCOORD", 3, 2)]);
+
+        let constant_buffer = &reflection.constant_buffers[0];
+        assert_eq!((constant_buffer.slot, constant_buffer.size), (3, 160));
+        assert_eq!(
+            members(constant_buffer),
+            [
+                ("Direction", 0, 12),
+                ("Intensity", 12, 4),
+                ("Offset", 16, 8),
+                ("Color", 32, 12),
+                ("Rows", 48, 28),
+                ("Columns", 80, 28),
+                ("Weights", 112, 36),
+                ("Enabled", 148, 4),
+            ]
+        );
+    }
+
+    #[test]
+    fn unsupported_declarations_are_rejected() {
+        for (source, message) in [
+            (
+                "float4 main() : SV_Target { return 0; }\nfloat4 Color;",
+                "Line 2: Global variable 'Color' must be declared in a constant buffer or be static.",
+            ),
+            (
+                "Texture2D Tex;\nvoid main() {}",
+                "Line 1: 'Tex' must be bound to a register of the form 't<slot>'.",
+            ),
+            (
+                "SamplerState Sampler : register(t0);",
+                "Line 1: 'Sampler' must be bound to a register of the form 's<slot>'.",
+            ),
+            (
+                "cbuffer Constants { float4 Color; }",
+                "Line 1: Constant buffer 'Constants' must be bound to a register of the form 'b<slot>'.",
+            ),
+            (
+                "cbuffer Constants : register(b0) {\n double Value; }",
+                "Line 2: Unsupported type 'double'.",
+            ),
+            (
+                "struct Input { float2 uv; };\nfloat4 main(Input input) : SV_Target {}",
+                "Line 1: Member 'uv' has no semantic.",
+            ),
+            (
+                "float4 main(float4x4 m : MATRIX) : SV_Target {}",
+                "Line 1: Input 'm' has the unsupported type 'float4x4'.",
+            ),
+            ("float4 helper() { return 0; }", "The shader has no 'main' function."),
+            ("void main() {\n", "Line 1: Unexpected end of the shader."),
+        ] {
+            assert_eq!(reflect(source), Err(message.to_string()), "{source}");
+        }
+    }
+}
@@ -0,0 +1,189 @@
+use super::{pipeline, shader_compiler::ShaderCompiler};
+use crate::{error, log::Category};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
+};
+
+/// The time between two scans of the source directory. Polling is used instead of the operating system's file
+/// change notifications, as it works the same on all platforms and the number of asset files is small.
+const SCAN_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Watches the asset sources during development and recompiles the assets whose files have changed, so that the
+/// resources created from them can be replaced while the game is running. Compile errors are reported without
+/// affecting the previously compiled assets.
+pub struct HotReloader {
+    source: PathBuf,
+    target: PathBuf,
+    shader_compiler: Box<dyn ShaderCompiler>,
+    /// The modification time and size of each file in the source directory as of the last scan.
+    files: BTreeMap<PathBuf, (SystemTime, u64)>,
+    last_scan: Instant,
+}
+
+impl HotReloader {
+    /// Watches the files in the source directory, which are expected to have been compiled into the target directory
+    /// already, as is done by the build script.
+    pub fn new(
+        source: impl Into<PathBuf>,
+        target: impl Into<PathBuf>,
+        shader_compiler: impl ShaderCompiler + 'static,
+    ) -> HotReloader {
+        let source = source.into();
+        HotReloader {
+            files: scan(&source),
+            source,
+            target: target.into(),
+            shader_compiler: Box::new(shader_compiler),
+            last_scan: Instant::now(),
+        }
+    }
+
+    /// Checks for changed files at most every few hundred milliseconds and returns the ids and contents of the assets
+    /// that have been recompiled since the last call.
+    pub fn poll(&mut self) -> Vec<(String, Vec<u8>)> {
+        if self.last_scan.elapsed() < SCAN_INTERVAL {
+            return vec![];
+        }
+
+        self.last_scan = Instant::now();
+        self.reload()
+    }
+
+    fn reload(&mut self) -> Vec<(String, Vec<u8>)> {
+        let files = scan(&self.source);
+        if files == self.files {
+            return vec![];
+        }
+
+        // The archive is not updated, as the running game might have mapped it into memory; the recompiled assets
+        // are returned instead and the archive is updated by the next build.
+        self.files = files;
+        let compilation = match pipeline::compile(&self.source, &self.target, &*self.shader_compiler) {
+            Ok(compilation) => compilation,
+            Err(e) => {
+                error!(Category::Assets, "{e}");
+                return vec![];
+            }
+        };
+
+        for error in &compilation.errors {
+            error!(Category::Assets, "{error}");
+        }
+
+        // Assets that have been removed from the manifest have no output anymore and are skipped.
+        compilation
+            .changed
+            .into_iter()
+            .filter_map(|id| fs::read(self.target.join(&id)).ok().map(|data| (id, data)))
+            .collect()
+    }
+}
+
+/// Finds all files in the directory and its subdirectories, ignoring files that cannot be accessed.
+fn scan(directory: &Path) -> BTreeMap<PathBuf, (SystemTime, u64)> {
+    let mut files = BTreeMap::new();
+    let mut directories = vec![directory.to_path_buf()];
+    while let Some(directory) = directories.pop() {
+        for entry in fs::read_dir(&directory).into_iter().flatten().flatten() {
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+
+            if metadata.is_dir() {
+                directories.push(entry.path());
+            } else if let Ok(modified) = metadata.modified() {
+                files.insert(entry.path(), (modified, metadata.len()));
+            }
+        }
+    }
+
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        super::{level, shader},
+        *,
+    };
+    use crate::{
+        assets::pipeline::{AssetKind, MANIFEST},
+        test_util::directory,
+    };
+
+    /// Pretends to compile shaders into the text of their trailing comment; shaders without one are invalid.
+    fn compile_shader(path: &Path, _: AssetKind) -> Result<Vec<u8>, String> {
+        let source = fs::read_to_string(path).unwrap();
+        match source.split_once("// ") {
+            Some((_, byte_code)) => Ok(byte_code.as_bytes().to_vec()),
+            None => Err("error X3000: syntax error".to_string()),
+        }
+    }
+
+    fn byte_code(compiled: &[u8]) -> &[u8] {
+        shader::load(compiled).unwrap().byte_code
+    }
+
+    #[test]
+    fn changed_assets_are_reloaded() {
+        let (source, target) = (directory("hot-reload-changes-source"), directory("hot-reload-changes-target"));
+        fs::create_dir(source.join("levels")).unwrap();
+        fs::write(source.join("levels/arena.txt"), "name Arena\nsize 100 100\nspawn 0 0").unwrap();
+        fs::write(source.join("sprite.ps.hlsl"), "void main() {} // ps").unwrap();
+        fs::write(source.join(MANIFEST), "level levels/arena.txt\npixel-shader sprite.ps.hlsl").unwrap();
+        pipeline::run(&source, &target, &compile_shader).unwrap();
+
+        let mut reloader = HotReloader::new(&source, &target, compile_shader);
+        assert!(reloader.poll().is_empty(), "Files should not be scanned again immediately.");
+        assert!(reloader.reload().is_empty());
+
+        fs::write(source.join("levels/arena.txt"), "name Changed Arena\nsize 100 100\nspawn 0 0").unwrap();
+        let reloaded = reloader.reload();
+        assert_eq!(reloaded.len(), 1);
+        assert_eq!(reloaded[0].0, "levels/arena.level");
+        assert_eq!(level::load(&reloaded[0].1).unwrap().name, "Changed Arena");
+        assert!(reloader.reload().is_empty());
+
+        fs::write(source.join("sprite.ps.hlsl"), "void main() {} // pixel shader").unwrap();
+        let reloaded = reloader.reload();
+        assert_eq!(reloaded.len(), 1);
+        assert_eq!(reloaded[0].0, "sprite.ps.hlsl");
+        assert_eq!(byte_code(&reloaded[0].1), b"pixel shader");
+
+        fs::remove_dir_all(source).unwrap();
+        fs::remove_dir_all(target).unwrap();
+    }
+
+    #[test]
+    fn compile_errors_keep_the_previous_assets() {
+        let (source, target) = (directory("hot-reload-errors-source"), directory("hot-reload-errors-target"));
+        fs::write(source.join("arena.txt"), "name Arena\nsize 100 100\nspawn 0 0").unwrap();
+        fs::write(source.join("sprite.vs.hlsl"), "void main() {} // vs").unwrap();
+        fs::write(source.join(MANIFEST), "level arena.txt\nvertex-shader sprite.vs.hlsl").unwrap();
+        pipeline::run(&source, &target, &compile_shader).unwrap();
+
+        let mut reloader = HotReloader::new(&source, &target, compile_shader);
+        fs::write(source.join("sprite.vs.hlsl"), "void main() {}").unwrap();
+        fs::write(source.join("arena.txt"), "name Arena\nsize 100 100\nspawn 0 0\nsun 0 0 10").unwrap();
+        let reloaded = reloader.reload();
+        assert_eq!(
+            reloaded.iter().map(|(id, _)| id.as_str()).collect::<Vec<_>>(),
+            ["arena.level"]
+        );
+        assert_eq!(byte_code(&fs::read(target.join("sprite.vs.hlsl")).unwrap()), b"vs");
+
+        // The shader is compiled once it has been fixed; invalid manifests are reported and ignored.
+        fs::write(source.join("sprite.vs.hlsl"), "void main() {} // fixed vs").unwrap();
+        let reloaded = reloader.reload();
+        assert_eq!(reloaded.len(), 1);
+        assert_eq!(byte_code(&reloaded[0].1), b"fixed vs");
+        fs::write(source.join(MANIFEST), "model ship.obj").unwrap();
+        assert!(reloader.reload().is_empty());
+
+        fs::remove_dir_all(source).unwrap();
+        fs::remove_dir_all(target).unwrap();
+    }
+}
@@ -0,0 +1,76 @@
+use super::DecodeError;
+
+mod bmp;
+mod png;
+mod tga;
+
+/// The maximum width and height of decoded images, guarding against allocating huge buffers for corrupt headers.
+const MAX_SIZE: u32 = 16384;
+
+/// An image whose pixels are stored row by row from top to bottom as RGBA8, ready to be uploaded to a texture.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+/// Decodes a PNG, BMP or TGA image. As TGA files have no signature, data that is neither a PNG nor a BMP file is
+/// assumed to be a TGA file.
+pub fn decode(data: &[u8]) -> Result<Image, DecodeError> {
+    if data.starts_with(png::SIGNATURE) {
+        png::decode(data)
+    } else if data.starts_with(b"BM") {
+        bmp::decode(data)
+    } else {
+        tga::decode(data)
+    }
+}
+
+fn check_size(format: &'static str, width: u32, height: u32) -> Result<(), DecodeError> {
+    if width == 0 || height == 0 || width > MAX_SIZE || height > MAX_SIZE {
+        return Err(DecodeError {
+            format,
+            message: "Unsupported image size.",
+        });
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The pixel at the given position of the images in the test corpus, which are all 7x5 pixels large.
+    pub fn pattern(x: u32, y: u32) -> [u8; 4] {
+        [
+            (x * 36) as u8,
+            (y * 60) as u8,
+            ((x * y * 20) % 256) as u8,
+            (255 - x * 10 - y * 20) as u8,
+        ]
+    }
+
+    pub fn assert_pattern(image: &Image, pixel: impl Fn([u8; 4]) -> [u8; 4]) {
+        assert_eq!((image.width, image.height), (7, 5));
+        for y in 0..5 {
+            for x in 0..7 {
+                let offset = ((y * 7 + x) * 4) as usize;
+                assert_eq!(image.pixels[offset..offset + 4], pixel(pattern(x, y)), "pixel ({x}, {y})");
+            }
+        }
+    }
+
+    #[test]
+    fn formats_are_detected() {
+        let opaque = |[r, g, b, _]: [u8; 4]| [r, g, b, 255];
+        assert_pattern(&decode(include_bytes!("../../tests/images/rgba8.png")).unwrap(), |pixel| {
+            pixel
+        });
+        assert_pattern(&decode(include_bytes!("../../tests/images/rgb24.bmp")).unwrap(), opaque);
+        assert_pattern(&decode(include_bytes!("../../tests/images/rgba32.tga")).unwrap(), |pixel| {
+            pixel
+        });
+    }
+}
@@ -0,0 +1,166 @@
+use super::{check_size, Image};
+use crate::assets::{reader::Reader, DecodeError};
+
+const BI_RGB: u32 = 0;
+const BI_BITFIELDS: u32 = 3;
+
+pub fn decode(data: &[u8]) -> Result<Image, DecodeError> {
+    let mut reader = Reader::new(data, "BMP");
+    if &reader.array()? != b"BM" {
+        return Err(reader.error("Invalid signature."));
+    }
+
+    reader.bytes(8)?; // file size and reserved fields
+    let pixel_offset = reader.u32_le()? as usize;
+
+    let header_size = reader.u32_le()?;
+    if !matches!(header_size, 40 | 52 | 56 | 108 | 124) {
+        return Err(reader.error("Unsupported header version."));
+    }
+
+    let width = reader.i32_le()?;
+    let height = reader.i32_le()?;
+    let _planes = reader.u16_le()?;
+    let depth = reader.u16_le()?;
+    let compression = reader.u32_le()?;
+    reader.bytes(12)?; // image size and resolution
+    let palette_size = reader.u32_le()? as usize;
+    reader.bytes(4)?; // the number of important colors
+
+    // Bottom-up images, the default, have a positive height.
+    let top_down = height < 0;
+    let (width, height) = (width.unsigned_abs(), height.unsigned_abs());
+    check_size("BMP", width, height)?;
+
+    let masks = match (compression, depth) {
+        (BI_RGB, 1 | 4 | 8 | 24) => None,
+        // Without bitfields, the 16 and 32 bit formats have no alpha channel.
+        (BI_RGB, 16) => Some([0x7C00, 0x03E0, 0x001F, 0]),
+        (BI_RGB, 32) => Some([0x00FF0000, 0x0000FF00, 0x000000FF, 0]),
+        (BI_BITFIELDS, 16 | 32) => {
+            // Version 1 headers are followed by the color masks, later versions contain them.
+            let masks = [reader.u32_le()?, reader.u32_le()?, reader.u32_le()?];
+            let alpha_mask = if header_size >= 56 { reader.u32_le()? } else { 0 };
+            Some([masks[0], masks[1], masks[2], alpha_mask])
+        }
+        (BI_RGB | BI_BITFIELDS, _) => return Err(reader.error("Unsupported pixel depth.")),
+        _ => return Err(reader.error("Unsupported compression.")),
+    };
+
+    let palette = if depth <= 8 {
+        reader.seek(14 + header_size as usize)?;
+        let palette_size = if palette_size == 0 { 1 << depth } else { palette_size };
+        reader
+            .bytes(palette_size.min(256) * 4)?
+            .chunks_exact(4)
+            .map(|entry| [entry[2], entry[1], entry[0], 255])
+            .collect()
+    } else {
+        vec![]
+    };
+
+    // Rows are padded to multiples of four bytes.
+    let row_size = (width as usize * depth as usize).div_ceil(32) * 4;
+    reader.seek(pixel_offset)?;
+    let rows = reader.bytes(row_size * height as usize)?;
+
+    let mut image = Image {
+        width,
+        height,
+        pixels: vec![0; (width * height * 4) as usize],
+    };
+
+    for (row_index, row) in rows.chunks_exact(row_size).enumerate() {
+        let y = if top_down {
+            row_index as u32
+        } else {
+            height - 1 - row_index as u32
+        };
+
+        for x in 0..width as usize {
+            let rgba = match (depth, masks) {
+                (24, _) => [row[x * 3 + 2], row[x * 3 + 1], row[x * 3], 255],
+                (16, Some(masks)) => from_masks(u16::from_le_bytes([row[x * 2], row[x * 2 + 1]]) as u32, masks),
+                (32, Some(masks)) => from_masks(
+                    u32::from_le_bytes([row[x * 4], row[x * 4 + 1], row[x * 4 + 2], row[x * 4 + 3]]),
+                    masks,
+                ),
+                _ => {
+                    let bit = x * depth as usize;
+                    let index = (row[bit / 8] >> (8 - depth as usize - bit % 8)) & ((1 << depth) - 1) as u8;
+                    *palette
+                        .get(index as usize)
+                        .ok_or(reader.error("Palette index out of range."))?
+                }
+            };
+
+            let offset = ((y * width + x as u32) * 4) as usize;
+            image.pixels[offset..offset + 4].copy_from_slice(&rgba);
+        }
+    }
+
+    Ok(image)
+}
+
+/// Extracts the channels from a pixel and scales them to 8 bits. Without an alpha mask, the pixel is opaque.
+fn from_masks(pixel: u32, masks: [u32; 4]) -> [u8; 4] {
+    masks.map(|mask| {
+        if mask == 0 {
+            return 255;
+        }
+
+        let value = (pixel & mask) >> mask.trailing_zeros();
+        let max = mask >> mask.trailing_zeros();
+        (value as u64 * 255 / max as u64) as u8
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::tests::assert_pattern, *};
+
+    macro_rules! corpus {
+        ($file:literal) => {
+            decode(include_bytes!(concat!("../../../tests/images/", $file)))
+        };
+    }
+
+    #[test]
+    fn true_color_images_are_decoded() {
+        assert_pattern(&corpus!("rgb24.bmp").unwrap(), |[r, g, b, _]| [r, g, b, 255]);
+        assert_pattern(&corpus!("rgb32.bmp").unwrap(), |[r, g, b, _]| [r, g, b, 255]);
+        assert_pattern(&corpus!("rgba32_bitfields_top_down.bmp").unwrap(), |pixel| pixel);
+
+        let five_bits = |channel: u8| ((channel >> 3) as u32 * 255 / 31) as u8;
+        assert_pattern(&corpus!("rgb16.bmp").unwrap(), |[r, g, b, _]| {
+            [five_bits(r), five_bits(g), five_bits(b), 255]
+        });
+    }
+
+    #[test]
+    fn palettes_are_resolved() {
+        // The palettes contain the image's red values as gray levels.
+        let palette = |bits: u32| {
+            move |[r, ..]: [u8; 4]| {
+                let gray = (((r as u32) >> (8 - bits)) * 255 / ((1 << bits) - 1)) as u8;
+                [gray, gray, gray, 255]
+            }
+        };
+
+        assert_pattern(&corpus!("palette1.bmp").unwrap(), palette(1));
+        assert_pattern(&corpus!("palette4.bmp").unwrap(), palette(4));
+        assert_pattern(&corpus!("palette8.bmp").unwrap(), palette(8));
+    }
+
+    #[test]
+    fn malformed_files_are_rejected() {
+        for (file, message) in [
+            (corpus!("bad_header.bmp"), "Unsupported header version."),
+            (corpus!("bad_palette_index.bmp"), "Palette index out of range."),
+            (corpus!("rle8.bmp"), "Unsupported compression."),
+            (corpus!("truncated.bmp"), "Unexpected end of data."),
+        ] {
+            assert_eq!(file.map_err(|error| error.message), Err(message));
+        }
+    }
+}
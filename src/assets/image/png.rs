@@ -0,0 +1,386 @@
+use super::{check_size, Image};
+use crate::assets::{checksum::crc32, inflate::decompress_zlib, reader::Reader, DecodeError};
+
+pub const SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+// The starting position and the spacing of the pixels of the seven Adam7 interlacing passes.
+const ADAM7_PASSES: [(u32, u32, u32, u32); 7] = [
+    (0, 0, 8, 8),
+    (4, 0, 8, 8),
+    (0, 4, 4, 8),
+    (2, 0, 4, 4),
+    (0, 2, 2, 4),
+    (1, 0, 2, 2),
+    (0, 1, 1, 2),
+];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ColorType {
+    Gray,
+    Rgb,
+    Palette,
+    GrayAlpha,
+    Rgba,
+}
+
+struct Header {
+    width: u32,
+    height: u32,
+    bit_depth: u8,
+    color_type: ColorType,
+    interlaced: bool,
+}
+
+/// The information required to turn the samples of a pixel into an RGBA8 color.
+struct Colors<'a> {
+    header: &'a Header,
+    palette: &'a [u8],
+    // The alpha values of the palette entries or the 16 bit RGB/gray values of the transparent color.
+    transparency: &'a [u8],
+}
+
+pub fn decode(data: &[u8]) -> Result<Image, DecodeError> {
+    let mut reader = Reader::new(data, "PNG");
+    if reader.bytes(SIGNATURE.len())? != SIGNATURE {
+        return Err(reader.error("Invalid signature."));
+    }
+
+    let mut header = None;
+    let mut palette: &[u8] = &[];
+    let mut transparency: &[u8] = &[];
+    let mut image_data = Vec::new();
+
+    loop {
+        let length = reader.u32_be()? as usize;
+        let chunk = reader.bytes(length.checked_add(4).ok_or(reader.error("Invalid chunk length."))?)?;
+        if reader.u32_be()? != crc32(chunk) {
+            return Err(reader.error("Chunk checksum mismatch."));
+        }
+
+        let (chunk_type, chunk_data) = chunk.split_at(4);
+        if header.is_none() && chunk_type != b"IHDR" {
+            return Err(reader.error("The first chunk must be the image header."));
+        }
+
+        match chunk_type {
+            b"IHDR" => header = Some(decode_header(&reader, chunk_data)?),
+            b"PLTE" => palette = chunk_data,
+            b"tRNS" => transparency = chunk_data,
+            b"IDAT" => image_data.extend_from_slice(chunk_data),
+            b"IEND" => break,
+            // Unknown chunks can be ignored unless they're critical, i.e. start with an uppercase letter.
+            _ if chunk_type[0].is_ascii_uppercase() => return Err(reader.error("Unsupported critical chunk.")),
+            _ => {}
+        }
+    }
+
+    let header = header.ok_or(reader.error("Missing image header."))?;
+    if header.color_type == ColorType::Palette && (palette.is_empty() || !palette.len().is_multiple_of(3)) {
+        return Err(reader.error("Missing or invalid palette."));
+    }
+
+    let scanlines = decompress_zlib(&image_data)?;
+    let colors = Colors {
+        header: &header,
+        palette,
+        transparency,
+    };
+
+    let mut image = Image {
+        width: header.width,
+        height: header.height,
+        pixels: vec![0; (header.width * header.height * 4) as usize],
+    };
+
+    if header.interlaced {
+        let mut scanlines = &scanlines[..];
+        for (x, y, dx, dy) in ADAM7_PASSES {
+            let width = header.width.saturating_sub(x).div_ceil(dx);
+            let height = header.height.saturating_sub(y).div_ceil(dy);
+            if width != 0 && height != 0 {
+                let size = unfilter_size(&header, width, height);
+                let pass = scanlines.get(..size).ok_or(reader.error("Not enough image data."))?;
+                decode_pass(&reader, &colors, pass, (x, y, dx, dy), width, &mut image)?;
+                scanlines = &scanlines[size..];
+            }
+        }
+    } else {
+        let (width, height) = (header.width, header.height);
+        let pass = scanlines
+            .get(..unfilter_size(&header, width, height))
+            .ok_or(reader.error("Not enough image data."))?;
+        decode_pass(&reader, &colors, pass, (0, 0, 1, 1), width, &mut image)?;
+    }
+
+    Ok(image)
+}
+
+fn decode_header(reader: &Reader, data: &[u8]) -> Result<Header, DecodeError> {
+    let mut header = Reader::new(data, "PNG");
+    let width = header.u32_be()?;
+    let height = header.u32_be()?;
+    let [bit_depth, color_type, compression, filter, interlace] = header.array()?;
+    check_size("PNG", width, height)?;
+
+    let (color_type, bit_depths): (_, &[u8]) = match color_type {
+        0 => (ColorType::Gray, &[1, 2, 4, 8, 16]),
+        2 => (ColorType::Rgb, &[8, 16]),
+        3 => (ColorType::Palette, &[1, 2, 4, 8]),
+        4 => (ColorType::GrayAlpha, &[8, 16]),
+        6 => (ColorType::Rgba, &[8, 16]),
+        _ => return Err(reader.error("Invalid color type.")),
+    };
+
+    if !bit_depths.contains(&bit_depth) {
+        return Err(reader.error("Invalid bit depth for the color type."));
+    }
+
+    if compression != 0 || filter != 0 || interlace > 1 {
+        return Err(reader.error("Unsupported compression, filter, or interlace method."));
+    }
+
+    Ok(Header {
+        width,
+        height,
+        bit_depth,
+        color_type,
+        interlaced: interlace == 1,
+    })
+}
+
+fn bits_per_pixel(header: &Header) -> usize {
+    let channels = match header.color_type {
+        ColorType::Gray | ColorType::Palette => 1,
+        ColorType::GrayAlpha => 2,
+        ColorType::Rgb => 3,
+        ColorType::Rgba => 4,
+    };
+
+    channels * header.bit_depth as usize
+}
+
+/// Gets the size of the filtered scanlines of a pass, each of which starts with the filter type.
+fn unfilter_size(header: &Header, width: u32, height: u32) -> usize {
+    (1 + (width as usize * bits_per_pixel(header)).div_ceil(8)) * height as usize
+}
+
+fn decode_pass(
+    reader: &Reader,
+    colors: &Colors,
+    data: &[u8],
+    (x, y, dx, dy): (u32, u32, u32, u32),
+    width: u32,
+    image: &mut Image,
+) -> Result<(), DecodeError> {
+    let bits_per_pixel = bits_per_pixel(colors.header);
+    let row_size = (width as usize * bits_per_pixel).div_ceil(8);
+    // Filters operate on bytes, referring to the corresponding byte of the previous pixel.
+    let pixel_size = (bits_per_pixel / 8).max(1);
+
+    let mut previous = vec![0; row_size];
+    let mut current = vec![0; row_size];
+    for (row, scanline) in data.chunks_exact(row_size + 1).enumerate() {
+        current.copy_from_slice(&scanline[1..]);
+        unfilter(reader, scanline[0], &mut current, &previous, pixel_size)?;
+
+        for column in 0..width {
+            let color = colors.pixel(&current, column as usize)?;
+            let offset = (((y + row as u32 * dy) * image.width + x + column * dx) * 4) as usize;
+            image.pixels[offset..offset + 4].copy_from_slice(&color);
+        }
+
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    Ok(())
+}
+
+fn unfilter(reader: &Reader, filter: u8, row: &mut [u8], previous: &[u8], pixel_size: usize) -> Result<(), DecodeError> {
+    for i in 0..row.len() {
+        let left = if i >= pixel_size { row[i - pixel_size] } else { 0 };
+        let up = previous[i];
+        let up_left = if i >= pixel_size { previous[i - pixel_size] } else { 0 };
+
+        row[i] = row[i].wrapping_add(match filter {
+            0 => 0,
+            1 => left,
+            2 => up,
+            3 => ((left as u16 + up as u16) / 2) as u8,
+            4 => paeth(left, up, up_left),
+            _ => return Err(reader.error("Invalid filter type.")),
+        });
+    }
+
+    Ok(())
+}
+
+fn paeth(left: u8, up: u8, up_left: u8) -> u8 {
+    let estimate = left as i16 + up as i16 - up_left as i16;
+    let (distance_left, distance_up, distance_up_left) = (
+        (estimate - left as i16).abs(),
+        (estimate - up as i16).abs(),
+        (estimate - up_left as i16).abs(),
+    );
+
+    if distance_left <= distance_up && distance_left <= distance_up_left {
+        left
+    } else if distance_up <= distance_up_left {
+        up
+    } else {
+        up_left
+    }
+}
+
+impl Colors<'_> {
+    fn pixel(&self, row: &[u8], column: usize) -> Result<[u8; 4], DecodeError> {
+        let bit_depth = self.header.bit_depth as usize;
+        let sample = |index: usize| -> u16 {
+            match bit_depth {
+                16 => u16::from_be_bytes([row[index * 2], row[index * 2 + 1]]),
+                8 => row[index] as u16,
+                _ => {
+                    let bit = index * bit_depth;
+                    let shift = 8 - bit_depth - bit % 8;
+                    ((row[bit / 8] >> shift) & ((1 << bit_depth) - 1)) as u16
+                }
+            }
+        };
+
+        // Scales samples to 8 bits; for 16 bit samples, that's the most significant byte.
+        let scale = |value: u16| -> u8 {
+            match bit_depth {
+                16 => (value >> 8) as u8,
+                _ => (value as u32 * 255 / ((1 << bit_depth) - 1)) as u8,
+            }
+        };
+
+        let transparent_color = |index: usize| {
+            self.transparency
+                .get(index * 2..index * 2 + 2)
+                .map(|value| u16::from_be_bytes([value[0], value[1]]))
+        };
+
+        Ok(match self.header.color_type {
+            ColorType::Gray => {
+                let gray = sample(column);
+                let alpha = if transparent_color(0) == Some(gray) { 0 } else { 255 };
+                [scale(gray), scale(gray), scale(gray), alpha]
+            }
+            ColorType::GrayAlpha => {
+                let gray = scale(sample(column * 2));
+                [gray, gray, gray, scale(sample(column * 2 + 1))]
+            }
+            ColorType::Rgb => {
+                let rgb = [0, 1, 2].map(|channel| sample(column * 3 + channel));
+                let transparent = [0, 1, 2].map(transparent_color) == rgb.map(Some);
+                let [r, g, b] = rgb.map(scale);
+                [r, g, b, if transparent { 0 } else { 255 }]
+            }
+            ColorType::Rgba => [0, 1, 2, 3].map(|channel| scale(sample(column * 4 + channel))),
+            ColorType::Palette => {
+                let index = sample(column) as usize;
+                let Some(&[r, g, b]) = self.palette.get(index * 3..index * 3 + 3) else {
+                    return Err(DecodeError {
+                        format: "PNG",
+                        message: "Palette index out of range.",
+                    });
+                };
+                [r, g, b, *self.transparency.get(index).unwrap_or(&255)]
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::tests::assert_pattern, *};
+
+    macro_rules! corpus {
+        ($file:literal) => {
+            decode(include_bytes!(concat!("../../../tests/images/", $file)))
+        };
+    }
+
+    fn gray(bits: u32) -> impl Fn([u8; 4]) -> [u8; 4] {
+        move |[r, ..]| {
+            let gray = ((r as u32 >> (8 - bits)) * 255 / ((1 << bits) - 1)) as u8;
+            [gray, gray, gray, 255]
+        }
+    }
+
+    #[test]
+    fn color_types_and_bit_depths_are_decoded() {
+        assert_pattern(&corpus!("rgba8.png").unwrap(), |pixel| pixel);
+        assert_pattern(&corpus!("rgba16.png").unwrap(), |pixel| pixel);
+        assert_pattern(&corpus!("rgb8.png").unwrap(), |[r, g, b, _]| [r, g, b, 255]);
+        assert_pattern(&corpus!("rgb16.png").unwrap(), |[r, g, b, _]| [r, g, b, 255]);
+        assert_pattern(&corpus!("gray_alpha8.png").unwrap(), |[r, _, _, a]| [r, r, r, a]);
+        for (file, bits) in [
+            (corpus!("gray1.png"), 1),
+            (corpus!("gray2.png"), 2),
+            (corpus!("gray4.png"), 4),
+            (corpus!("gray8.png"), 8),
+            (corpus!("gray16.png"), 8),
+        ] {
+            assert_pattern(&file.unwrap(), gray(bits));
+        }
+    }
+
+    #[test]
+    fn palettes_are_resolved() {
+        // The palettes contain the image's red values as gray levels, with alpha values for the first entries.
+        let palette = |bits: u32| {
+            move |[r, ..]: [u8; 4]| {
+                let index = r >> (8 - bits);
+                let gray = ((index as u32) * 255 / ((1 << bits) - 1)) as u8;
+                [gray, gray, gray, if index < 2 { 128 } else { 255 }]
+            }
+        };
+
+        assert_pattern(&corpus!("palette1.png").unwrap(), palette(1));
+        assert_pattern(&corpus!("palette2.png").unwrap(), palette(2));
+        assert_pattern(&corpus!("palette4.png").unwrap(), palette(4));
+        assert_pattern(&corpus!("palette8.png").unwrap(), palette(8));
+    }
+
+    #[test]
+    fn transparent_colors_are_applied() {
+        // The color of the pixel (1, 2) is marked transparent.
+        let image = corpus!("rgb8_transparent.png").unwrap();
+        for (x, y) in [(0, 0), (1, 2), (6, 4)] {
+            let alpha = image.pixels[((y * 7 + x) * 4 + 3) as usize];
+            assert_eq!(alpha, if (x, y) == (1, 2) { 0 } else { 255 });
+        }
+    }
+
+    #[test]
+    fn interlaced_images_are_decoded() {
+        assert_pattern(&corpus!("rgba8_interlaced.png").unwrap(), |pixel| pixel);
+        assert_pattern(&corpus!("gray2_interlaced.png").unwrap(), gray(2));
+    }
+
+    #[test]
+    fn all_deflate_block_types_are_decoded() {
+        assert_pattern(&corpus!("rgba8_stored.png").unwrap(), |pixel| pixel);
+        assert_pattern(&corpus!("rgba8_fixed.png").unwrap(), |pixel| pixel);
+        assert_pattern(&corpus!("rgba8_split.png").unwrap(), |pixel| pixel);
+    }
+
+    #[test]
+    fn malformed_files_are_rejected() {
+        for (file, message) in [
+            (corpus!("bad_signature.png"), "Invalid signature."),
+            (corpus!("bad_crc.png"), "Chunk checksum mismatch."),
+            (corpus!("bad_filter.png"), "Invalid filter type."),
+            (corpus!("bad_palette_index.png"), "Palette index out of range."),
+            (corpus!("bad_size.png"), "Unsupported image size."),
+            (corpus!("missing_data.png"), "Not enough image data."),
+            (corpus!("missing_end.png"), "Unexpected end of data."),
+            (corpus!("truncated.png"), "Unexpected end of data."),
+        ] {
+            assert_eq!(file.map_err(|error| error.message), Err(message));
+        }
+
+        assert_eq!(corpus!("bad_adler.png").unwrap_err().message, "Checksum mismatch.");
+    }
+}
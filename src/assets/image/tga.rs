@@ -0,0 +1,190 @@
+use super::{check_size, Image};
+use crate::assets::{reader::Reader, DecodeError};
+
+pub fn decode(data: &[u8]) -> Result<Image, DecodeError> {
+    let mut reader = Reader::new(data, "TGA");
+    let id_length = reader.u8()?;
+    let color_map_type = reader.u8()?;
+    let image_type = reader.u8()?;
+    let color_map_start = reader.u16_le()? as usize;
+    let color_map_length = reader.u16_le()? as usize;
+    let color_map_depth = reader.u8()?;
+    reader.bytes(4)?; // the image's origin on the screen
+    let width = reader.u16_le()? as u32;
+    let height = reader.u16_le()? as u32;
+    let depth = reader.u8()?;
+    let descriptor = reader.u8()?;
+    reader.bytes(id_length as usize)?;
+    check_size("TGA", width, height)?;
+
+    let has_alpha = descriptor & 0x0F != 0;
+    let (color_mapped, compressed) = match image_type {
+        1 => (true, false),
+        2 | 3 => (false, false),
+        9 => (true, true),
+        10 | 11 => (false, true),
+        _ => return Err(reader.error("Unsupported image type.")),
+    };
+
+    let grayscale = image_type & 3 == 3;
+    let valid_depth = match (color_mapped, grayscale) {
+        (true, _) => depth == 8 && color_map_type == 1,
+        (false, true) => depth == 8,
+        (false, false) => matches!(depth, 15 | 16 | 24 | 32),
+    };
+
+    if !valid_depth {
+        return Err(reader.error("Unsupported pixel depth."));
+    }
+
+    let color_map = match color_map_type {
+        0 => vec![],
+        1 => {
+            let entry_size = bytes_per_pixel(&reader, color_map_depth)?;
+            let entries = reader.bytes(color_map_length * entry_size)?;
+            entries
+                .chunks_exact(entry_size)
+                .map(|entry| color(entry, has_alpha))
+                .collect()
+        }
+        _ => return Err(reader.error("Invalid color map type.")),
+    };
+
+    let pixel_size = bytes_per_pixel(&reader, depth)?;
+    let pixel_count = (width * height) as usize;
+    let mut pixels = Vec::with_capacity(pixel_count * pixel_size);
+    if compressed {
+        // Each packet consists of a header and either a single pixel that is repeated or a run of raw pixels.
+        while pixels.len() < pixel_count * pixel_size {
+            let header = reader.u8()?;
+            let count = (header & 0x7F) as usize + 1;
+            if pixels.len() + count * pixel_size > pixel_count * pixel_size {
+                return Err(reader.error("Run-length encoded packet exceeds the image."));
+            }
+
+            if header & 0x80 != 0 {
+                let pixel = reader.bytes(pixel_size)?;
+                for _ in 0..count {
+                    pixels.extend_from_slice(pixel);
+                }
+            } else {
+                pixels.extend_from_slice(reader.bytes(count * pixel_size)?);
+            }
+        }
+    } else {
+        pixels.extend_from_slice(reader.bytes(pixel_count * pixel_size)?);
+    }
+
+    let mut image = Image {
+        width,
+        height,
+        pixels: vec![0; pixel_count * 4],
+    };
+
+    let (right_to_left, top_to_bottom) = (descriptor & 0x10 != 0, descriptor & 0x20 != 0);
+    for (i, pixel) in pixels.chunks_exact(pixel_size).enumerate() {
+        let rgba = if color_mapped {
+            let index = (pixel[0] as usize)
+                .checked_sub(color_map_start)
+                .ok_or(reader.error("Color map index out of range."))?;
+            *color_map.get(index).ok_or(reader.error("Color map index out of range."))?
+        } else if grayscale {
+            [pixel[0], pixel[0], pixel[0], 255]
+        } else {
+            color(pixel, has_alpha)
+        };
+
+        let (mut x, mut y) = (i as u32 % width, i as u32 / width);
+        if right_to_left {
+            x = width - 1 - x;
+        }
+        if !top_to_bottom {
+            y = height - 1 - y;
+        }
+
+        let offset = ((y * width + x) * 4) as usize;
+        image.pixels[offset..offset + 4].copy_from_slice(&rgba);
+    }
+
+    Ok(image)
+}
+
+fn bytes_per_pixel(reader: &Reader, depth: u8) -> Result<usize, DecodeError> {
+    match depth {
+        8 => Ok(1),
+        15 | 16 => Ok(2),
+        24 => Ok(3),
+        32 => Ok(4),
+        _ => Err(reader.error("Unsupported pixel depth.")),
+    }
+}
+
+/// Converts a BGR(A) color or a 16 bit ARGB1555 color to RGBA8. 8 bit colors are only valid as color map indices
+/// or grayscale values and are handled by the caller.
+fn color(pixel: &[u8], has_alpha: bool) -> [u8; 4] {
+    match *pixel {
+        [low, high] => {
+            let value = u16::from_le_bytes([low, high]);
+            let channel = |shift: u16| {
+                let channel = ((value >> shift) & 0x1F) as u8;
+                (channel << 3) | (channel >> 2)
+            };
+            let alpha = if !has_alpha || value & 0x8000 != 0 { 255 } else { 0 };
+            [channel(10), channel(5), channel(0), alpha]
+        }
+        [b, g, r] => [r, g, b, 255],
+        [b, g, r, a] => [r, g, b, if has_alpha { a } else { 255 }],
+        _ => [pixel[0], pixel[0], pixel[0], 255],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::tests::assert_pattern, *};
+
+    macro_rules! corpus {
+        ($file:literal) => {
+            decode(include_bytes!(concat!("../../../tests/images/", $file)))
+        };
+    }
+
+    #[test]
+    fn true_color_images_are_decoded() {
+        assert_pattern(&corpus!("rgba32.tga").unwrap(), |pixel| pixel);
+        assert_pattern(&corpus!("rgba32_top_down.tga").unwrap(), |pixel| pixel);
+        assert_pattern(&corpus!("rgb24.tga").unwrap(), |[r, g, b, _]| [r, g, b, 255]);
+        assert_pattern(&corpus!("rgba32_rle.tga").unwrap(), |pixel| pixel);
+    }
+
+    #[test]
+    fn sixteen_bit_images_are_decoded() {
+        let expand = |channel: u8| (channel & 0xF8) | (channel >> 5);
+        assert_pattern(&corpus!("argb16.tga").unwrap(), |[r, g, b, a]| {
+            [expand(r), expand(g), expand(b), if a >= 128 { 255 } else { 0 }]
+        });
+    }
+
+    #[test]
+    fn grayscale_and_color_mapped_images_are_decoded() {
+        assert_pattern(&corpus!("gray8.tga").unwrap(), |[r, ..]| [r, r, r, 255]);
+        assert_pattern(&corpus!("gray8_rle.tga").unwrap(), |[r, ..]| [r, r, r, 255]);
+        // The color map contains the image's red values as gray levels.
+        assert_pattern(&corpus!("color_mapped8.tga").unwrap(), |[r, ..]| [r, r, r, 255]);
+    }
+
+    #[test]
+    fn malformed_files_are_rejected() {
+        for (file, message) in [
+            (corpus!("bad_type.tga"), "Unsupported image type."),
+            (corpus!("bad_depth.tga"), "Unsupported pixel depth."),
+            (corpus!("bad_color_map_index.tga"), "Color map index out of range."),
+            (corpus!("overlong_rle.tga"), "Run-length encoded packet exceeds the image."),
+            (corpus!("truncated.tga"), "Unexpected end of data."),
+            (corpus!("truncated_rle.tga"), "Unexpected end of data."),
+        ] {
+            assert_eq!(file.map_err(|error| error.message), Err(message));
+        }
+
+        assert_eq!(decode(&[]).map_err(|error| error.message), Err("Unexpected end of data."));
+    }
+}
@@ -0,0 +1,322 @@
+use super::{checksum::adler32, DecodeError};
+
+const MAX_BITS: usize = 15;
+pub(super) const LENGTH_BASES: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258,
+];
+pub(super) const LENGTH_EXTRA_BITS: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+pub(super) const DISTANCE_BASES: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145, 8193,
+    12289, 16385, 24577,
+];
+pub(super) const DISTANCE_EXTRA_BITS: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13,
+];
+// The order in which the code lengths of the code length alphabet are stored in dynamic blocks.
+const CODE_LENGTH_ORDER: [usize; 19] = [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+
+/// Decompresses a zlib stream (RFC 1950), verifying its checksum.
+pub fn decompress_zlib(data: &[u8]) -> Result<Vec<u8>, DecodeError> {
+    let error = |message| DecodeError { format: "zlib", message };
+
+    let [method, flags, ..] = *data else {
+        return Err(error("Unexpected end of data."));
+    };
+
+    if method & 0x0F != 8 || method >> 4 > 7 {
+        return Err(error("Unsupported compression method."));
+    }
+
+    if !(method as u16 * 256 + flags as u16).is_multiple_of(31) {
+        return Err(error("Corrupt header."));
+    }
+
+    if flags & 0x20 != 0 {
+        return Err(error("Preset dictionaries are not supported."));
+    }
+
+    let mut bits = Bits::new(&data[2..]);
+    let decompressed = inflate_from(&mut bits)?;
+
+    let checksum = bits.aligned_bytes(4)?;
+    if u32::from_be_bytes([checksum[0], checksum[1], checksum[2], checksum[3]]) != adler32(&decompressed) {
+        return Err(error("Checksum mismatch."));
+    }
+
+    Ok(decompressed)
+}
+
+/// Decompresses raw deflate data (RFC 1951).
+pub fn inflate(data: &[u8]) -> Result<Vec<u8>, DecodeError> {
+    inflate_from(&mut Bits::new(data))
+}
+
+fn inflate_from(bits: &mut Bits) -> Result<Vec<u8>, DecodeError> {
+    let mut output = Vec::new();
+
+    loop {
+        let is_final = bits.read(1)? == 1;
+        match bits.read(2)? {
+            0 => {
+                let header = bits.aligned_bytes(4)?;
+                let length = u16::from_le_bytes([header[0], header[1]]);
+                if length != !u16::from_le_bytes([header[2], header[3]]) {
+                    return Err(error("Corrupt stored block length."));
+                }
+
+                output.extend_from_slice(bits.aligned_bytes(length as usize)?);
+            }
+            1 => {
+                let mut lengths = [0; 288];
+                lengths[..144].fill(8);
+                lengths[144..256].fill(9);
+                lengths[256..280].fill(7);
+                lengths[280..].fill(8);
+
+                let literals = Huffman::new(&lengths)?;
+                let distances = Huffman::new(&[5; 30])?;
+                inflate_block(bits, &mut output, &literals, &distances)?;
+            }
+            2 => {
+                let (literals, distances) = read_dynamic_codes(bits)?;
+                inflate_block(bits, &mut output, &literals, &distances)?;
+            }
+            _ => return Err(error("Invalid block type.")),
+        }
+
+        if is_final {
+            return Ok(output);
+        }
+    }
+}
+
+fn read_dynamic_codes(bits: &mut Bits) -> Result<(Huffman, Huffman), DecodeError> {
+    let literal_count = bits.read(5)? as usize + 257;
+    let distance_count = bits.read(5)? as usize + 1;
+    let code_length_count = bits.read(4)? as usize + 4;
+    if literal_count > 286 || distance_count > 30 {
+        return Err(error("Too many codes."));
+    }
+
+    let mut code_length_lengths = [0; 19];
+    for &symbol in &CODE_LENGTH_ORDER[..code_length_count] {
+        code_length_lengths[symbol] = bits.read(3)? as u8;
+    }
+    let code_lengths = Huffman::new(&code_length_lengths)?;
+
+    let mut lengths = Vec::with_capacity(literal_count + distance_count);
+    while lengths.len() < literal_count + distance_count {
+        let (length, repetitions) = match code_lengths.decode(bits)? {
+            symbol @ 0..=15 => (symbol as u8, 1),
+            16 => (
+                *lengths.last().ok_or(error("Repeated code length without a previous one."))?,
+                3 + bits.read(2)?,
+            ),
+            17 => (0, 3 + bits.read(3)?),
+            _ => (0, 11 + bits.read(7)?),
+        };
+
+        if lengths.len() + repetitions as usize > literal_count + distance_count {
+            return Err(error("Too many code lengths."));
+        }
+
+        lengths.extend((0..repetitions).map(|_| length));
+    }
+
+    if lengths[256] == 0 {
+        return Err(error("Missing end-of-block code."));
+    }
+
+    Ok((
+        Huffman::new(&lengths[..literal_count])?,
+        Huffman::new(&lengths[literal_count..])?,
+    ))
+}
+
+fn inflate_block(bits: &mut Bits, output: &mut Vec<u8>, literals: &Huffman, distances: &Huffman) -> Result<(), DecodeError> {
+    loop {
+        let symbol = literals.decode(bits)? as usize;
+        match symbol {
+            0..=255 => output.push(symbol as u8),
+            256 => return Ok(()),
+            257..=285 => {
+                let index = symbol - 257;
+                let length = LENGTH_BASES[index] as usize + bits.read(LENGTH_EXTRA_BITS[index])? as usize;
+
+                let index = distances.decode(bits)? as usize;
+                if index >= DISTANCE_BASES.len() {
+                    return Err(error("Invalid distance code."));
+                }
+
+                let distance = DISTANCE_BASES[index] as usize + bits.read(DISTANCE_EXTRA_BITS[index])? as usize;
+                if distance > output.len() {
+                    return Err(error("Distance refers to data before the start of the output."));
+                }
+
+                // The referenced data may overlap with the copied data, so it has to be copied byte by byte.
+                let start = output.len() - distance;
+                for i in 0..length {
+                    output.push(output[start + i]);
+                }
+            }
+            _ => return Err(error("Invalid length code.")),
+        }
+    }
+}
+
+fn error(message: &'static str) -> DecodeError {
+    DecodeError {
+        format: "deflate",
+        message,
+    }
+}
+
+/// Reads the bits of deflate data starting with the least significant bit of each byte.
+struct Bits<'a> {
+    data: &'a [u8],
+    position: usize,
+    bit_buffer: u32,
+    bit_count: u8,
+}
+
+impl<'a> Bits<'a> {
+    fn new(data: &'a [u8]) -> Bits<'a> {
+        Bits {
+            data,
+            position: 0,
+            bit_buffer: 0,
+            bit_count: 0,
+        }
+    }
+
+    fn read(&mut self, count: u8) -> Result<u32, DecodeError> {
+        while self.bit_count < count {
+            let byte = *self.data.get(self.position).ok_or(error("Unexpected end of data."))?;
+            self.position += 1;
+            self.bit_buffer |= (byte as u32) << self.bit_count;
+            self.bit_count += 8;
+        }
+
+        let value = self.bit_buffer & ((1u64 << count) - 1) as u32;
+        self.bit_buffer >>= count;
+        self.bit_count -= count;
+        Ok(value)
+    }
+
+    /// Discards the remaining bits of the current byte and reads the following bytes.
+    fn aligned_bytes(&mut self, count: usize) -> Result<&'a [u8], DecodeError> {
+        self.bit_buffer = 0;
+        self.bit_count = 0;
+
+        let bytes = self
+            .data
+            .get(self.position..self.position + count)
+            .ok_or(error("Unexpected end of data."))?;
+        self.position += count;
+        Ok(bytes)
+    }
+}
+
+/// A canonical Huffman code, decoded bit by bit: Codes of the same length are consecutive numbers, so it suffices to
+/// know how many codes there are of each length and which symbols they belong to, ordered by code.
+struct Huffman {
+    counts: [u16; MAX_BITS + 1],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Result<Huffman, DecodeError> {
+        let mut counts = [0; MAX_BITS + 1];
+        for &length in lengths {
+            counts[length as usize] += 1;
+        }
+
+        // Each code length halves the number of available codes; more codes than available cannot be decoded.
+        let mut available = 1i32;
+        for &count in &counts[1..] {
+            available = available * 2 - count as i32;
+            if available < 0 {
+                return Err(error("Over-subscribed Huffman code."));
+            }
+        }
+
+        let mut offsets = [0; MAX_BITS + 2];
+        for length in 1..=MAX_BITS {
+            offsets[length + 1] = offsets[length] + counts[length];
+        }
+
+        let mut symbols = vec![0; offsets[MAX_BITS + 1] as usize];
+        for (symbol, &length) in lengths.iter().enumerate() {
+            if length != 0 {
+                symbols[offsets[length as usize] as usize] = symbol as u16;
+                offsets[length as usize] += 1;
+            }
+        }
+
+        Ok(Huffman { counts, symbols })
+    }
+
+    fn decode(&self, bits: &mut Bits) -> Result<u16, DecodeError> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for &count in &self.counts[1..] {
+            code |= bits.read(1)? as i32;
+            let count = count as i32;
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+
+        Err(error("Invalid Huffman code."))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stored_blocks_are_copied() {
+        assert_eq!(
+            inflate(&[0, 2, 0, 0xFD, 0xFF, 1, 2, 1, 1, 0, 0xFE, 0xFF, 3]),
+            Ok(vec![1, 2, 3])
+        );
+    }
+
+    #[test]
+    fn fixed_codes_are_decoded() {
+        // "abcabcabc" as produced by zlib with the fixed Huffman code: three literals and a back reference.
+        let data = [0x4B, 0x4C, 0x4A, 0x4E, 0x04, 0x23, 0x00];
+        assert_eq!(inflate(&data), Ok(b"abcabcabc".to_vec()));
+    }
+
+    #[test]
+    fn zlib_checksum_is_verified() {
+        let valid = [0x78, 0x01, 0x01, 0x01, 0x00, 0xFE, 0xFF, 0x2A, 0x00, 0x2B, 0x00, 0x2B];
+        assert_eq!(decompress_zlib(&valid), Ok(vec![42]));
+
+        let mut corrupt = valid;
+        corrupt[11] ^= 1;
+        assert!(decompress_zlib(&corrupt).is_err());
+    }
+
+    #[test]
+    fn malformed_data_is_rejected() {
+        for data in [
+            &[][..],
+            &[0x07],                         // reserved block type
+            &[0x00, 2, 0, 2, 0],             // stored block length mismatch
+            &[0x01, 4, 0, 0xFB, 0xFF, 1],    // truncated stored block
+            &[0x4B, 0x4C, 0x4A, 0x4E, 0x04], // truncated fixed block
+            &[0x03, 0x02, 0x00],             // distance beyond the start of the output
+            &[0xFF; 16],                     // nonsensical dynamic block
+        ] {
+            assert!(inflate(data).is_err(), "{data:?}");
+        }
+    }
+}
@@ -0,0 +1,233 @@
+use super::{reader::Reader, DecodeError};
+
+/// Identifies compiled levels and the version of their format.
+pub(super) const MAGIC: &[u8; 4] = b"LVL1";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum EntityKind {
+    Sun,
+    Planet,
+    SpawnPoint,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Entity {
+    pub kind: EntityKind,
+    pub position: [f32; 2],
+    /// The radius of suns and planets; zero for spawn points.
+    pub radius: f32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Level {
+    pub name: String,
+    /// The width and height of the arena, which is centered on the origin.
+    pub size: [f32; 2],
+    pub entities: Vec<Entity>,
+}
+
+/// Compiles a level description into the runtime level format. Level descriptions consist of one statement per
+/// line; empty lines and lines starting with `#` are ignored:
+///
+/// ```text
+/// name <name>
+/// size <width> <height>
+/// sun <x> <y> <radius>
+/// planet <x> <y> <radius>
+/// spawn <x> <y>
+/// ```
+pub fn compile(source: &str) -> Result<Vec<u8>, String> {
+    let level = parse(source)?;
+
+    let mut compiled = MAGIC.to_vec();
+    compiled.extend_from_slice(&(level.name.len() as u16).to_le_bytes());
+    compiled.extend_from_slice(level.name.as_bytes());
+    compiled.extend(level.size.iter().flat_map(|value| value.to_le_bytes()));
+    compiled.extend_from_slice(&(level.entities.len() as u32).to_le_bytes());
+    for entity in level.entities {
+        compiled.push(entity.kind as u8);
+        compiled.extend(
+            [entity.position[0], entity.position[1], entity.radius]
+                .iter()
+                .flat_map(|value| value.to_le_bytes()),
+        );
+    }
+
+    Ok(compiled)
+}
+
+pub fn load(data: &[u8]) -> Result<Level, DecodeError> {
+    let mut reader = Reader::new(data, "level");
+    if &reader.array()? != MAGIC {
+        return Err(reader.error("Invalid signature."));
+    }
+
+    let name_length = reader.u16_le()? as usize;
+    let name = String::from_utf8(reader.bytes(name_length)?.to_vec()).map_err(|_| reader.error("Invalid name."))?;
+    let size = [reader.f32_le()?, reader.f32_le()?];
+
+    let entity_count = reader.u32_le()? as usize;
+    // Each entity occupies 13 bytes; checking this up front avoids allocating huge vectors for corrupt data.
+    if entity_count > reader.remaining() / 13 {
+        return Err(reader.error("Unexpected end of data."));
+    }
+
+    let mut entities = Vec::with_capacity(entity_count);
+    for _ in 0..entity_count {
+        let kind = match reader.u8()? {
+            0 => EntityKind::Sun,
+            1 => EntityKind::Planet,
+            2 => EntityKind::SpawnPoint,
+            _ => return Err(reader.error("Invalid entity kind.")),
+        };
+
+        entities.push(Entity {
+            kind,
+            position: [reader.f32_le()?, reader.f32_le()?],
+            radius: reader.f32_le()?,
+        });
+    }
+
+    Ok(Level { name, size, entities })
+}
+
+fn parse(source: &str) -> Result<Level, String> {
+    let (mut name, mut size, mut entities) = (None, None, vec![]);
+
+    for (line_number, line) in source.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let error = |message: &str| format!("Line {}: {message}", line_number + 1);
+        let (statement, arguments) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let values = || -> Result<Vec<f32>, String> {
+            arguments
+                .split_whitespace()
+                .map(|value| value.parse().map_err(|_| error(&format!("Invalid number '{value}'."))))
+                .collect()
+        };
+
+        let entity = |kind, values: Vec<f32>| -> Result<Entity, String> {
+            match (kind, &values[..]) {
+                (EntityKind::SpawnPoint, &[x, y]) => Ok(Entity {
+                    kind,
+                    position: [x, y],
+                    radius: 0.0,
+                }),
+                (EntityKind::Sun | EntityKind::Planet, &[x, y, radius]) if radius > 0.0 => Ok(Entity {
+                    kind,
+                    position: [x, y],
+                    radius,
+                }),
+                (EntityKind::Sun | EntityKind::Planet, &[_, _, _]) => Err(error("The radius must be positive.")),
+                (EntityKind::SpawnPoint, _) => Err(error("Expected a position.")),
+                _ => Err(error("Expected a position and a radius.")),
+            }
+        };
+
+        match statement {
+            "name" if !arguments.trim().is_empty() => name = Some(arguments.trim().to_string()),
+            "name" => return Err(error("Expected a name.")),
+            "size" => match values()?[..] {
+                [width, height] if width > 0.0 && height > 0.0 => size = Some([width, height]),
+                _ => return Err(error("Expected a positive width and height.")),
+            },
+            "sun" => entities.push(entity(EntityKind::Sun, values()?)?),
+            "planet" => entities.push(entity(EntityKind::Planet, values()?)?),
+            "spawn" => entities.push(entity(EntityKind::SpawnPoint, values()?)?),
+            _ => return Err(error(&format!("Unknown statement '{statement}'."))),
+        }
+    }
+
+    let level = Level {
+        name: name.ok_or("The level has no name.")?,
+        size: size.ok_or("The level has no size.")?,
+        entities,
+    };
+
+    if !level.entities.iter().any(|entity| entity.kind == EntityKind::SpawnPoint) {
+        return Err("The level has no spawn points.".to_string());
+    }
+
+    if let Some(entity) = level
+        .entities
+        .iter()
+        .find(|entity| entity.position[0].abs() > level.size[0] / 2.0 || entity.position[1].abs() > level.size[1] / 2.0)
+    {
+        return Err(format!(
+            "The {:?} at {:?} lies outside of the level.",
+            entity.kind, entity.position
+        ));
+    }
+
+    Ok(level)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levels_are_compiled() {
+        let source = "# A small level.\n\nname Twin Suns\nsize 4000 2000\nsun -500 0 300\n  sun 500 0 300\n\
+                      planet 1200 -400 80\nspawn -1800 900\n";
+
+        let entity = |kind, x, y, radius| Entity {
+            kind,
+            position: [x, y],
+            radius,
+        };
+
+        assert_eq!(
+            load(&compile(source).unwrap()),
+            Ok(Level {
+                name: "Twin Suns".to_string(),
+                size: [4000.0, 2000.0],
+                entities: vec![
+                    entity(EntityKind::Sun, -500.0, 0.0, 300.0),
+                    entity(EntityKind::Sun, 500.0, 0.0, 300.0),
+                    entity(EntityKind::Planet, 1200.0, -400.0, 80.0),
+                    entity(EntityKind::SpawnPoint, -1800.0, 900.0, 0.0),
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn invalid_levels_are_rejected() {
+        for (source, message) in [
+            ("size 10 10\nspawn 0 0", "The level has no name."),
+            ("name a\nspawn 0 0", "The level has no size."),
+            ("name a\nsize 10 10", "The level has no spawn points."),
+            (
+                "name a\nsize 10 10\nspawn 0 6",
+                "The SpawnPoint at [0.0, 6.0] lies outside of the level.",
+            ),
+            ("name a\n\nmoon 1 2 3", "Line 3: Unknown statement 'moon'."),
+            ("name a\nsize 10", "Line 2: Expected a positive width and height."),
+            ("name a\nsize 10 x", "Line 2: Invalid number 'x'."),
+            ("planet 1 2", "Line 1: Expected a position and a radius."),
+            ("sun 1 2 0", "Line 1: The radius must be positive."),
+            ("spawn 1 2 3", "Line 1: Expected a position."),
+            ("name", "Line 1: Expected a name."),
+        ] {
+            assert_eq!(compile(source), Err(message.to_string()), "{source}");
+        }
+    }
+
+    #[test]
+    fn corrupt_data_is_rejected() {
+        let compiled = compile("name a\nsize 10 10\nspawn 0 0").unwrap();
+        assert!(load(&compiled[..compiled.len() - 1]).is_err());
+
+        let mut invalid_kind = compiled.clone();
+        invalid_kind[compiled.len() - 13] = 7;
+        assert_eq!(
+            load(&invalid_kind).map_err(|error| error.message),
+            Err("Invalid entity kind.")
+        );
+    }
+}
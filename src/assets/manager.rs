@@ -0,0 +1,103 @@
+use super::archive::{self, Archive};
+use crate::platform::memory_map::MemoryMap;
+use std::{
+    borrow::Cow,
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+/// Loads compiled assets by their id from the memory-mapped asset archive or, during development, from the loose
+/// files written by the build script.
+pub struct AssetManager {
+    directory: PathBuf,
+    archive: Option<Archive<MemoryMap>>,
+    loose_files: bool,
+}
+
+impl AssetManager {
+    /// Opens the archive in the given directory. If `loose_files` is set, assets that are missing from the archive
+    /// or the archive itself may be missing, in which case the files in the directory are loaded instead.
+    pub fn new(directory: impl Into<PathBuf>, loose_files: bool) -> Result<AssetManager, String> {
+        let directory = directory.into();
+        let path = directory.join(archive::FILE_NAME);
+
+        let archive = match MemoryMap::open(&path) {
+            Ok(map) => Some(Archive::new(map).map_err(|e| format!("Failed to open asset archive '{}': {e}", path.display()))?),
+            Err(_) if loose_files => None,
+            Err(e) => return Err(format!("Failed to open asset archive '{}': {e}.", path.display())),
+        };
+
+        Ok(AssetManager {
+            directory,
+            archive,
+            loose_files,
+        })
+    }
+
+    /// Finds the asset directory: The directory of the executable if it contains an asset archive, as is the case
+    /// for distributed builds, or the build script's output directory relative to the working directory otherwise.
+    pub fn default_directory() -> PathBuf {
+        let executable_directory = env::current_exe().ok().and_then(|path| path.parent().map(Path::to_path_buf));
+        match executable_directory {
+            Some(directory) if directory.join(archive::FILE_NAME).is_file() => directory,
+            _ => Path::new("target/assets").join(if cfg!(debug_assertions) { "debug" } else { "release" }),
+        }
+    }
+
+    pub fn load(&self, id: &str) -> Result<Cow<'_, [u8]>, String> {
+        if let Some(result) = self.archive.as_ref().and_then(|archive| archive.read(id)) {
+            return result.map_err(|e| format!("Failed to load asset '{id}': {e}"));
+        }
+
+        if !self.loose_files {
+            return Err(format!("Asset '{id}' does not exist."));
+        }
+
+        let path = self.directory.join(id);
+        fs::read(&path)
+            .map(Cow::Owned)
+            .map_err(|e| format!("Failed to load asset '{id}' from '{}': {e}.", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::directory;
+
+    #[test]
+    fn assets_are_loaded_from_the_archive() {
+        let directory = directory("asset-manager-archive");
+        fs::write(directory.join(archive::FILE_NAME), archive::write([("a", &b"archived"[..])])).unwrap();
+        fs::write(directory.join("a"), b"loose").unwrap();
+        fs::write(directory.join("b"), b"loose").unwrap();
+
+        let assets = AssetManager::new(&directory, false).unwrap();
+        assert_eq!(assets.load("a").as_deref(), Ok(&b"archived"[..]));
+        assert_eq!(assets.load("b"), Err("Asset 'b' does not exist.".to_string()));
+
+        let assets = AssetManager::new(&directory, true).unwrap();
+        assert_eq!(assets.load("a").as_deref(), Ok(&b"archived"[..]));
+        assert_eq!(assets.load("b").as_deref(), Ok(&b"loose"[..]));
+        assert!(assets.load("c").is_err());
+
+        drop(assets);
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn loose_files_are_used_without_an_archive() {
+        let directory = directory("asset-manager-loose");
+        fs::create_dir(directory.join("shaders")).unwrap();
+        fs::write(directory.join("shaders/sprite.vs.hlsl"), b"loose").unwrap();
+
+        assert!(AssetManager::new(&directory, false).is_err());
+        let assets = AssetManager::new(&directory, true).unwrap();
+        assert_eq!(assets.load("shaders/sprite.vs.hlsl").as_deref(), Ok(&b"loose"[..]));
+
+        fs::write(directory.join(archive::FILE_NAME), b"PAK0").unwrap();
+        assert!(AssetManager::new(&directory, true).is_err());
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -0,0 +1,560 @@
+use super::{archive, checksum::crc32, font, hlsl, level, shader, shader_compiler::ShaderCompiler, sound, texture};
+use crate::{info, log::Category};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// The file that lists the assets to compile, relative to the source directory.
+pub const MANIFEST: &str = "manifest.txt";
+
+/// The file that records the inputs of the compiled assets, relative to the target directory.
+const DEPENDENCIES: &str = "dependencies.txt";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssetKind {
+    VertexShader,
+    PixelShader,
+    Texture,
+    Font,
+    Sound,
+    Level,
+}
+
+impl AssetKind {
+    const ALL: [AssetKind; 6] = [
+        AssetKind::VertexShader,
+        AssetKind::PixelShader,
+        AssetKind::Texture,
+        AssetKind::Font,
+        AssetKind::Sound,
+        AssetKind::Level,
+    ];
+
+    /// The name of the kind as used in the manifest.
+    pub fn name(self) -> &'static str {
+        match self {
+            AssetKind::VertexShader => "vertex-shader",
+            AssetKind::PixelShader => "pixel-shader",
+            AssetKind::Texture => "texture",
+            AssetKind::Font => "font",
+            AssetKind::Sound => "sound",
+            AssetKind::Level => "level",
+        }
+    }
+
+    /// Identifies the format of compiled assets, so that assets compiled into an outdated format are recompiled.
+    fn format(self) -> &'static [u8] {
+        match self {
+            AssetKind::VertexShader | AssetKind::PixelShader => shader::MAGIC,
+            AssetKind::Texture => texture::MAGIC,
+            AssetKind::Font => font::MAGIC,
+            AssetKind::Sound => sound::MAGIC,
+            AssetKind::Level => level::MAGIC,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Asset {
+    pub kind: AssetKind,
+    /// The path of the asset's source file relative to the source directory, using `/` as the separator.
+    pub path: String,
+}
+
+impl Asset {
+    /// The path of the compiled asset relative to the target directory. Shaders keep the name of their source file.
+    pub fn output_path(&self) -> String {
+        let extension = match self.kind {
+            AssetKind::VertexShader | AssetKind::PixelShader => return self.path.clone(),
+            AssetKind::Texture => "texture",
+            AssetKind::Font => "font",
+            AssetKind::Sound => "sound",
+            AssetKind::Level => "level",
+        };
+
+        // Only the extension of the file name is replaced, not dots in directory names or leading dots.
+        let file_name = self.path.rfind('/').map_or(0, |separator| separator + 1);
+        let stem = match self.path[file_name..].rfind('.') {
+            Some(0) | None => &self.path,
+            Some(dot) => &self.path[..file_name + dot],
+        };
+        format!("{stem}.{extension}")
+    }
+}
+
+/// Parses a manifest, which lists one asset per line as `<kind> <path>`. Empty lines and lines starting with `#` are
+/// ignored.
+pub fn parse_manifest(manifest: &str) -> Result<Vec<Asset>, String> {
+    let mut assets: Vec<Asset> = vec![];
+
+    for (line_number, line) in manifest.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let error = |message: String| format!("Line {}: {message}", line_number + 1);
+        let (kind, path) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let kind = AssetKind::ALL
+            .into_iter()
+            .find(|candidate| candidate.name() == kind)
+            .ok_or_else(|| error(format!("Unknown asset kind '{kind}'.")))?;
+
+        let asset = Asset {
+            kind,
+            path: path.trim().to_string(),
+        };
+
+        if asset.path.is_empty() {
+            return Err(error("Expected the path of the asset.".to_string()));
+        }
+
+        if assets.iter().any(|other| other.output_path() == asset.output_path()) {
+            return Err(error(format!("'{}' conflicts with another asset.", asset.path)));
+        }
+
+        assets.push(asset);
+    }
+
+    Ok(assets)
+}
+
+/// The result of compiling the assets listed in a manifest.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Compilation {
+    /// The output paths of all assets listed in the manifest.
+    pub outputs: Vec<String>,
+    /// The output paths of the assets that have been compiled or removed from the manifest.
+    pub changed: Vec<String>,
+    /// The errors of the assets that failed to compile; their previous outputs, if any, are left untouched.
+    pub errors: Vec<String>,
+}
+
+/// Compiles the assets listed in the source directory's manifest into the target directory, skipping assets whose
+/// inputs have not changed since they were last compiled.
+/// Assets that fail to compile do not prevent the remaining assets from being compiled; they are retried the next
+/// time. The archive is not updated, so that this can be used while the game runs.
+pub fn compile(source: &Path, target: &Path, shader_compiler: &dyn ShaderCompiler) -> Result<Compilation, String> {
+    let manifest_path = source.join(MANIFEST);
+    let manifest =
+        fs::read_to_string(&manifest_path).map_err(|e| format!("Failed to read manifest '{}': {e}.", manifest_path.display()))?;
+    let assets = parse_manifest(&manifest).map_err(|e| format!("Invalid manifest '{}': {e}", manifest_path.display()))?;
+
+    let dependencies_path = target.join(DEPENDENCIES);
+    let mut dependencies = Dependencies::load(&dependencies_path);
+    let mut compilation = Compilation {
+        outputs: assets.iter().map(Asset::output_path).collect(),
+        ..Compilation::default()
+    };
+
+    // Remove the compiled files of assets that are no longer listed in the manifest.
+    dependencies.entries.retain(|output, _| {
+        let listed = compilation.outputs.contains(output);
+        if !listed {
+            let _ = fs::remove_file(target.join(output));
+            compilation.changed.push(output.clone());
+        }
+        listed
+    });
+
+    if !compilation.changed.is_empty() {
+        dependencies.save(&dependencies_path)?;
+    }
+
+    for (asset, output) in assets.iter().zip(compilation.outputs.clone()) {
+        if dependencies.is_up_to_date(source, target, asset.kind, &output) {
+            continue;
+        }
+
+        info!(Category::Assets, "Compiling {} '{}'.", asset.kind.name(), asset.path);
+
+        let mut inputs = Inputs { source, files: vec![] };
+        let result = compile_asset(asset, &mut inputs, shader_compiler)
+            .map_err(|e| format!("Failed to compile {} '{}': {e}", asset.kind.name(), asset.path))
+            .and_then(|data| write_file(&target.join(&output), &data));
+
+        if let Err(e) = result {
+            compilation.errors.push(e);
+            continue;
+        }
+
+        dependencies.entries.insert(
+            output.clone(),
+            Entry {
+                fingerprint: fingerprint(asset.kind, &inputs.files),
+                inputs: inputs.files.into_iter().map(|(path, _)| path).collect(),
+            },
+        );
+        dependencies.save(&dependencies_path)?;
+        compilation.changed.push(output);
+    }
+
+    Ok(compilation)
+}
+
+/// Compiles the assets like [`compile`] and packs them into the archive if anything has changed. Fails if any asset
+/// fails to compile. Returns the output paths of the assets that have been compiled or removed.
+pub fn run(source: &Path, target: &Path, shader_compiler: &dyn ShaderCompiler) -> Result<Vec<String>, String> {
+    let compilation = compile(source, target, shader_compiler)?;
+    if !compilation.errors.is_empty() {
+        return Err(compilation.errors.join("\n"));
+    }
+
+    let archive_path = target.join(archive::FILE_NAME);
+    if !compilation.changed.is_empty() || !archive_path.is_file() {
+        pack(target, &compilation.outputs, &archive_path)?;
+    }
+
+    Ok(compilation.changed)
+}
+
+/// Reads the inputs of an asset and compiles them.
+fn compile_asset(asset: &Asset, inputs: &mut Inputs, shader_compiler: &dyn ShaderCompiler) -> Result<Vec<u8>, String> {
+    match asset.kind {
+        AssetKind::VertexShader | AssetKind::PixelShader => {
+            // The compiler reads the source itself, so that it can resolve includes relative to the source's path.
+            let reflection = hlsl::reflect(&inputs.read_string(&asset.path)?)?;
+            let byte_code = shader_compiler.compile(&inputs.source.join(&asset.path), asset.kind)?;
+            for dependency in shader_compiler.dependencies(&asset.path) {
+                inputs.read(&dependency)?;
+            }
+            Ok(shader::compile(&reflection, &byte_code))
+        }
+        AssetKind::Texture => texture::compile(&inputs.read(&asset.path)?).map_err(|e| e.to_string()),
+        AssetKind::Font => {
+            let descriptor = inputs.read_string(&asset.path)?;
+            let directory = asset.path.rsplit_once('/').map_or("", |(directory, _)| directory);
+            font::compile(&descriptor, |page| match directory {
+                "" => inputs.read(page),
+                _ => inputs.read(&format!("{directory}/{page}")),
+            })
+        }
+        AssetKind::Sound => sound::compile(&inputs.read(&asset.path)?).map_err(|e| e.to_string()),
+        AssetKind::Level => level::compile(&inputs.read_string(&asset.path)?),
+    }
+}
+
+/// Packs the compiled assets into the archive.
+fn pack(target: &Path, outputs: &[String], archive_path: &Path) -> Result<(), String> {
+    info!(Category::Assets, "Packing assets into '{}'.", archive_path.display());
+
+    let mut entries = vec![];
+    for output in outputs {
+        if let Ok(data) = fs::read(target.join(output)) {
+            entries.push((output.as_str(), data));
+        }
+    }
+
+    // The archive is replaced rather than overwritten, as it might be memory-mapped by a running game.
+    let temporary_path = archive_path.with_extension("tmp");
+    write_file(
+        &temporary_path,
+        &archive::write(entries.iter().map(|(id, data)| (*id, &data[..]))),
+    )?;
+    fs::rename(&temporary_path, archive_path).map_err(|e| format!("Failed to replace file '{}': {e}.", archive_path.display()))
+}
+
+/// Computes a checksum of an asset's kind and the paths and contents of its inputs.
+fn fingerprint(kind: AssetKind, inputs: &[(String, u32)]) -> u32 {
+    let mut data = [kind.name().as_bytes(), kind.format()].concat();
+    for (path, checksum) in inputs {
+        data.extend_from_slice(path.as_bytes());
+        data.push(0);
+        data.extend_from_slice(&checksum.to_le_bytes());
+    }
+
+    crc32(&data)
+}
+
+/// Reads the input files of an asset, remembering their paths and checksums.
+struct Inputs<'a> {
+    source: &'a Path,
+    files: Vec<(String, u32)>,
+}
+
+impl Inputs<'_> {
+    fn read(&mut self, path: &str) -> Result<Vec<u8>, String> {
+        let data = fs::read(self.source.join(path)).map_err(|e| format!("Failed to read file '{path}': {e}."))?;
+        self.files.push((path.to_string(), crc32(&data)));
+        Ok(data)
+    }
+
+    fn read_string(&mut self, path: &str) -> Result<String, String> {
+        String::from_utf8(self.read(path)?).map_err(|_| format!("File '{path}' is not valid UTF-8."))
+    }
+}
+
+struct Entry {
+    fingerprint: u32,
+    inputs: Vec<String>,
+}
+
+/// Maps the output paths of compiled assets to the inputs they were compiled from. The dependencies are stored as
+/// one line per asset containing its tab-separated output path, fingerprint and input paths.
+struct Dependencies {
+    entries: BTreeMap<String, Entry>,
+}
+
+impl Dependencies {
+    /// Loads the dependencies. As they are merely a cache, missing or malformed files cause all assets to be compiled.
+    fn load(path: &Path) -> Dependencies {
+        let entries = fs::read_to_string(path)
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|line| {
+                let mut columns = line.split('\t');
+                let output = columns.next()?.to_string();
+                let fingerprint = u32::from_str_radix(columns.next()?, 16).ok()?;
+                let inputs = columns.map(str::to_string).collect();
+                Some((output, Entry { fingerprint, inputs }))
+            })
+            .collect();
+
+        Dependencies { entries }
+    }
+
+    fn save(&self, path: &Path) -> Result<(), String> {
+        let mut content = String::new();
+        for (output, entry) in &self.entries {
+            content.push_str(&format!("{output}\t{:08x}", entry.fingerprint));
+            for input in &entry.inputs {
+                content.push('\t');
+                content.push_str(input);
+            }
+            content.push('\n');
+        }
+
+        write_file(path, content.as_bytes())
+    }
+
+    fn is_up_to_date(&self, source: &Path, target: &Path, kind: AssetKind, output: &str) -> bool {
+        let Some(entry) = self.entries.get(output) else {
+            return false;
+        };
+
+        if !target.join(output).is_file() {
+            return false;
+        }
+
+        let mut inputs = Inputs { source, files: vec![] };
+        entry.inputs.iter().all(|input| inputs.read(input).is_ok()) && fingerprint(kind, &inputs.files) == entry.fingerprint
+    }
+}
+
+fn write_file(path: &Path, content: &[u8]) -> Result<(), String> {
+    let directory = path.parent().map(PathBuf::from).unwrap_or_default();
+    if !directory.as_os_str().is_empty() && !directory.is_dir() {
+        fs::create_dir_all(&directory).map_err(|e| format!("Failed to create directory '{}': {e}.", directory.display()))?;
+    }
+
+    fs::write(path, content).map_err(|e| format!("Failed to write file '{}': {e}.", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        super::shader_compiler::{self, PrecompiledShaders},
+        *,
+    };
+    use crate::test_util::directory;
+
+    fn archived(target: &Path) -> Vec<String> {
+        let archive = archive::Archive::new(fs::read(target.join(archive::FILE_NAME)).unwrap()).unwrap();
+        let ids = archive.ids().map(str::to_string).collect::<Vec<_>>();
+        for id in &ids {
+            assert_eq!(archive.read(id).unwrap().unwrap(), fs::read(target.join(id)).unwrap());
+        }
+        ids
+    }
+
+    #[test]
+    fn manifests_are_parsed() {
+        let manifest =
+            "# Shaders\nvertex-shader shaders/sprite.vs.hlsl\n\n  texture  textures/ship 1.png  \nlevel levels.v1/arena";
+        let asset = |kind, path: &str| Asset {
+            kind,
+            path: path.to_string(),
+        };
+
+        let assets = parse_manifest(manifest).unwrap();
+        assert_eq!(
+            assets,
+            [
+                asset(AssetKind::VertexShader, "shaders/sprite.vs.hlsl"),
+                asset(AssetKind::Texture, "textures/ship 1.png"),
+                asset(AssetKind::Level, "levels.v1/arena"),
+            ]
+        );
+
+        let outputs: Vec<_> = assets.iter().map(Asset::output_path).collect();
+        assert_eq!(
+            outputs,
+            ["shaders/sprite.vs.hlsl", "textures/ship 1.texture", "levels.v1/arena.level"]
+        );
+    }
+
+    #[test]
+    fn invalid_manifests_are_rejected() {
+        for (manifest, message) in [
+            ("model ship.obj", "Line 1: Unknown asset kind 'model'."),
+            ("\ntexture", "Line 2: Expected the path of the asset."),
+            (
+                "texture a.png\ntexture a.tga",
+                "Line 2: 'a.tga' conflicts with another asset.",
+            ),
+        ] {
+            assert_eq!(parse_manifest(manifest), Err(message.to_string()));
+        }
+    }
+
+    #[test]
+    fn only_changed_assets_are_compiled() {
+        let (source, target) = (directory("pipeline-changes-source"), directory("pipeline-changes-target"));
+        fs::create_dir(source.join("fonts")).unwrap();
+        fs::write(source.join("ship.png"), include_bytes!("../../tests/images/rgba8.png")).unwrap();
+        fs::write(source.join("arena.txt"), "name Arena\nsize 100 100\nspawn 0 0").unwrap();
+        fs::write(
+            source.join("fonts/font.fnt"),
+            "common lineHeight=8 base=6 pages=1\npage id=0 file=\"page.tga\"",
+        )
+        .unwrap();
+        fs::write(source.join("fonts/page.tga"), include_bytes!("../../tests/images/rgba32.tga")).unwrap();
+        fs::write(
+            source.join(MANIFEST),
+            "texture ship.png\nfont fonts/font.fnt\nlevel arena.txt",
+        )
+        .unwrap();
+
+        assert_eq!(
+            run(&source, &target, &PrecompiledShaders),
+            Ok(vec![
+                "ship.texture".to_string(),
+                "fonts/font.font".to_string(),
+                "arena.level".to_string()
+            ])
+        );
+        assert!(texture::load(&fs::read(target.join("ship.texture")).unwrap()).is_ok());
+        assert_eq!(archived(&target), ["arena.level", "fonts/font.font", "ship.texture"]);
+        assert_eq!(run(&source, &target, &PrecompiledShaders), Ok(vec![]));
+
+        // Changing a dependency recompiles the asset that depends on it.
+        fs::write(source.join("fonts/page.tga"), include_bytes!("../../tests/images/rgb24.tga")).unwrap();
+        assert_eq!(
+            run(&source, &target, &PrecompiledShaders),
+            Ok(vec!["fonts/font.font".to_string()])
+        );
+
+        // Deleted outputs are recompiled and outputs of removed assets are deleted.
+        fs::remove_file(target.join("ship.texture")).unwrap();
+        fs::write(source.join(MANIFEST), "texture ship.png\nfont fonts/font.fnt").unwrap();
+        assert_eq!(
+            run(&source, &target, &PrecompiledShaders),
+            Ok(vec!["arena.level".to_string(), "ship.texture".to_string()])
+        );
+        assert!(!target.join("arena.level").exists());
+        assert_eq!(archived(&target), ["fonts/font.font", "ship.texture"]);
+
+        // Invalid assets are reported, but neither affect their previous outputs nor the other assets.
+        let texture = fs::read(target.join("ship.texture")).unwrap();
+        fs::write(source.join("ship.png"), b"not an image").unwrap();
+        fs::write(source.join("fonts/page.tga"), include_bytes!("../../tests/images/rgba32.tga")).unwrap();
+        let error = "Failed to compile texture 'ship.png': Invalid TGA data: Unexpected end of data.".to_string();
+        assert_eq!(run(&source, &target, &PrecompiledShaders), Err(error.clone()));
+        assert_eq!(fs::read(target.join("ship.texture")).unwrap(), texture);
+        assert_eq!(
+            compile(&source, &target, &PrecompiledShaders),
+            Ok(Compilation {
+                outputs: vec!["ship.texture".to_string(), "fonts/font.font".to_string()],
+                changed: vec![],
+                errors: vec![error],
+            })
+        );
+
+        // Failed assets are retried, while the assets compiled in the meantime are up to date.
+        fs::write(source.join("ship.png"), include_bytes!("../../tests/images/rgb24.tga")).unwrap();
+        assert_eq!(
+            run(&source, &target, &PrecompiledShaders),
+            Ok(vec!["ship.texture".to_string()])
+        );
+
+        fs::remove_dir_all(source).unwrap();
+        fs::remove_dir_all(target).unwrap();
+    }
+
+    #[test]
+    fn shaders_are_compiled_with_the_given_compiler() {
+        let (source, target) = (directory("pipeline-shaders-source"), directory("pipeline-shaders-target"));
+        fs::write(source.join("sprite.vs.hlsl"), "void main(float2 position : POSITION) {}").unwrap();
+        fs::write(source.join(MANIFEST), "vertex-shader sprite.vs.hlsl").unwrap();
+
+        // Shaders are never skipped, even if they cannot be compiled on this platform.
+        let error = run(&source, &target, &PrecompiledShaders).unwrap_err();
+        assert!(
+            error.starts_with("Failed to compile vertex-shader 'sprite.vs.hlsl': The shader has not been precompiled; "),
+            "{error}"
+        );
+        assert!(!target.join("sprite.vs.hlsl").exists());
+
+        let compiler = |path: &Path, kind| {
+            assert_eq!(kind, AssetKind::VertexShader);
+            Ok(fs::read(path).unwrap().into_iter().rev().collect())
+        };
+
+        assert_eq!(run(&source, &target, &compiler), Ok(vec!["sprite.vs.hlsl".to_string()]));
+        let compiled = fs::read(target.join("sprite.vs.hlsl")).unwrap();
+        let shader = shader::load(&compiled).unwrap();
+        assert_eq!(shader.byte_code, b"}{ )NOITISOP : noitisop 2taolf(niam diov");
+        assert_eq!(shader.reflection.inputs[0].semantic, "POSITION");
+        assert_eq!(archived(&target), ["sprite.vs.hlsl"]);
+        assert_eq!(run(&source, &target, &compiler), Ok(vec![]));
+
+        // Both the reflection and the compiler report errors.
+        fs::write(source.join("sprite.vs.hlsl"), "void main() {").unwrap();
+        assert_eq!(
+            run(&source, &target, &compiler),
+            Err("Failed to compile vertex-shader 'sprite.vs.hlsl': Line 1: Unexpected end of the shader.".to_string())
+        );
+
+        let failing_compiler = |_: &Path, _| Err("error X3000: syntax error".to_string());
+        fs::write(source.join("sprite.vs.hlsl"), "void main() { error }").unwrap();
+        assert_eq!(
+            run(&source, &target, &failing_compiler),
+            Err("Failed to compile vertex-shader 'sprite.vs.hlsl': error X3000: syntax error".to_string())
+        );
+
+        fs::remove_dir_all(source).unwrap();
+        fs::remove_dir_all(target).unwrap();
+    }
+
+    #[test]
+    fn shaders_are_recompiled_when_their_precompiled_files_change() {
+        let (source, target) = (
+            directory("pipeline-precompiled-source"),
+            directory("pipeline-precompiled-target"),
+        );
+        fs::write(source.join("sprite.ps.hlsl"), "float4 main() : SV_TARGET { return 1; }").unwrap();
+        fs::write(source.join(MANIFEST), "pixel-shader sprite.ps.hlsl").unwrap();
+
+        let compiler = |_: &Path, _| Ok(b"DXBC 1".to_vec());
+        shader_compiler::precompile(&source, &compiler).unwrap();
+        assert_eq!(
+            run(&source, &target, &PrecompiledShaders),
+            Ok(vec!["sprite.ps.hlsl".to_string()])
+        );
+        assert_eq!(run(&source, &target, &PrecompiledShaders), Ok(vec![]));
+
+        let compiler = |_: &Path, _| Ok(b"DXBC 2".to_vec());
+        shader_compiler::precompile(&source, &compiler).unwrap();
+        assert_eq!(
+            run(&source, &target, &PrecompiledShaders),
+            Ok(vec!["sprite.ps.hlsl".to_string()])
+        );
+        let compiled = fs::read(target.join("sprite.ps.hlsl")).unwrap();
+        assert_eq!(shader::load(&compiled).unwrap().byte_code, b"DXBC 2");
+
+        fs::remove_dir_all(source).unwrap();
+        fs::remove_dir_all(target).unwrap();
+    }
+}
@@ -0,0 +1,86 @@
+use super::DecodeError;
+
+/// Reads the primitive values of binary file formats, reporting an error instead of panicking when the data ends
+/// prematurely.
+pub struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+    format: &'static str,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(data: &'a [u8], format: &'static str) -> Reader<'a> {
+        Reader {
+            data,
+            position: 0,
+            format,
+        }
+    }
+
+    pub fn error(&self, message: &'static str) -> DecodeError {
+        DecodeError {
+            format: self.format,
+            message,
+        }
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.data.len() - self.position
+    }
+
+    pub fn seek(&mut self, position: usize) -> Result<(), DecodeError> {
+        if position > self.data.len() {
+            return Err(self.error("Unexpected end of data."));
+        }
+
+        self.position = position;
+        Ok(())
+    }
+
+    pub fn bytes(&mut self, count: usize) -> Result<&'a [u8], DecodeError> {
+        if count > self.remaining() {
+            return Err(self.error("Unexpected end of data."));
+        }
+
+        self.position += count;
+        Ok(&self.data[self.position - count..self.position])
+    }
+
+    pub fn u8(&mut self) -> Result<u8, DecodeError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    pub fn u16_le(&mut self) -> Result<u16, DecodeError> {
+        Ok(u16::from_le_bytes(self.array()?))
+    }
+
+    pub fn i16_le(&mut self) -> Result<i16, DecodeError> {
+        Ok(i16::from_le_bytes(self.array()?))
+    }
+
+    pub fn u32_le(&mut self) -> Result<u32, DecodeError> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    pub fn i32_le(&mut self) -> Result<i32, DecodeError> {
+        Ok(i32::from_le_bytes(self.array()?))
+    }
+
+    pub fn u64_le(&mut self) -> Result<u64, DecodeError> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    pub fn f32_le(&mut self) -> Result<f32, DecodeError> {
+        Ok(f32::from_le_bytes(self.array()?))
+    }
+
+    pub fn u32_be(&mut self) -> Result<u32, DecodeError> {
+        Ok(u32::from_be_bytes(self.array()?))
+    }
+
+    pub fn array<const N: usize>(&mut self) -> Result<[u8; N], DecodeError> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.bytes(N)?);
+        Ok(array)
+    }
+}
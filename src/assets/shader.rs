@@ -0,0 +1,269 @@
+use super::{reader::Reader, DecodeError};
+
+/// Identifies compiled shaders and the version of their format.
+pub(super) const MAGIC: &[u8; 4] = b"SDR1";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ScalarType {
+    Float,
+    Int,
+    Uint,
+    Bool,
+}
+
+/// An input of a shader's entry point. System values such as `SV_Position` are omitted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShaderInput {
+    /// The upper-case semantic name without its index, e.g. `TEXCOORD` for `TEXCOORD1`.
+    pub semantic: String,
+    pub semantic_index: u32,
+    pub scalar_type: ScalarType,
+    pub components: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConstantBufferMember {
+    pub name: String,
+    /// The byte offset of the member within the constant buffer, taking the HLSL packing rules into account.
+    pub offset: u32,
+    pub size: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConstantBufferLayout {
+    pub name: String,
+    pub slot: u32,
+    /// The size of the constant buffer, which is a multiple of 16 bytes.
+    pub size: u32,
+    pub members: Vec<ConstantBufferMember>,
+}
+
+/// The slot a texture or sampler is bound to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResourceBinding {
+    pub name: String,
+    pub slot: u32,
+}
+
+/// Describes the interface of a shader, so that vertex layouts and resource bindings can be derived from and
+/// validated against the shader instead of being duplicated in code.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Reflection {
+    pub inputs: Vec<ShaderInput>,
+    pub constant_buffers: Vec<ConstantBufferLayout>,
+    pub textures: Vec<ResourceBinding>,
+    pub samplers: Vec<ResourceBinding>,
+}
+
+impl Reflection {
+    /// Finds the slot of the constant buffer, checking that a buffer with elements of the given size can be bound to it.
+    pub fn constant_buffer_slot(&self, name: &str, element_size: usize) -> Result<u32, String> {
+        let constant_buffer = self
+            .constant_buffers
+            .iter()
+            .find(|constant_buffer| constant_buffer.name == name)
+            .ok_or_else(|| format!("The shader has no constant buffer '{name}'."))?;
+
+        if element_size.next_multiple_of(16) != constant_buffer.size as usize {
+            return Err(format!(
+                "The constant buffer '{name}' occupies {} bytes, but {element_size} bytes are provided.",
+                constant_buffer.size
+            ));
+        }
+
+        Ok(constant_buffer.slot)
+    }
+
+    pub fn texture_slot(&self, name: &str) -> Result<u32, String> {
+        find_slot(&self.textures, name).ok_or_else(|| format!("The shader has no texture '{name}'."))
+    }
+
+    pub fn sampler_slot(&self, name: &str) -> Result<u32, String> {
+        find_slot(&self.samplers, name).ok_or_else(|| format!("The shader has no sampler '{name}'."))
+    }
+}
+
+fn find_slot(bindings: &[ResourceBinding], name: &str) -> Option<u32> {
+    bindings
+        .iter()
+        .find(|binding| binding.name == name)
+        .map(|binding| binding.slot)
+}
+
+pub struct Shader<'a> {
+    pub reflection: Reflection,
+    pub byte_code: &'a [u8],
+}
+
+/// Stores the shader's reflection data in front of its byte code.
+pub fn compile(reflection: &Reflection, byte_code: &[u8]) -> Vec<u8> {
+    let mut compiled = MAGIC.to_vec();
+    let write_string = |compiled: &mut Vec<u8>, value: &str| {
+        compiled.extend_from_slice(&(value.len() as u16).to_le_bytes());
+        compiled.extend_from_slice(value.as_bytes());
+    };
+
+    compiled.extend_from_slice(&(reflection.inputs.len() as u16).to_le_bytes());
+    for input in &reflection.inputs {
+        write_string(&mut compiled, &input.semantic);
+        compiled.extend_from_slice(&input.semantic_index.to_le_bytes());
+        compiled.push(input.scalar_type as u8);
+        compiled.push(input.components);
+    }
+
+    compiled.extend_from_slice(&(reflection.constant_buffers.len() as u16).to_le_bytes());
+    for constant_buffer in &reflection.constant_buffers {
+        write_string(&mut compiled, &constant_buffer.name);
+        compiled.extend_from_slice(&constant_buffer.slot.to_le_bytes());
+        compiled.extend_from_slice(&constant_buffer.size.to_le_bytes());
+        compiled.extend_from_slice(&(constant_buffer.members.len() as u16).to_le_bytes());
+        for member in &constant_buffer.members {
+            write_string(&mut compiled, &member.name);
+            compiled.extend_from_slice(&member.offset.to_le_bytes());
+            compiled.extend_from_slice(&member.size.to_le_bytes());
+        }
+    }
+
+    for bindings in [&reflection.textures, &reflection.samplers] {
+        compiled.extend_from_slice(&(bindings.len() as u16).to_le_bytes());
+        for binding in bindings {
+            write_string(&mut compiled, &binding.name);
+            compiled.extend_from_slice(&binding.slot.to_le_bytes());
+        }
+    }
+
+    compiled.extend_from_slice(byte_code);
+    compiled
+}
+
+pub fn load(data: &[u8]) -> Result<Shader<'_>, DecodeError> {
+    let mut reader = Reader::new(data, "shader");
+    if &reader.array()? != MAGIC {
+        return Err(reader.error("Invalid signature."));
+    }
+
+    let read_string = |reader: &mut Reader| {
+        let length = reader.u16_le()? as usize;
+        String::from_utf8(reader.bytes(length)?.to_vec()).map_err(|_| reader.error("Invalid name."))
+    };
+
+    let mut reflection = Reflection::default();
+    for _ in 0..reader.u16_le()? {
+        reflection.inputs.push(ShaderInput {
+            semantic: read_string(&mut reader)?,
+            semantic_index: reader.u32_le()?,
+            scalar_type: match reader.u8()? {
+                0 => ScalarType::Float,
+                1 => ScalarType::Int,
+                2 => ScalarType::Uint,
+                3 => ScalarType::Bool,
+                _ => return Err(reader.error("Invalid scalar type.")),
+            },
+            components: reader.u8()?,
+        });
+    }
+
+    for _ in 0..reader.u16_le()? {
+        let name = read_string(&mut reader)?;
+        let slot = reader.u32_le()?;
+        let size = reader.u32_le()?;
+        let mut members = vec![];
+        for _ in 0..reader.u16_le()? {
+            members.push(ConstantBufferMember {
+                name: read_string(&mut reader)?,
+                offset: reader.u32_le()?,
+                size: reader.u32_le()?,
+            });
+        }
+
+        reflection.constant_buffers.push(ConstantBufferLayout {
+            name,
+            slot,
+            size,
+            members,
+        });
+    }
+
+    for bindings in [&mut reflection.textures, &mut reflection.samplers] {
+        for _ in 0..reader.u16_le()? {
+            bindings.push(ResourceBinding {
+                name: read_string(&mut reader)?,
+                slot: reader.u32_le()?,
+            });
+        }
+    }
+
+    let byte_code = reader.bytes(reader.remaining())?;
+    Ok(Shader { reflection, byte_code })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reflection() -> Reflection {
+        Reflection {
+            inputs: vec![ShaderInput {
+                semantic: "TEXCOORD".to_string(),
+                semantic_index: 1,
+                scalar_type: ScalarType::Float,
+                components: 2,
+            }],
+            constant_buffers: vec![ConstantBufferLayout {
+                name: "PerFrameConstants".to_string(),
+                slot: 2,
+                size: 80,
+                members: vec![
+                    ConstantBufferMember {
+                        name: "Projection".to_string(),
+                        offset: 0,
+                        size: 64,
+                    },
+                    ConstantBufferMember {
+                        name: "Time".to_string(),
+                        offset: 64,
+                        size: 4,
+                    },
+                ],
+            }],
+            textures: vec![ResourceBinding {
+                name: "Tex".to_string(),
+                slot: 3,
+            }],
+            samplers: vec![ResourceBinding {
+                name: "TexSampler".to_string(),
+                slot: 1,
+            }],
+        }
+    }
+
+    #[test]
+    fn compiled_shaders_can_be_loaded() {
+        let compiled = compile(&reflection(), b"DXBC byte code");
+        let shader = load(&compiled).unwrap();
+        assert_eq!(shader.reflection, reflection());
+        assert_eq!(shader.byte_code, b"DXBC byte code");
+
+        assert!(load(&compiled[..20]).is_err());
+        assert_eq!(load(b"DXBC").err().map(|error| error.message), Some("Invalid signature."));
+    }
+
+    #[test]
+    fn bindings_are_validated() {
+        let reflection = reflection();
+        assert_eq!(reflection.constant_buffer_slot("PerFrameConstants", 68), Ok(2));
+        assert_eq!(reflection.constant_buffer_slot("PerFrameConstants", 80), Ok(2));
+        assert_eq!(
+            reflection.constant_buffer_slot("PerFrameConstants", 64),
+            Err("The constant buffer 'PerFrameConstants' occupies 80 bytes, but 64 bytes are provided.".to_string())
+        );
+        assert!(reflection.constant_buffer_slot("PerObjectConstants", 64).is_err());
+        assert_eq!(reflection.texture_slot("Tex"), Ok(3));
+        assert_eq!(reflection.sampler_slot("TexSampler"), Ok(1));
+        assert_eq!(
+            reflection.sampler_slot("Tex"),
+            Err("The shader has no sampler 'Tex'.".to_string())
+        );
+    }
+}
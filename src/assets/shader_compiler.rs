@@ -0,0 +1,219 @@
+use super::{
+    checksum::crc32,
+    pipeline::{self, AssetKind},
+    reader::Reader,
+};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Identifies precompiled shaders and the version of their format.
+const MAGIC: &[u8; 4] = b"SHD1";
+
+/// The extension that replaces the `hlsl` extension of a shader's source file for its precompiled byte code.
+pub const PRECOMPILED_EXTENSION: &str = "compiled";
+
+/// The command that updates the precompiled shaders, as suggested by the errors about missing or outdated ones.
+const PRECOMPILE_COMMAND: &str = "cargo run --example precompile_shaders";
+
+/// Compiles HLSL shaders into the byte code expected by the graphics device. Only the D3D shader compiler translates
+/// the HLSL source; the byte code of the software backend merely names hand-written programs, see [`SoftwareShaders`].
+pub trait ShaderCompiler {
+    /// Compiles the shader, returning the compiler's error messages if the shader is invalid.
+    fn compile(&self, path: &Path, kind: AssetKind) -> Result<Vec<u8>, String>;
+
+    /// Gets the files besides the shader's source that the byte code depends on, relative to the source directory,
+    /// so that the shader is compiled again when they change.
+    fn dependencies(&self, _path: &str) -> Vec<String> {
+        vec![]
+    }
+}
+
+impl<F: Fn(&Path, AssetKind) -> Result<Vec<u8>, String>> ShaderCompiler for F {
+    fn compile(&self, path: &Path, kind: AssetKind) -> Result<Vec<u8>, String> {
+        self(path, kind)
+    }
+}
+
+/// Gets the compiler for the graphics backend of this platform: The D3D shader compiler on Windows, and the programs
+/// of the software backend everywhere else.
+#[cfg(windows)]
+pub fn for_platform() -> impl ShaderCompiler {
+    super::d3d_compiler::D3DCompiler
+}
+
+/// Gets the compiler for the graphics backend of this platform: The D3D shader compiler on Windows, and the programs
+/// of the software backend everywhere else.
+#[cfg(not(windows))]
+pub fn for_platform() -> impl ShaderCompiler {
+    SoftwareShaders
+}
+
+/// Compiles shaders for the software graphics backend, which cannot execute shader byte code. Instead, the byte code
+/// is the file name of the shader without the `hlsl` extension, e.g. `sprite.vs`, under which programs equivalent to
+/// the shader have to be registered with the software device.
+///
+/// The HLSL source is therefore not the source of truth for the software backend: Only the reflection data is
+/// extracted from it, while the programs are translated from it by hand. Changes to a shader's body have no effect
+/// until its program has been updated; changes to its interface are detected by comparing the reflection data with
+/// the interface the program has been written for.
+pub struct SoftwareShaders;
+
+impl ShaderCompiler for SoftwareShaders {
+    fn compile(&self, path: &Path, _kind: AssetKind) -> Result<Vec<u8>, String> {
+        match path.file_stem().and_then(|name| name.to_str()) {
+            Some(name) => Ok(name.as_bytes().to_vec()),
+            None => Err(format!("The shader '{}' has no name.", path.display())),
+        }
+    }
+}
+
+/// Loads the byte code of Direct3D 11 shaders from files next to their HLSL source, so that the game can be built for
+/// Windows on platforms without the D3D shader compiler. The HLSL source remains the source of truth: The files are
+/// created by [`precompile`] on Windows and are rejected once the source has changed. As long as they have not been
+/// created and committed, loading them fails, and so does building for Windows on other platforms.
+pub struct PrecompiledShaders;
+
+impl ShaderCompiler for PrecompiledShaders {
+    fn compile(&self, path: &Path, _kind: AssetKind) -> Result<Vec<u8>, String> {
+        let source = fs::read(path).map_err(|e| format!("Failed to read file '{}': {e}.", path.display()))?;
+        let precompiled_path = precompiled_path(path);
+        let Ok(precompiled) = fs::read(&precompiled_path) else {
+            return Err(format!(
+                "The shader has not been precompiled; run `{PRECOMPILE_COMMAND}` on Windows to create '{}'.",
+                precompiled_path.display()
+            ));
+        };
+
+        match load(&precompiled) {
+            Some((checksum, byte_code)) if checksum == source_checksum(&source) => Ok(byte_code.to_vec()),
+            Some(_) => Err(format!(
+                "The precompiled shader '{}' is out of date; run `{PRECOMPILE_COMMAND}` on Windows to update it.",
+                precompiled_path.display()
+            )),
+            None => Err(format!("The precompiled shader '{}' is invalid.", precompiled_path.display())),
+        }
+    }
+
+    fn dependencies(&self, path: &str) -> Vec<String> {
+        let precompiled_path = precompiled_path(Path::new(path));
+        vec![precompiled_path.to_string_lossy().into_owned()]
+    }
+}
+
+/// Compiles the shaders listed in the manifest of the source directory and writes their byte code next to them for
+/// [`PrecompiledShaders`], returning the paths of the files that have changed. The files are meant to be committed
+/// along with the HLSL source, so they have to be created with the same compiler settings on every machine.
+pub fn precompile(source: &Path, compiler: &dyn ShaderCompiler) -> Result<Vec<PathBuf>, String> {
+    let manifest_path = source.join(pipeline::MANIFEST);
+    let manifest =
+        fs::read_to_string(&manifest_path).map_err(|e| format!("Failed to read manifest '{}': {e}.", manifest_path.display()))?;
+    let assets =
+        pipeline::parse_manifest(&manifest).map_err(|e| format!("Invalid manifest '{}': {e}", manifest_path.display()))?;
+
+    let mut changed = vec![];
+    for asset in assets {
+        if !matches!(asset.kind, AssetKind::VertexShader | AssetKind::PixelShader) {
+            continue;
+        }
+
+        let path = source.join(&asset.path);
+        let shader = fs::read(&path).map_err(|e| format!("Failed to read file '{}': {e}.", path.display()))?;
+        let byte_code = compiler
+            .compile(&path, asset.kind)
+            .map_err(|e| format!("Failed to compile {} '{}': {e}", asset.kind.name(), asset.path))?;
+        let precompiled = [&MAGIC[..], &source_checksum(&shader).to_le_bytes(), &byte_code].concat();
+
+        let precompiled_path = precompiled_path(&path);
+        if fs::read(&precompiled_path).ok().as_ref() != Some(&precompiled) {
+            fs::write(&precompiled_path, precompiled)
+                .map_err(|e| format!("Failed to write file '{}': {e}.", precompiled_path.display()))?;
+            changed.push(precompiled_path);
+        }
+    }
+
+    Ok(changed)
+}
+
+fn precompiled_path(path: &Path) -> PathBuf {
+    path.with_extension(PRECOMPILED_EXTENSION)
+}
+
+/// Computes the checksum of a shader's source. Line endings are normalized, so that precompiled shaders remain valid
+/// when Git converts the line endings of the source on checkout.
+fn source_checksum(source: &[u8]) -> u32 {
+    crc32(&String::from_utf8_lossy(source).replace("\r\n", "\n").into_bytes())
+}
+
+/// Splits a precompiled shader into the checksum of the source it was compiled from and its byte code.
+fn load(data: &[u8]) -> Option<(u32, &[u8])> {
+    let mut reader = Reader::new(data, "precompiled shader");
+    if &reader.array().ok()? != MAGIC {
+        return None;
+    }
+
+    let checksum = reader.u32_le().ok()?;
+    Some((checksum, reader.bytes(reader.remaining()).ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::directory;
+
+    fn reverse(path: &Path, _: AssetKind) -> Result<Vec<u8>, String> {
+        Ok(fs::read(path).unwrap().into_iter().rev().collect())
+    }
+
+    #[test]
+    fn precompiled_shaders_are_used_without_a_compiler() {
+        let directory = directory("shader-compiler-precompiled");
+        let path = directory.join("sprite.vs.hlsl");
+        fs::write(
+            directory.join(pipeline::MANIFEST),
+            "vertex-shader sprite.vs.hlsl\ntexture ship.png",
+        )
+        .unwrap();
+        fs::write(&path, "void main() {}\n").unwrap();
+
+        let error = PrecompiledShaders.compile(&path, AssetKind::VertexShader).unwrap_err();
+        assert!(error.starts_with("The shader has not been precompiled; "), "{error}");
+
+        assert_eq!(
+            precompile(&directory, &reverse),
+            Ok(vec![directory.join("sprite.vs.compiled")])
+        );
+        assert_eq!(precompile(&directory, &reverse), Ok(vec![]));
+        assert_eq!(
+            PrecompiledShaders.compile(&path, AssetKind::VertexShader).unwrap(),
+            b"\n}{ )(niam diov"
+        );
+        assert_eq!(
+            PrecompiledShaders.dependencies("shaders/sprite.vs.hlsl"),
+            ["shaders/sprite.vs.compiled"]
+        );
+
+        // Different line endings do not invalidate the precompiled shader, but other changes do.
+        fs::write(&path, "void main() {}\r\n").unwrap();
+        assert!(PrecompiledShaders.compile(&path, AssetKind::VertexShader).is_ok());
+
+        fs::write(&path, "void main() { }\n").unwrap();
+        let error = PrecompiledShaders.compile(&path, AssetKind::VertexShader).unwrap_err();
+        assert!(error.contains("is out of date"), "{error}");
+
+        fs::write(directory.join("sprite.vs.compiled"), b"DXBC").unwrap();
+        let error = PrecompiledShaders.compile(&path, AssetKind::VertexShader).unwrap_err();
+        assert!(error.ends_with("is invalid."), "{error}");
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn software_shaders_are_named_after_their_source() {
+        assert_eq!(
+            SoftwareShaders.compile(Path::new("shaders/sprite.ps.hlsl"), AssetKind::PixelShader),
+            Ok(b"sprite.ps".to_vec())
+        );
+    }
+}
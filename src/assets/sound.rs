@@ -0,0 +1,185 @@
+use super::{reader::Reader, DecodeError};
+
+/// Identifies compiled sounds and the version of their format.
+pub(super) const MAGIC: &[u8; 4] = b"SND1";
+
+const WAVE_FORMAT_PCM: u16 = 1;
+const WAVE_FORMAT_IEEE_FLOAT: u16 = 3;
+const WAVE_FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+/// A compiled sound consisting of interleaved signed 16 bit samples.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sound {
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub samples: Vec<i16>,
+}
+
+/// Compiles a mono or stereo WAV file with 8, 16, 24 or 32 bit integer or 32 bit floating point samples into the
+/// runtime sound format.
+pub fn compile(source: &[u8]) -> Result<Vec<u8>, DecodeError> {
+    let sound = decode_wav(source)?;
+
+    let mut compiled = Vec::with_capacity(14 + sound.samples.len() * 2);
+    compiled.extend_from_slice(MAGIC);
+    compiled.extend_from_slice(&sound.sample_rate.to_le_bytes());
+    compiled.extend_from_slice(&sound.channels.to_le_bytes());
+    compiled.extend_from_slice(&(sound.samples.len() as u32).to_le_bytes());
+    for sample in sound.samples {
+        compiled.extend_from_slice(&sample.to_le_bytes());
+    }
+
+    Ok(compiled)
+}
+
+pub fn load(data: &[u8]) -> Result<Sound, DecodeError> {
+    let mut reader = Reader::new(data, "sound");
+    if &reader.array()? != MAGIC {
+        return Err(reader.error("Invalid signature."));
+    }
+
+    let sample_rate = reader.u32_le()?;
+    let channels = reader.u16_le()?;
+    let sample_count = reader.u32_le()? as usize;
+    let samples = reader
+        .bytes(sample_count * 2)?
+        .chunks_exact(2)
+        .map(|sample| i16::from_le_bytes([sample[0], sample[1]]))
+        .collect();
+
+    Ok(Sound {
+        sample_rate,
+        channels,
+        samples,
+    })
+}
+
+fn decode_wav(data: &[u8]) -> Result<Sound, DecodeError> {
+    let mut reader = Reader::new(data, "WAV");
+    if &reader.array()? != b"RIFF" {
+        return Err(reader.error("Invalid signature."));
+    }
+
+    reader.u32_le()?; // the file size
+    if &reader.array()? != b"WAVE" {
+        return Err(reader.error("Invalid signature."));
+    }
+
+    let mut format = None;
+    loop {
+        let id = reader.array::<4>()?;
+        let size = reader.u32_le()? as usize;
+        let chunk = reader.bytes(size)?;
+        // Chunks are padded to an even number of bytes.
+        if size % 2 == 1 && reader.remaining() > 0 {
+            reader.u8()?;
+        }
+
+        match &id {
+            b"fmt " => {
+                let mut chunk = Reader::new(chunk, "WAV");
+                let mut tag = chunk.u16_le()?;
+                let channels = chunk.u16_le()?;
+                let sample_rate = chunk.u32_le()?;
+                chunk.bytes(6)?; // the byte rate and block alignment
+                let bits = chunk.u16_le()?;
+                if tag == WAVE_FORMAT_EXTENSIBLE {
+                    // The actual format is stored in the first two bytes of the sub format GUID.
+                    chunk.bytes(8)?;
+                    tag = chunk.u16_le()?;
+                }
+
+                if !matches!(channels, 1 | 2) {
+                    return Err(reader.error("Unsupported channel count."));
+                }
+
+                format = Some((tag, channels, sample_rate, bits));
+            }
+            b"data" => {
+                let (tag, channels, sample_rate, bits) = format.ok_or(reader.error("Missing format chunk."))?;
+                let convert: fn(&[u8]) -> i16 = match (tag, bits) {
+                    (WAVE_FORMAT_PCM, 8) => |s| ((s[0] as i16) - 128) << 8,
+                    (WAVE_FORMAT_PCM, 16) => |s| i16::from_le_bytes([s[0], s[1]]),
+                    (WAVE_FORMAT_PCM, 24) => |s| i16::from_le_bytes([s[1], s[2]]),
+                    (WAVE_FORMAT_PCM, 32) => |s| i16::from_le_bytes([s[2], s[3]]),
+                    (WAVE_FORMAT_IEEE_FLOAT, 32) => {
+                        |s| (f32::from_le_bytes([s[0], s[1], s[2], s[3]]).clamp(-1.0, 1.0) * i16::MAX as f32) as i16
+                    }
+                    _ => return Err(reader.error("Unsupported sample format.")),
+                };
+
+                let frame_size = (bits / 8 * channels) as usize;
+                if chunk.len() % frame_size != 0 {
+                    return Err(reader.error("Incomplete sample frame."));
+                }
+
+                return Ok(Sound {
+                    sample_rate,
+                    channels,
+                    samples: chunk.chunks_exact(bits as usize / 8).map(convert).collect(),
+                });
+            }
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wav(tag: u16, channels: u16, bits: u16, samples: &[u8]) -> Vec<u8> {
+        let mut wav = b"RIFF\0\0\0\0WAVE".to_vec();
+        wav.extend_from_slice(b"LIST\x03\0\0\0abc\0");
+        wav.extend_from_slice(b"fmt \x10\0\0\0");
+        wav.extend_from_slice(&tag.to_le_bytes());
+        wav.extend_from_slice(&channels.to_le_bytes());
+        wav.extend_from_slice(&22050u32.to_le_bytes());
+        wav.extend_from_slice(&[0; 6]);
+        wav.extend_from_slice(&bits.to_le_bytes());
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&(samples.len() as u32).to_le_bytes());
+        wav.extend_from_slice(samples);
+        wav
+    }
+
+    fn round_trip(wav: &[u8]) -> Result<Sound, DecodeError> {
+        load(&compile(wav)?)
+    }
+
+    #[test]
+    fn samples_are_converted_to_16_bits() {
+        let sound = |channels, samples: Vec<i16>| Sound {
+            sample_rate: 22050,
+            channels,
+            samples,
+        };
+
+        assert_eq!(
+            round_trip(&wav(1, 1, 8, &[0, 128, 255])),
+            Ok(sound(1, vec![-32768, 0, 32512]))
+        );
+        assert_eq!(round_trip(&wav(1, 2, 16, &[1, 0, 0xFF, 0xFF])), Ok(sound(2, vec![1, -1])));
+        assert_eq!(round_trip(&wav(1, 1, 24, &[0xAA, 0x34, 0x12])), Ok(sound(1, vec![0x1234])));
+
+        let floats = [1.5f32, -1.0, 0.0]
+            .iter()
+            .flat_map(|sample| sample.to_le_bytes())
+            .collect::<Vec<_>>();
+        assert_eq!(round_trip(&wav(3, 1, 32, &floats)), Ok(sound(1, vec![32767, -32767, 0])));
+    }
+
+    #[test]
+    fn malformed_files_are_rejected() {
+        for (wav, message) in [
+            (wav(1, 1, 16, &[0, 0])[..12].to_vec(), "Unexpected end of data."),
+            (b"RIFF\0\0\0\0WAVEdata\0\0\0\0".to_vec(), "Missing format chunk."),
+            (wav(1, 6, 16, &[0; 12]), "Unsupported channel count."),
+            (wav(1, 1, 12, &[0; 3]), "Unsupported sample format."),
+            (wav(2, 1, 16, &[0; 2]), "Unsupported sample format."),
+            (wav(1, 2, 16, &[0; 6]), "Incomplete sample frame."),
+        ] {
+            assert_eq!(compile(&wav).map_err(|error| error.message), Err(message));
+        }
+    }
+}
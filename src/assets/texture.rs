@@ -0,0 +1,60 @@
+use super::{image, reader::Reader, DecodeError};
+
+/// Identifies compiled textures and the version of their format.
+pub(super) const MAGIC: &[u8; 4] = b"TEX1";
+
+/// A compiled texture, whose RGBA8 pixels can be uploaded without any further conversion. Mipmaps are generated
+/// by the graphics device when the texture is created.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Texture<'a> {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: &'a [u8],
+}
+
+/// Compiles a PNG, BMP or TGA image into the runtime texture format.
+pub fn compile(source: &[u8]) -> Result<Vec<u8>, DecodeError> {
+    let image = image::decode(source)?;
+
+    let mut texture = Vec::with_capacity(12 + image.pixels.len());
+    texture.extend_from_slice(MAGIC);
+    texture.extend_from_slice(&image.width.to_le_bytes());
+    texture.extend_from_slice(&image.height.to_le_bytes());
+    texture.extend_from_slice(&image.pixels);
+    Ok(texture)
+}
+
+pub fn load(data: &[u8]) -> Result<Texture<'_>, DecodeError> {
+    let mut reader = Reader::new(data, "texture");
+    if &reader.array()? != MAGIC {
+        return Err(reader.error("Invalid signature."));
+    }
+
+    let width = reader.u32_le()?;
+    let height = reader.u32_le()?;
+    let pixels = reader.bytes(width as usize * height as usize * 4)?;
+    Ok(Texture { width, height, pixels })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compiled_textures_contain_the_decoded_pixels() {
+        let source = include_bytes!("../../tests/images/rgba8.png");
+        let image = image::decode(source).unwrap();
+        let compiled = compile(source).unwrap();
+
+        assert_eq!(
+            load(&compiled),
+            Ok(Texture {
+                width: 7,
+                height: 5,
+                pixels: &image.pixels
+            })
+        );
+        assert!(load(&compiled[..compiled.len() - 1]).is_err());
+        assert!(load(b"TEX0").is_err());
+    }
+}
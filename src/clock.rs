@@ -0,0 +1,253 @@
+use std::{
+    cell::Cell,
+    collections::VecDeque,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+const STATISTICS_SAMPLES: usize = 120;
+
+pub trait TimeSource {
+    /// Gets the time that has elapsed since some fixed point in the past.
+    fn now(&self) -> Duration;
+}
+
+pub struct SystemTimeSource {
+    start: Instant,
+}
+
+/// A time source that only advances when told to. Clones share the same time, so a test can keep a clone around to
+/// control the time source that it has handed to a `GameClock`.
+#[derive(Clone, Default)]
+pub struct ManualTimeSource {
+    now: Rc<Cell<Duration>>,
+}
+
+/// Divides the time between frames into simulation ticks of a fixed duration. Time that is left over is carried over
+/// to the next frame and can be used to interpolate between the last two simulation states when rendering.
+pub struct GameClock<T: TimeSource> {
+    time_source: T,
+    tick_duration: Duration,
+    max_ticks_per_frame: u32,
+    last_time: Duration,
+    accumulator: Duration,
+    tick: u64,
+    statistics: FrameStatistics,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameTiming {
+    /// The time that has passed since the previous frame.
+    pub frame_time: Duration,
+    /// The number of simulation ticks to run this frame.
+    pub ticks: u32,
+    /// How far the time has progressed towards the next tick, between 0 and 1; the rendered state should be
+    /// interpolated by this factor between the states of the previous and the latest tick.
+    pub alpha: f32,
+    /// The time that was not simulated because more than the maximum number of ticks per frame would have been
+    /// required to catch up.
+    pub dropped_time: Duration,
+}
+
+pub struct FrameStatistics {
+    frame_times: VecDeque<Duration>,
+}
+
+impl SystemTimeSource {
+    pub fn new() -> SystemTimeSource {
+        SystemTimeSource { start: Instant::now() }
+    }
+}
+
+impl TimeSource for SystemTimeSource {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+impl ManualTimeSource {
+    pub fn new() -> ManualTimeSource {
+        ManualTimeSource::default()
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        self.now.set(self.now.get() + duration);
+    }
+}
+
+impl TimeSource for ManualTimeSource {
+    fn now(&self) -> Duration {
+        self.now.get()
+    }
+}
+
+impl<T: TimeSource> GameClock<T> {
+    pub fn new(time_source: T, ticks_per_second: u32) -> GameClock<T> {
+        assert!(ticks_per_second > 0, "The game clock requires at least one tick per second.");
+        let last_time = time_source.now();
+        GameClock {
+            time_source,
+            tick_duration: Duration::from_secs(1) / ticks_per_second,
+            // Without a limit, a slow frame requires many ticks, making the next frame even slower, and so on.
+            max_ticks_per_frame: 8,
+            last_time,
+            accumulator: Duration::ZERO,
+            tick: 0,
+            statistics: FrameStatistics {
+                frame_times: VecDeque::with_capacity(STATISTICS_SAMPLES),
+            },
+        }
+    }
+
+    pub fn set_max_ticks_per_frame(&mut self, max_ticks_per_frame: u32) {
+        self.max_ticks_per_frame = max_ticks_per_frame;
+    }
+
+    /// Starts a new frame, determining how many ticks have to be simulated to catch up with the time source.
+    pub fn advance(&mut self) -> FrameTiming {
+        let now = self.time_source.now();
+        let frame_time = now.saturating_sub(self.last_time);
+        self.last_time = now;
+        self.statistics.record(frame_time);
+
+        self.accumulator += frame_time;
+        let required_ticks = self.accumulator.as_nanos() / self.tick_duration.as_nanos();
+        let ticks = required_ticks.min(self.max_ticks_per_frame as u128) as u32;
+        self.accumulator -= self.tick_duration * ticks;
+        self.tick += ticks as u64;
+
+        let mut dropped_time = Duration::ZERO;
+        if ticks as u128 != required_ticks {
+            dropped_time = self.tick_duration * (required_ticks - ticks as u128) as u32;
+            self.accumulator -= dropped_time;
+        }
+
+        FrameTiming {
+            frame_time,
+            ticks,
+            alpha: self.accumulator.as_secs_f32() / self.tick_duration.as_secs_f32(),
+            dropped_time,
+        }
+    }
+
+    pub fn tick_duration(&self) -> Duration {
+        self.tick_duration
+    }
+
+    /// Gets the number of ticks that have been simulated so far.
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    pub fn statistics(&self) -> &FrameStatistics {
+        &self.statistics
+    }
+}
+
+impl FrameStatistics {
+    fn record(&mut self, frame_time: Duration) {
+        if self.frame_times.len() == STATISTICS_SAMPLES {
+            self.frame_times.pop_front();
+        }
+
+        self.frame_times.push_back(frame_time);
+    }
+
+    /// Gets the average time of the most recent frames.
+    pub fn average(&self) -> Duration {
+        match self.frame_times.len() {
+            0 => Duration::ZERO,
+            count => self.frame_times.iter().sum::<Duration>() / count as u32,
+        }
+    }
+
+    pub fn min(&self) -> Duration {
+        self.frame_times.iter().copied().min().unwrap_or_default()
+    }
+
+    pub fn max(&self) -> Duration {
+        self.frame_times.iter().copied().max().unwrap_or_default()
+    }
+
+    pub fn frames_per_second(&self) -> f32 {
+        match self.average().as_secs_f32() {
+            average if average > 0. => 1. / average,
+            _ => 0.,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TICK: Duration = Duration::from_millis(10);
+
+    fn clock() -> (GameClock<ManualTimeSource>, ManualTimeSource) {
+        let time_source = ManualTimeSource::new();
+        (GameClock::new(time_source.clone(), 100), time_source)
+    }
+
+    #[test]
+    fn time_is_accumulated_into_fixed_ticks() {
+        let (mut clock, time_source) = clock();
+        assert_eq!(clock.tick_duration(), TICK);
+
+        time_source.advance(Duration::from_millis(25));
+        let frame = clock.advance();
+        assert_eq!((frame.frame_time, frame.ticks), (Duration::from_millis(25), 2));
+        assert!((frame.alpha - 0.5).abs() < 1e-6);
+
+        // The remaining 5ms are carried over to the next frame.
+        time_source.advance(Duration::from_millis(7));
+        let frame = clock.advance();
+        assert_eq!(frame.ticks, 1);
+        assert!((frame.alpha - 0.2).abs() < 1e-6);
+
+        let frame = clock.advance();
+        assert_eq!((frame.frame_time, frame.ticks), (Duration::ZERO, 0));
+        assert_eq!(clock.tick(), 3);
+    }
+
+    #[test]
+    fn catching_up_is_limited() {
+        let (mut clock, time_source) = clock();
+        clock.set_max_ticks_per_frame(4);
+
+        time_source.advance(Duration::from_millis(73));
+        let frame = clock.advance();
+        assert_eq!(frame.ticks, 4);
+        assert_eq!(frame.dropped_time, Duration::from_millis(30));
+        assert!((frame.alpha - 0.3).abs() < 1e-6);
+        assert_eq!(clock.tick(), 4);
+
+        // The dropped time is not simulated later on.
+        time_source.advance(Duration::from_millis(7));
+        let frame = clock.advance();
+        assert_eq!((frame.ticks, frame.dropped_time), (1, Duration::ZERO));
+        assert_eq!(clock.tick(), 5);
+    }
+
+    #[test]
+    fn frame_times_are_recorded() {
+        let (mut clock, time_source) = clock();
+        assert_eq!(clock.statistics().frames_per_second(), 0.);
+
+        for millis in [10, 30, 20] {
+            time_source.advance(Duration::from_millis(millis));
+            clock.advance();
+        }
+
+        let statistics = clock.statistics();
+        assert_eq!(statistics.average(), Duration::from_millis(20));
+        assert_eq!(statistics.min(), TICK);
+        assert_eq!(statistics.max(), Duration::from_millis(30));
+        assert!((statistics.frames_per_second() - 50.).abs() < 1e-3);
+    }
+
+    #[test]
+    #[should_panic(expected = "The game clock requires at least one tick per second.")]
+    fn clocks_must_tick() {
+        GameClock::new(ManualTimeSource::new(), 0);
+    }
+}
@@ -0,0 +1,173 @@
+//! Describes fatal errors in enough detail to diagnose them from a player's bug report: Besides the error itself, a
+//! report contains the location and backtrace of panics, the most recent log entries, the version of the game and
+//! the state of the window and the graphics device.
+
+use crate::log::{self, format_time};
+use std::{
+    collections::BTreeMap,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    sync::{Mutex, PoisonError},
+    time::SystemTime,
+};
+
+/// The state the game reports if it crashes, e.g. the size of the window; maps the descriptions of the values to the
+/// values.
+static CONTEXT: Mutex<BTreeMap<&'static str, String>> = Mutex::new(BTreeMap::new());
+
+/// Sets a value that is included in crash reports, replacing the previous value with the same description.
+pub fn set_context(description: &'static str, value: impl Into<String>) {
+    CONTEXT
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .insert(description, value.into());
+}
+
+pub struct CrashReport {
+    pub time: SystemTime,
+    pub message: String,
+    /// The source location of a panic, e.g. `src/lib.rs:12:5`; errors returned by the game have no location.
+    pub location: Option<String>,
+    pub backtrace: Option<String>,
+    pub context: BTreeMap<&'static str, String>,
+    pub log: Vec<log::Entry>,
+}
+
+impl CrashReport {
+    /// Captures the context set with `set_context` and the most recent log entries.
+    pub fn new(message: &str, location: Option<String>, backtrace: Option<String>) -> CrashReport {
+        CrashReport {
+            time: SystemTime::now(),
+            message: message.to_string(),
+            location,
+            backtrace,
+            context: CONTEXT.lock().unwrap_or_else(PoisonError::into_inner).clone(),
+            log: log::recent(),
+        }
+    }
+
+    /// Writes the report to the `crashes` directory within the game's data directory.
+    pub fn write(&self) -> io::Result<PathBuf> {
+        self.write_to(&log::data_directory().join("crashes"))
+    }
+
+    /// Writes the report to a file in the directory that is named after the time of the crash, e.g.
+    /// `crash-2021-03-14-15-09-26.txt`.
+    pub fn write_to(&self, directory: &Path) -> io::Result<PathBuf> {
+        fs::create_dir_all(directory)?;
+
+        // The milliseconds are dropped, as it is unlikely that the game crashes twice within a second.
+        let time = format_time(self.time)[..19].replace([' ', ':'], "-");
+        let path = directory.join(format!("crash-{time}.txt"));
+        fs::write(&path, self.to_string())?;
+        Ok(path)
+    }
+}
+
+impl fmt::Display for CrashReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let build = if cfg!(debug_assertions) { "debug" } else { "release" };
+        writeln!(
+            f,
+            "lwar {} ({build} build, {} {}) crashed at {} UTC.",
+            env!("CARGO_PKG_VERSION"),
+            std::env::consts::OS,
+            std::env::consts::ARCH,
+            format_time(self.time)
+        )?;
+
+        writeln!(f, "\nError: {}", self.message)?;
+        if let Some(location) = &self.location {
+            writeln!(f, "Location: {location}")?;
+        }
+
+        if !self.context.is_empty() {
+            writeln!(f, "\nContext:")?;
+            for (description, value) in &self.context {
+                writeln!(f, "    {description}: {value}")?;
+            }
+        }
+
+        if !self.log.is_empty() {
+            writeln!(f, "\nRecent log entries:")?;
+            for entry in &self.log {
+                writeln!(f, "    {entry}")?;
+            }
+        }
+
+        if let Some(backtrace) = &self.backtrace {
+            writeln!(f, "\nBacktrace:\n{backtrace}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        log::{Category, Entry, Level},
+        test_util::directory,
+    };
+    use std::{env, time::Duration, time::UNIX_EPOCH};
+
+    fn report() -> CrashReport {
+        let time = UNIX_EPOCH + Duration::from_millis(1_615_734_566_535);
+        CrashReport {
+            time,
+            message: "attempt to subtract with overflow".to_string(),
+            location: Some("src/lib.rs:12:5".to_string()),
+            backtrace: Some("   0: lwar::run\n   1: main".to_string()),
+            context: BTreeMap::from([("Window size", "1280x720".to_string())]),
+            log: vec![Entry {
+                time,
+                level: Level::Warning,
+                category: Category::Graphics,
+                message: "The graphics device has been lost.".to_string(),
+            }],
+        }
+    }
+
+    #[test]
+    fn reports_describe_the_crash() {
+        let version = env!("CARGO_PKG_VERSION");
+        let (os, arch) = (env::consts::OS, env::consts::ARCH);
+        let build = if cfg!(debug_assertions) { "debug" } else { "release" };
+
+        assert_eq!(
+            report().to_string(),
+            format!(
+                "lwar {version} ({build} build, {os} {arch}) crashed at 2021-03-14 15:09:26.535 UTC.
+
+Error: attempt to subtract with overflow
+Location: src/lib.rs:12:5
+
+Context:
+    Window size: 1280x720
+
+Recent log entries:
+    2021-03-14 15:09:26.535 [warning] [graphics] The graphics device has been lost.
+
+Backtrace:
+   0: lwar::run
+   1: main
+"
+            )
+        );
+
+        let report = CrashReport::new("Failed to create window.", None, None).to_string();
+        assert!(report.contains("\nError: Failed to create window.\n"));
+        assert!(!report.contains("Location:") && !report.contains("Backtrace:"));
+    }
+
+    #[test]
+    fn reports_are_written_to_timestamped_files() {
+        let directory = directory("crash-report");
+        let path = report().write_to(&directory).unwrap();
+
+        assert_eq!(path, directory.join("crash-2021-03-14-15-09-26.txt"));
+        assert_eq!(fs::read_to_string(&path).unwrap(), report().to_string());
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
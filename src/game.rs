@@ -0,0 +1,135 @@
+use crate::{
+    input::{
+        bindings::{Action, Bindings},
+        state::InputState,
+    },
+    primitives::Vector2,
+};
+use std::{f32::consts::PI, time::Duration};
+
+/// The angle the ship turns by per second, in radians.
+const TURN_SPEED: f32 = PI;
+
+/// The acceleration of the ship while thrusting, in pixels per second squared.
+const ACCELERATION: f32 = 240.;
+
+/// The fraction of its velocity the ship loses per second, so that it comes to a halt when it stops thrusting.
+const DRAG: f32 = 0.5;
+
+/// The player's ship, which turns and thrusts according to the player's input. The state of the previous tick is kept,
+/// so that the ship can be drawn between the previous and the latest tick.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ship {
+    position: Vector2,
+    velocity: Vector2,
+    /// The clockwise rotation in radians; the ship points to the right if it is not rotated.
+    rotation: f32,
+    previous_position: Vector2,
+    previous_rotation: f32,
+}
+
+impl Ship {
+    pub fn new(position: Vector2) -> Ship {
+        Ship {
+            position,
+            velocity: Vector2::default(),
+            rotation: 0.,
+            previous_position: position,
+            previous_rotation: 0.,
+        }
+    }
+
+    /// Simulates a single tick of the given duration.
+    pub fn update(&mut self, bindings: &Bindings, input: &InputState, tick_duration: Duration) {
+        let seconds = tick_duration.as_secs_f32();
+        self.previous_position = self.position;
+        self.previous_rotation = self.rotation;
+
+        if bindings.is_down(Action::TurnLeft, input) {
+            self.rotation -= TURN_SPEED * seconds;
+        }
+        if bindings.is_down(Action::TurnRight, input) {
+            self.rotation += TURN_SPEED * seconds;
+        }
+
+        if bindings.is_down(Action::Thrust, input) {
+            let (sin, cos) = self.rotation.sin_cos();
+            self.velocity.x += cos * ACCELERATION * seconds;
+            self.velocity.y += sin * ACCELERATION * seconds;
+        }
+
+        let drag = (1. - DRAG).powf(seconds);
+        self.velocity = Vector2::new(self.velocity.x * drag, self.velocity.y * drag);
+        self.position.x += self.velocity.x * seconds;
+        self.position.y += self.velocity.y * seconds;
+    }
+
+    /// Gets the position and rotation of the ship between the previous and the latest tick, where an `alpha` of 0 is
+    /// the previous and 1 the latest tick.
+    pub fn interpolate(&self, alpha: f32) -> (Vector2, f32) {
+        let lerp = |previous: f32, latest: f32| previous + (latest - previous) * alpha;
+        (
+            Vector2::new(
+                lerp(self.previous_position.x, self.position.x),
+                lerp(self.previous_position.y, self.position.y),
+            ),
+            lerp(self.previous_rotation, self.rotation),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::{input::Key, Event};
+
+    const TICK: Duration = Duration::from_millis(250);
+
+    fn input(keys: &[Key]) -> InputState {
+        let mut input = InputState::new();
+        input.begin_frame();
+        for &key in keys {
+            input.handle_event(Event::KeyPressed(key, 0));
+        }
+        input
+    }
+
+    #[test]
+    fn ships_turn_and_thrust() {
+        let bindings = Bindings::default();
+        let mut ship = Ship::new(Vector2::new(100., 100.));
+
+        ship.update(&bindings, &input(&[]), TICK);
+        assert_eq!(ship.interpolate(1.), (Vector2::new(100., 100.), 0.));
+
+        ship.update(&bindings, &input(&[Key::Right]), TICK);
+        assert_eq!(ship.interpolate(1.), (Vector2::new(100., 100.), PI / 4.));
+        ship.update(&bindings, &input(&[Key::Right]), TICK);
+        ship.update(&bindings, &input(&[Key::Left]), TICK);
+        assert_eq!(ship.interpolate(1.).1, PI / 4.);
+
+        // The ship accelerates in the direction it is pointing to and slows down once it stops thrusting.
+        ship.update(&bindings, &input(&[Key::Up]), TICK);
+        let (position, _) = ship.interpolate(1.);
+        assert!(position.x > 100. && position.y > 100.);
+        assert!((position.x - position.y).abs() < 1e-3);
+
+        let speed = |ship: &Ship| ship.velocity.x.hypot(ship.velocity.y);
+        let thrusting_speed = speed(&ship);
+        ship.update(&bindings, &input(&[]), TICK);
+        assert!(speed(&ship) < thrusting_speed);
+    }
+
+    #[test]
+    fn ships_are_interpolated_between_ticks() {
+        let mut ship = Ship::new(Vector2::new(0., 0.));
+        ship.update(&Bindings::default(), &input(&[Key::Right, Key::Up]), TICK);
+
+        let (position, rotation) = ship.interpolate(1.);
+        assert_eq!(ship.interpolate(0.), (Vector2::new(0., 0.), 0.));
+        assert_eq!(
+            ship.interpolate(0.5),
+            (Vector2::new(position.x / 2., position.y / 2.), rotation / 2.)
+        );
+    }
+}
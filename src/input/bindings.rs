@@ -0,0 +1,685 @@
+use super::state::{Button, InputState};
+use crate::{
+    log,
+    platform::{
+        error::Error,
+        input::{Key, MouseButton, PhysicalKey},
+        Event,
+    },
+};
+use std::{
+    collections::BTreeMap,
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+/// The logical actions of the game that the player can bind to keys and mouse buttons.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Action {
+    Thrust,
+    TurnLeft,
+    TurnRight,
+    FirePrimary,
+    FireSecondary,
+    Scoreboard,
+    Chat,
+}
+
+impl Action {
+    pub const ALL: [Action; 7] = [
+        Action::Thrust,
+        Action::TurnLeft,
+        Action::TurnRight,
+        Action::FirePrimary,
+        Action::FireSecondary,
+        Action::Scoreboard,
+        Action::Chat,
+    ];
+}
+
+/// The modifier keys that have to be held for a chord; the left and right keys are not distinguished.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Modifiers {
+    pub control: bool,
+    pub shift: bool,
+    pub alt: bool,
+}
+
+impl Modifiers {
+    pub const NONE: Modifiers = Modifiers {
+        control: false,
+        shift: false,
+        alt: false,
+    };
+
+    /// The modifiers that are currently held.
+    pub fn held(input: &InputState) -> Modifiers {
+        Modifiers {
+            control: input.is_down(Key::LeftControl) || input.is_down(Key::RightControl),
+            shift: input.is_down(Key::LeftShift) || input.is_down(Key::RightShift),
+            alt: input.is_down(Key::LeftAlt) || input.is_down(Key::RightAlt),
+        }
+    }
+
+    /// The modifier the key corresponds to, or no modifiers for all other keys.
+    fn of(key: Key) -> Modifiers {
+        Modifiers {
+            control: matches!(key, Key::LeftControl | Key::RightControl),
+            shift: matches!(key, Key::LeftShift | Key::RightShift),
+            alt: matches!(key, Key::LeftAlt | Key::RightAlt),
+        }
+    }
+
+    fn contains(self, other: Modifiers) -> bool {
+        (self.control || !other.control) && (self.shift || !other.shift) && (self.alt || !other.alt)
+    }
+
+    fn union(self, other: Modifiers) -> Modifiers {
+        Modifiers {
+            control: self.control || other.control,
+            shift: self.shift || other.shift,
+            alt: self.alt || other.alt,
+        }
+    }
+
+    fn names(self) -> impl Iterator<Item = &'static str> {
+        [(self.control, "Control"), (self.shift, "Shift"), (self.alt, "Alt")]
+            .into_iter()
+            .filter_map(|(held, name)| held.then_some(name))
+    }
+}
+
+/// A key or mouse button that triggers an action while the modifiers are held, written like `Control+Shift+W`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Chord {
+    pub modifiers: Modifiers,
+    pub button: Button,
+}
+
+impl Chord {
+    pub fn new(button: impl Into<Button>) -> Chord {
+        Chord {
+            modifiers: Modifiers::NONE,
+            button: button.into(),
+        }
+    }
+
+    pub fn with(modifiers: Modifiers, button: impl Into<Button>) -> Chord {
+        Chord {
+            modifiers,
+            button: button.into(),
+        }
+    }
+}
+
+impl fmt::Display for Chord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for name in self.modifiers.names() {
+            write!(f, "{name}+")?;
+        }
+
+        match self.button {
+            Button::Key(key) => write!(f, "{key:?}"),
+            // Physical keys and mouse buttons are prefixed, as some of their names coincide with the names of keys.
+            Button::Physical(key) => write!(f, "Physical{key:?}"),
+            Button::Mouse(button) => write!(f, "Mouse{button:?}"),
+        }
+    }
+}
+
+fn parse_chord(chord: &str) -> Result<Chord, String> {
+    let (modifier_names, button) = chord.rsplit_once('+').unwrap_or(("", chord));
+    let mut modifiers = Modifiers::NONE;
+    for name in modifier_names.split('+').filter(|name| !name.is_empty()) {
+        match name.trim() {
+            "Control" => modifiers.control = true,
+            "Shift" => modifiers.shift = true,
+            "Alt" => modifiers.alt = true,
+            name => return Err(format!("Unknown modifier '{name}'.")),
+        }
+    }
+
+    // Physical keys and mouse buttons are prefixed with `Physical` and `Mouse` to distinguish them from the keys with
+    // the same names.
+    let name = button.trim();
+    let physical_key = name.strip_prefix("Physical").and_then(|name| {
+        (0..0x200)
+            .filter_map(|code| PhysicalKey::try_from(code).ok())
+            .find(|key| format!("{key:?}") == name)
+            .map(Button::Physical)
+    });
+    let mouse_button = || {
+        name.strip_prefix("Mouse").and_then(|name| {
+            (0..5)
+                .filter_map(|code| MouseButton::try_from(code).ok())
+                .find(|button| format!("{button:?}") == name)
+                .map(Button::Mouse)
+        })
+    };
+    let key = || {
+        (0..=u8::MAX as i32)
+            .filter_map(|code| Key::try_from(code).ok())
+            .find(|key| format!("{key:?}") == name)
+            .map(Button::Key)
+    };
+
+    let button = physical_key
+        .or_else(mouse_button)
+        .or_else(key)
+        .ok_or_else(|| format!("Unknown key or mouse button '{name}'."))?;
+
+    Ok(Chord { modifiers, button })
+}
+
+/// A failure to parse, load or save key bindings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BindingsError {
+    /// The bindings file could not be read or written.
+    Io(Error),
+    /// The line, counted from one, is not a valid binding.
+    Syntax { line: usize, message: String },
+}
+
+impl fmt::Display for BindingsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BindingsError::Io(error) => error.fmt(f),
+            BindingsError::Syntax { line, message } => write!(f, "Invalid key binding in line {line}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for BindingsError {}
+
+/// Maps the actions to the chords that trigger them. An action can be bound to several chords and a chord to several
+/// actions, in which case the bindings conflict; conflicts are reported, but not prevented.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bindings {
+    chords: BTreeMap<Action, Vec<Chord>>,
+}
+
+impl Bindings {
+    /// Creates bindings without any chords.
+    pub fn empty() -> Bindings {
+        Bindings {
+            chords: Action::ALL.into_iter().map(|action| (action, vec![])).collect(),
+        }
+    }
+
+    pub fn chords(&self, action: Action) -> &[Chord] {
+        &self.chords[&action]
+    }
+
+    /// Binds the chord to the action in addition to the action's other chords.
+    pub fn bind(&mut self, action: Action, chord: Chord) {
+        let chords = self.chords.entry(action).or_default();
+        if !chords.contains(&chord) {
+            chords.push(chord);
+        }
+    }
+
+    pub fn unbind(&mut self, action: Action, chord: Chord) {
+        self.chords.entry(action).or_default().retain(|bound| *bound != chord);
+    }
+
+    pub fn clear(&mut self, action: Action) {
+        self.chords.entry(action).or_default().clear();
+    }
+
+    /// The actions the chord is bound to.
+    pub fn actions(&self, chord: Chord) -> Vec<Action> {
+        self.chords
+            .iter()
+            .filter(|(_, chords)| chords.contains(&chord))
+            .map(|(action, _)| *action)
+            .collect()
+    }
+
+    /// The chords that trigger more than one action, along with these actions. Physical keys are resolved through
+    /// `key_at`, usually `Window::key_at`, so that a physical key conflicts with the key it produces with the current
+    /// layout, e.g. `PhysicalW` with `W` on QWERTY keyboards. Of several chords resolving to the same key, the first
+    /// bound one is reported.
+    pub fn conflicts(&self, key_at: impl Fn(PhysicalKey) -> Option<Key>) -> Vec<(Chord, Vec<Action>)> {
+        let resolve = |chord: Chord| match chord.button {
+            Button::Physical(key) => key_at(key).map_or(chord, |key| Chord::with(chord.modifiers, key)),
+            _ => chord,
+        };
+
+        let mut conflicts: Vec<(Chord, Vec<Action>)> = vec![];
+        for chord in self.chords.values().flatten() {
+            if conflicts.iter().any(|(conflict, _)| resolve(*conflict) == resolve(*chord)) {
+                continue;
+            }
+
+            let actions: Vec<Action> = self
+                .chords
+                .iter()
+                .filter(|(_, chords)| chords.iter().any(|bound| resolve(*bound) == resolve(*chord)))
+                .map(|(action, _)| *action)
+                .collect();
+            if actions.len() > 1 {
+                conflicts.push((*chord, actions));
+            }
+        }
+
+        conflicts
+    }
+
+    /// Checks whether any of the action's chords is held.
+    pub fn is_down(&self, action: Action, input: &InputState) -> bool {
+        self.chords(action)
+            .iter()
+            .any(|chord| input.is_down(chord.button) && self.is_selected(*chord, input))
+    }
+
+    /// Checks whether any of the action's chords has been completed during the current frame by pressing its button.
+    pub fn was_pressed(&self, action: Action, input: &InputState) -> bool {
+        self.chords(action)
+            .iter()
+            .any(|chord| input.was_pressed(chord.button) && self.is_selected(*chord, input))
+    }
+
+    /// Checks whether the chord's modifiers are held and no other chord with the same button and more of the held
+    /// modifiers is bound, so that e.g. `Control+W` does not trigger the action bound to `W` as well.
+    fn is_selected(&self, chord: Chord, input: &InputState) -> bool {
+        let held = Modifiers::held(input);
+        held.contains(chord.modifiers)
+            && !self.chords.values().flatten().any(|other| {
+                other.button == chord.button
+                    && other.modifiers != chord.modifiers
+                    && other.modifiers.contains(chord.modifiers)
+                    && held.contains(other.modifiers)
+            })
+    }
+
+    /// Parses bindings written like `Thrust = W, Up`, one action per line. Actions that are not listed keep their
+    /// default chords, so that actions added by later versions of the game are bound. Empty lines and lines starting
+    /// with `#` are ignored.
+    pub fn parse(bindings: &str) -> Result<Bindings, BindingsError> {
+        let mut parsed = Bindings::default();
+        for (line_number, line) in bindings.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let error = |message: String| BindingsError::Syntax {
+                line: line_number + 1,
+                message,
+            };
+            let (name, chords) = line
+                .split_once('=')
+                .ok_or_else(|| error(format!("Expected '=', but found '{line}'.")))?;
+
+            let name = name.trim();
+            let action = Action::ALL
+                .into_iter()
+                .find(|action| format!("{action:?}") == name)
+                .ok_or_else(|| error(format!("Unknown action '{name}'.")))?;
+
+            parsed.clear(action);
+            for chord in chords.split(',').map(str::trim).filter(|chord| !chord.is_empty()) {
+                parsed.bind(action, parse_chord(chord).map_err(error)?);
+            }
+        }
+
+        Ok(parsed)
+    }
+
+    /// The file the player's bindings are stored in, which is located in the game's data directory.
+    pub fn default_path() -> PathBuf {
+        log::data_directory().join("bindings.txt")
+    }
+
+    /// Loads the bindings, falling back to the default ones if the file does not exist.
+    pub fn load(path: &Path) -> Result<Bindings, BindingsError> {
+        match fs::read_to_string(path) {
+            Ok(bindings) => Bindings::parse(&bindings),
+            Err(_) if !path.exists() => Ok(Bindings::default()),
+            Err(e) => Err(BindingsError::Io(Error::other(
+                format!("Failed to read key bindings '{}'.", path.display()),
+                e.to_string(),
+            ))),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), BindingsError> {
+        fs::write(path, self.to_string()).map_err(|e| {
+            BindingsError::Io(Error::other(
+                format!("Failed to write key bindings '{}'.", path.display()),
+                e.to_string(),
+            ))
+        })
+    }
+}
+
+/// The bindings of the original lwar. The movement keys are bound by their physical location, so that they are in the
+/// same place on all keyboard layouts.
+impl Default for Bindings {
+    fn default() -> Bindings {
+        let mut bindings = Bindings::empty();
+        bindings.bind(Action::Thrust, Chord::new(PhysicalKey::W));
+        bindings.bind(Action::Thrust, Chord::new(Key::Up));
+        bindings.bind(Action::TurnLeft, Chord::new(PhysicalKey::A));
+        bindings.bind(Action::TurnLeft, Chord::new(Key::Left));
+        bindings.bind(Action::TurnRight, Chord::new(PhysicalKey::D));
+        bindings.bind(Action::TurnRight, Chord::new(Key::Right));
+        bindings.bind(Action::FirePrimary, Chord::new(MouseButton::Left));
+        bindings.bind(Action::FireSecondary, Chord::new(MouseButton::Right));
+        bindings.bind(Action::Scoreboard, Chord::new(Key::Tab));
+        bindings.bind(Action::Chat, Chord::new(Key::Return));
+        bindings
+    }
+}
+
+/// Writes one line per action in the format read by `Bindings::parse`.
+impl fmt::Display for Bindings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (action, chords) in &self.chords {
+            let chords: Vec<_> = chords.iter().map(Chord::to_string).collect();
+            if chords.is_empty() {
+                writeln!(f, "{action:?} =")?;
+            } else {
+                writeln!(f, "{action:?} = {}", chords.join(", "))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The outcome of capturing a chord.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Captured {
+    Chord(Chord),
+    /// The player has pressed `Escape` or the window has lost the focus.
+    Cancelled,
+}
+
+/// Waits for the player to press the chord an action should be bound to. Modifiers that are held when a key or mouse
+/// button is pressed become part of the chord; releasing a modifier before anything else has been pressed captures
+/// the modifier key itself, so that e.g. `LeftShift` can be bound on its own.
+pub struct Capture {
+    modifiers: Modifiers,
+}
+
+impl Capture {
+    pub fn new() -> Capture {
+        Capture {
+            modifiers: Modifiers::NONE,
+        }
+    }
+
+    /// Handles the event, returning the outcome once the capture has ended.
+    pub fn handle_event(&mut self, event: Event) -> Option<Captured> {
+        match event {
+            Event::KeyPressed(Key::Escape, _) if self.modifiers == Modifiers::NONE => Some(Captured::Cancelled),
+            Event::FocusLost => Some(Captured::Cancelled),
+            Event::KeyPressed(key, _) if Modifiers::of(key) != Modifiers::NONE => {
+                self.modifiers = self.modifiers.union(Modifiers::of(key));
+                None
+            }
+            Event::KeyReleased(key, _) if Modifiers::of(key) != Modifiers::NONE => {
+                let modifiers = Modifiers {
+                    control: self.modifiers.control && !Modifiers::of(key).control,
+                    shift: self.modifiers.shift && !Modifiers::of(key).shift,
+                    alt: self.modifiers.alt && !Modifiers::of(key).alt,
+                };
+                Some(Captured::Chord(Chord::with(modifiers, key)))
+            }
+            // Keys are captured by their physical location, unless the scan code is unknown.
+            Event::KeyPressed(key, scan_code) => {
+                let button = PhysicalKey::from_scan_code(scan_code).map_or(Button::Key(key), Button::Physical);
+                Some(Captured::Chord(Chord::with(self.modifiers, button)))
+            }
+            Event::MousePressed(button) => Some(Captured::Chord(Chord::with(self.modifiers, button))),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::directory;
+
+    const CONTROL: Modifiers = Modifiers {
+        control: true,
+        shift: false,
+        alt: false,
+    };
+
+    fn frame(input: &mut InputState, events: impl IntoIterator<Item = Event>) {
+        input.begin_frame();
+        for event in events {
+            input.handle_event(event);
+        }
+    }
+
+    #[test]
+    fn actions_are_triggered_by_their_chords() {
+        let bindings = Bindings::default();
+        let mut input = InputState::new();
+
+        frame(
+            &mut input,
+            [Event::KeyPressed(Key::Up, 72), Event::MousePressed(MouseButton::Left)],
+        );
+        assert!(bindings.is_down(Action::Thrust, &input) && bindings.was_pressed(Action::Thrust, &input));
+        assert!(bindings.is_down(Action::FirePrimary, &input));
+        assert!(!bindings.is_down(Action::TurnLeft, &input));
+
+        frame(&mut input, [Event::KeyPressed(Key::W, 17)]);
+        assert!(bindings.is_down(Action::Thrust, &input) && bindings.was_pressed(Action::Thrust, &input));
+
+        frame(&mut input, [Event::KeyReleased(Key::Up, 72), Event::KeyReleased(Key::W, 17)]);
+        assert!(!bindings.is_down(Action::Thrust, &input));
+    }
+
+    #[test]
+    fn chords_with_more_modifiers_take_precedence() {
+        let mut bindings = Bindings::default();
+        bindings.bind(Action::Chat, Chord::with(CONTROL, PhysicalKey::W));
+        let mut input = InputState::new();
+
+        frame(
+            &mut input,
+            [Event::KeyPressed(Key::LeftControl, 29), Event::KeyPressed(Key::W, 17)],
+        );
+        assert!(bindings.was_pressed(Action::Chat, &input));
+        assert!(!bindings.is_down(Action::Thrust, &input));
+
+        // Modifiers that are not part of any chord do not prevent the chord from being held.
+        frame(
+            &mut input,
+            [Event::KeyPressed(Key::LeftShift, 42), Event::KeyPressed(Key::Up, 72)],
+        );
+        assert!(bindings.is_down(Action::Thrust, &input));
+
+        frame(&mut input, [Event::KeyReleased(Key::LeftControl, 29)]);
+        assert!(bindings.is_down(Action::Thrust, &input) && !bindings.is_down(Action::Chat, &input));
+    }
+
+    #[test]
+    fn conflicts_are_detected() {
+        let mut bindings = Bindings::default();
+        assert!(bindings.conflicts(PhysicalKey::us_layout_key).is_empty());
+
+        bindings.bind(Action::Chat, Chord::new(Key::Tab));
+        bindings.bind(Action::FireSecondary, Chord::new(MouseButton::Left));
+        bindings.bind(Action::Chat, Chord::with(CONTROL, Key::W));
+        assert_eq!(
+            bindings.conflicts(PhysicalKey::us_layout_key),
+            [
+                (
+                    Chord::new(MouseButton::Left),
+                    vec![Action::FirePrimary, Action::FireSecondary]
+                ),
+                (Chord::new(Key::Tab), vec![Action::Scoreboard, Action::Chat]),
+            ]
+        );
+
+        bindings.unbind(Action::Chat, Chord::new(Key::Tab));
+        bindings.clear(Action::FirePrimary);
+        assert!(bindings.conflicts(PhysicalKey::us_layout_key).is_empty());
+
+        // Physical keys conflict with the keys they produce with the layout, but not with the keys at their location
+        // on US keyboards if the layout differs.
+        bindings.bind(Action::Chat, Chord::new(Key::W));
+        assert_eq!(
+            bindings.conflicts(PhysicalKey::us_layout_key),
+            [(Chord::new(PhysicalKey::W), vec![Action::Thrust, Action::Chat])]
+        );
+
+        let azerty = |key| match key {
+            PhysicalKey::W => Some(Key::Z),
+            key => PhysicalKey::us_layout_key(key),
+        };
+        assert!(bindings.conflicts(azerty).is_empty());
+        bindings.bind(Action::Chat, Chord::new(Key::Z));
+        assert_eq!(
+            bindings.conflicts(azerty),
+            [(Chord::new(PhysicalKey::W), vec![Action::Thrust, Action::Chat])]
+        );
+    }
+
+    #[test]
+    fn bindings_can_be_saved_and_parsed() {
+        let mut bindings = Bindings::default();
+        bindings.clear(Action::Chat);
+        bindings.bind(Action::Scoreboard, Chord::with(CONTROL, Key::Tab));
+        bindings.bind(
+            Action::FireSecondary,
+            Chord::with(
+                Modifiers {
+                    shift: true,
+                    alt: true,
+                    ..CONTROL
+                },
+                MouseButton::XButton1,
+            ),
+        );
+
+        let saved = bindings.to_string();
+        assert_eq!(
+            saved,
+            "Thrust = PhysicalW, Up
+TurnLeft = PhysicalA, Left
+TurnRight = PhysicalD, Right
+FirePrimary = MouseLeft
+FireSecondary = MouseRight, Control+Shift+Alt+MouseXButton1
+Scoreboard = Tab, Control+Tab
+Chat =
+"
+        );
+        assert_eq!(Bindings::parse(&saved), Ok(bindings));
+
+        // Actions that are not listed keep their default chords.
+        let parsed = Bindings::parse("# Comment\n\nThrust = Shift+Space\n").unwrap();
+        assert_eq!(
+            parsed.chords(Action::Thrust),
+            [Chord::with(
+                Modifiers {
+                    shift: true,
+                    ..Modifiers::NONE
+                },
+                Key::Space
+            )]
+        );
+        assert_eq!(parsed.chords(Action::Chat), [Chord::new(Key::Return)]);
+    }
+
+    #[test]
+    fn invalid_bindings_are_reported() {
+        let error = |bindings| Bindings::parse(bindings).unwrap_err().to_string();
+        assert_eq!(
+            Bindings::parse("Thrust = W\n\nChat = Enter"),
+            Err(BindingsError::Syntax {
+                line: 3,
+                message: "Unknown key or mouse button 'Enter'.".to_string()
+            })
+        );
+        assert_eq!(
+            error("Thrust W"),
+            "Invalid key binding in line 1: Expected '=', but found 'Thrust W'."
+        );
+        assert_eq!(
+            error("\nJump = Space"),
+            "Invalid key binding in line 2: Unknown action 'Jump'."
+        );
+        assert_eq!(
+            error("Thrust = Super+W"),
+            "Invalid key binding in line 1: Unknown modifier 'Super'."
+        );
+        assert_eq!(
+            error("Thrust = MouseWheel"),
+            "Invalid key binding in line 1: Unknown key or mouse button 'MouseWheel'."
+        );
+        assert_eq!(
+            error("Thrust = PhysicalMouseLeft"),
+            "Invalid key binding in line 1: Unknown key or mouse button 'PhysicalMouseLeft'."
+        );
+    }
+
+    #[test]
+    fn bindings_are_loaded_and_saved() {
+        let directory = directory("bindings");
+        let path = directory.join("bindings.txt");
+
+        assert_eq!(Bindings::load(&path), Ok(Bindings::default()));
+
+        let mut bindings = Bindings::default();
+        bindings.bind(Action::Chat, Chord::new(Key::T));
+        bindings.save(&path).unwrap();
+        assert_eq!(Bindings::load(&path), Ok(bindings));
+
+        fs::write(&path, "Chat = Enter").unwrap();
+        assert!(matches!(Bindings::load(&path), Err(BindingsError::Syntax { line: 1, .. })));
+
+        let error = Bindings::default()
+            .save(&directory.join("missing").join("bindings.txt"))
+            .unwrap_err();
+        assert!(matches!(error, BindingsError::Io(_)), "{error}");
+        assert!(error.to_string().starts_with("Failed to write key bindings '"), "{error}");
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn chords_are_captured() {
+        let capture = |events: &[Event]| {
+            let mut capture = Capture::new();
+            events.iter().find_map(|event| capture.handle_event(*event))
+        };
+
+        assert_eq!(
+            capture(&[Event::MouseMoved(1, 2), Event::KeyPressed(Key::F, 33)]),
+            Some(Captured::Chord(Chord::new(PhysicalKey::F)))
+        );
+        assert_eq!(
+            capture(&[
+                Event::KeyPressed(Key::RightControl, 29),
+                Event::MousePressed(MouseButton::Middle)
+            ]),
+            Some(Captured::Chord(Chord::with(CONTROL, MouseButton::Middle)))
+        );
+        assert_eq!(
+            capture(&[
+                Event::KeyPressed(Key::LeftControl, 29),
+                Event::KeyPressed(Key::LeftShift, 42),
+                Event::KeyReleased(Key::LeftShift, 42),
+            ]),
+            Some(Captured::Chord(Chord::with(CONTROL, Key::LeftShift)))
+        );
+        assert_eq!(
+            capture(&[Event::KeyPressed(Key::LeftControl, 29), Event::KeyPressed(Key::Escape, 1)]),
+            Some(Captured::Chord(Chord::with(CONTROL, PhysicalKey::Escape)))
+        );
+        assert_eq!(capture(&[Event::KeyPressed(Key::Escape, 1)]), Some(Captured::Cancelled));
+        assert_eq!(
+            capture(&[Event::KeyPressed(Key::LeftAlt, 56), Event::FocusLost]),
+            Some(Captured::Cancelled)
+        );
+        assert_eq!(capture(&[Event::KeyPressed(Key::LeftAlt, 56)]), None);
+    }
+}
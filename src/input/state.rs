@@ -0,0 +1,231 @@
+use crate::platform::{
+    input::{Key, MouseButton, PhysicalKey},
+    Event,
+};
+use std::collections::HashSet;
+
+/// A key or mouse button, so that both can be queried and bound in the same way. Keys are identified either by the
+/// keyboard layout or by their physical location, e.g. for movement keys that should stay in place on all layouts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Button {
+    Key(Key),
+    Physical(PhysicalKey),
+    Mouse(MouseButton),
+}
+
+impl From<Key> for Button {
+    fn from(key: Key) -> Button {
+        Button::Key(key)
+    }
+}
+
+impl From<PhysicalKey> for Button {
+    fn from(key: PhysicalKey) -> Button {
+        Button::Physical(key)
+    }
+}
+
+impl From<MouseButton> for Button {
+    fn from(button: MouseButton) -> Button {
+        Button::Mouse(button)
+    }
+}
+
+/// Tracks the state of the keyboard and mouse from the window's events, so that the game can query it at any time
+/// instead of reacting to individual events. Presses, releases and the mouse wheel are reported for the frame they
+/// occurred in, which starts with `begin_frame`.
+pub struct InputState {
+    down: HashSet<Button>,
+    pressed: HashSet<Button>,
+    released: HashSet<Button>,
+    mouse_position: (u32, u32),
+    wheel_delta: i32,
+    has_focus: bool,
+}
+
+impl InputState {
+    pub fn new() -> InputState {
+        InputState {
+            down: HashSet::new(),
+            pressed: HashSet::new(),
+            released: HashSet::new(),
+            mouse_position: (0, 0),
+            wheel_delta: 0,
+            has_focus: true,
+        }
+    }
+
+    /// Forgets the presses, releases and mouse wheel movement of the previous frame. Must be called before the
+    /// frame's events are handled.
+    pub fn begin_frame(&mut self) {
+        self.pressed.clear();
+        self.released.clear();
+        self.wheel_delta = 0;
+    }
+
+    pub fn handle_event(&mut self, event: Event) {
+        match event {
+            Event::KeyPressed(key, scan_code) => keys(key, scan_code).for_each(|key| self.press(key)),
+            Event::KeyReleased(key, scan_code) => keys(key, scan_code).for_each(|key| self.release(key)),
+            Event::MousePressed(button) => self.press(button.into()),
+            Event::MouseReleased(button) => self.release(button.into()),
+            Event::MouseMoved(x, y) => self.mouse_position = (x, y),
+            Event::MouseWheel(delta) => self.wheel_delta += delta,
+            Event::FocusGained => self.has_focus = true,
+            Event::FocusLost => {
+                // The window does not receive the releases of keys that are let go while it is unfocused, so all
+                // keys are released immediately instead of staying down until they are pressed again.
+                self.has_focus = false;
+                self.released.extend(self.down.drain());
+            }
+            Event::CloseRequested | Event::Resized(..) | Event::CharacterEntered(_) => (),
+        }
+    }
+
+    fn press(&mut self, button: Button) {
+        // Repeated presses of a held key are not reported as new presses.
+        if self.down.insert(button) {
+            self.pressed.insert(button);
+        }
+    }
+
+    fn release(&mut self, button: Button) {
+        if self.down.remove(&button) {
+            self.released.insert(button);
+        }
+    }
+
+    pub fn is_down(&self, button: impl Into<Button>) -> bool {
+        self.down.contains(&button.into())
+    }
+
+    /// Checks whether the button has been pressed during the current frame. Buttons that have been pressed and
+    /// released again within the frame are reported as both pressed and released, but not as down.
+    pub fn was_pressed(&self, button: impl Into<Button>) -> bool {
+        self.pressed.contains(&button.into())
+    }
+
+    pub fn was_released(&self, button: impl Into<Button>) -> bool {
+        self.released.contains(&button.into())
+    }
+
+    /// The position of the mouse relative to the top left corner of the window's client area.
+    pub fn mouse_position(&self) -> (u32, u32) {
+        self.mouse_position
+    }
+
+    /// The number of steps the mouse wheel has been turned during the current frame; positive values mean the wheel
+    /// has been turned away from the user.
+    pub fn wheel_delta(&self) -> i32 {
+        self.wheel_delta
+    }
+
+    pub fn has_focus(&self) -> bool {
+        self.has_focus
+    }
+}
+
+/// The key along with its physical location, if the scan code is known.
+fn keys(key: Key, scan_code: u32) -> impl Iterator<Item = Button> {
+    [
+        Some(Button::Key(key)),
+        PhysicalKey::from_scan_code(scan_code).map(Button::Physical),
+    ]
+    .into_iter()
+    .flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(input: &mut InputState, events: impl IntoIterator<Item = Event>) {
+        input.begin_frame();
+        for event in events {
+            input.handle_event(event);
+        }
+    }
+
+    #[test]
+    fn presses_and_releases_are_reported_for_one_frame() {
+        let mut input = InputState::new();
+        frame(
+            &mut input,
+            [Event::KeyPressed(Key::W, 17), Event::MousePressed(MouseButton::Left)],
+        );
+        assert!(input.is_down(Key::W) && input.was_pressed(Key::W) && !input.was_released(Key::W));
+        assert!(input.is_down(MouseButton::Left) && input.was_pressed(MouseButton::Left));
+        assert!(!input.is_down(Key::S) && !input.is_down(MouseButton::Right));
+        assert!(input.is_down(PhysicalKey::W) && input.was_pressed(PhysicalKey::W));
+
+        // Key repeats do not count as new presses.
+        frame(&mut input, [Event::KeyPressed(Key::W, 17)]);
+        assert!(input.is_down(Key::W) && !input.was_pressed(Key::W));
+        assert!(input.is_down(MouseButton::Left) && !input.was_pressed(MouseButton::Left));
+
+        frame(
+            &mut input,
+            [Event::KeyReleased(Key::W, 17), Event::MouseReleased(MouseButton::Left)],
+        );
+        assert!(!input.is_down(Key::W) && input.was_released(Key::W));
+        assert!(!input.is_down(PhysicalKey::W) && input.was_released(PhysicalKey::W));
+        assert!(!input.is_down(MouseButton::Left) && input.was_released(MouseButton::Left));
+
+        frame(&mut input, []);
+        assert!(!input.was_released(Key::W) && !input.was_released(MouseButton::Left));
+    }
+
+    #[test]
+    fn taps_within_a_frame_are_not_lost() {
+        let mut input = InputState::new();
+        frame(
+            &mut input,
+            [Event::KeyPressed(Key::Space, 57), Event::KeyReleased(Key::Space, 57)],
+        );
+        assert!(input.was_pressed(Key::Space) && input.was_released(Key::Space) && !input.is_down(Key::Space));
+
+        // Releases of keys that have never been pressed, e.g. because they have been pressed before the window has
+        // been focused, are ignored.
+        frame(&mut input, [Event::KeyReleased(Key::A, 30)]);
+        assert!(!input.was_released(Key::A));
+    }
+
+    #[test]
+    fn focus_loss_releases_all_buttons() {
+        let mut input = InputState::new();
+        frame(
+            &mut input,
+            [Event::KeyPressed(Key::LeftShift, 42), Event::MousePressed(MouseButton::Right)],
+        );
+        frame(&mut input, [Event::FocusLost]);
+        assert!(!input.has_focus());
+        assert!(!input.is_down(Key::LeftShift) && input.was_released(Key::LeftShift));
+        assert!(!input.is_down(MouseButton::Right) && input.was_released(MouseButton::Right));
+
+        frame(&mut input, [Event::FocusGained, Event::KeyPressed(Key::LeftShift, 42)]);
+        assert!(input.has_focus() && input.was_pressed(Key::LeftShift));
+    }
+
+    #[test]
+    fn mouse_movement_is_tracked() {
+        let mut input = InputState::new();
+        frame(
+            &mut input,
+            [
+                Event::MouseMoved(10, 20),
+                Event::MouseWheel(1),
+                Event::MouseWheel(2),
+                Event::MouseMoved(15, 25),
+            ],
+        );
+        assert_eq!(input.mouse_position(), (15, 25));
+        assert_eq!(input.wheel_delta(), 3);
+
+        frame(&mut input, [Event::MouseWheel(-1)]);
+        assert_eq!(input.mouse_position(), (15, 25));
+        assert_eq!(input.wheel_delta(), -1);
+
+        frame(&mut input, []);
+        assert_eq!(input.wheel_delta(), 0);
+    }
+}
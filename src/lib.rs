@@ -1,51 +1,678 @@
 #![warn(clippy::all)]
 #![allow(clippy::new_without_default)]
 
+pub mod assets;
+pub mod clock;
+pub mod crash_report;
+pub mod game;
+pub mod input;
+pub mod log;
 pub mod platform;
-mod primitives;
+pub mod primitives;
+pub mod rendering;
+#[cfg(test)]
+mod test_util;
+#[cfg(any(windows, target_os = "linux"))]
+use assets::manager::AssetManager;
+#[cfg(any(test, target_os = "linux"))]
+use assets::shader::{ConstantBufferLayout, ConstantBufferMember, Reflection, ResourceBinding, ScalarType, ShaderInput};
+use assets::{hot_reload::HotReloader, shader_compiler, texture};
+use clock::{GameClock, SystemTimeSource, TimeSource};
+use game::Ship;
+use input::{bindings::Bindings, state::InputState};
+use log::Category;
+#[cfg(any(test, target_os = "linux"))]
+use platform::graphics::software::{SoftwareGraphicsDevice, VertexOutput};
+#[cfg(target_os = "linux")]
+use platform::window::x11::X11Window;
 use platform::{
-    graphics::{state::PrimitiveType, GraphicsDevice},
+    error::{Error, Result},
+    graphics::{
+        registry::{Resource, ResourceRegistry},
+        texture::{TextureDescription, TextureFormat},
+        GraphicsDevice,
+    },
     Event, Window,
 };
-use primitives::{Color, Rectangle};
-use winapi::{shared::dxgiformat::DXGI_FORMAT_R32G32B32A32_FLOAT, um::d3d11::D3D11_INPUT_ELEMENT_DESC};
+#[cfg(windows)]
+use platform::{graphics::d3d11::D3D11GraphicsDevice, window::win32::Win32Window};
+use primitives::{Color, Matrix, Rectangle, Vector2};
+use rendering::{
+    sprite_batch::{Sprite, SpriteBatch},
+    sprite_renderer::SpriteRenderer,
+};
+use std::path::Path;
 
-pub fn run() {
-    let mut window = Window::new();
-    let mut graphics_device = GraphicsDevice::new(&window);
-    let mut should_exit = false;
+const TICKS_PER_SECOND: u32 = 60;
+
+const SPRITE_VERTEX_SHADER: &str = "shaders/sprite.vs.hlsl";
+const SPRITE_PIXEL_SHADER: &str = "shaders/sprite.ps.hlsl";
+
+/// The textures the sprites are drawn with; sprites refer to them by their index.
+const TEXTURES: [&str; 1] = ["textures/ship.texture"];
+const SHIP_TEXTURE: usize = 0;
+
+/// The size of the ship in pixels, which is independent of the resolution of its texture.
+const SHIP_SIZE: f32 = 32.;
+
+#[cfg(windows)]
+pub fn run() -> Result<()> {
+    let mut window = Win32Window::new()?;
+    let graphics_device = D3D11GraphicsDevice::new(&window)?;
+    let assets = open_assets()?;
+
+    run_with(
+        &mut window,
+        graphics_device,
+        SystemTimeSource::new(),
+        &load_bindings(),
+        |id| load_asset(&assets, id),
+        hot_reloader(),
+    )
+}
+
+#[cfg(target_os = "linux")]
+pub fn run() -> Result<()> {
+    let mut window = X11Window::new()?;
+    let (width, height) = window.size();
+    let presenter = window.presenter();
+
+    let mut graphics_device = SoftwareGraphicsDevice::new(width, height);
+    graphics_device.set_presenter(move |pixels, width, height| presenter.present(pixels, width, height));
+    register_software_sprite_programs(&graphics_device);
+    let assets = open_assets()?;
+    let load = |id: &str| {
+        let data = load_asset(&assets, id)?;
+        check_software_sprite_shader(id, &data)?;
+        Ok(data)
+    };
+
+    // Recompiled shaders still refer to the same programs, so only the changes to their interfaces are noticed.
+    let mut poll_assets = hot_reloader();
+    let poll_assets = move || {
+        let mut reloaded = poll_assets();
+        reloaded.retain(|(id, data)| match check_software_sprite_shader(id, data) {
+            Ok(()) => true,
+            Err(e) => {
+                error!(Category::Assets, "{e}");
+                false
+            }
+        });
+        reloaded
+    };
 
-    let vertex_shader = graphics_device.create_vertex_shader(
-        include_bytes!("../target/assets/debug/shaders/sprite.vs.hlsl"),
-        &[D3D11_INPUT_ELEMENT_DESC {
-            SemanticName: b"POSITION\0".as_ptr() as _,
-            Format: DXGI_FORMAT_R32G32B32A32_FLOAT,
-            ..Default::default()
+    run_with(
+        &mut window,
+        graphics_device,
+        SystemTimeSource::new(),
+        &load_bindings(),
+        load,
+        poll_assets,
+    )
+}
+
+/// Opens the compiled assets; during development, the loose files written by the build script are used as well.
+#[cfg(any(windows, target_os = "linux"))]
+fn open_assets() -> Result<AssetManager> {
+    AssetManager::new(AssetManager::default_directory(), cfg!(debug_assertions))
+        .map_err(|e| Error::other("Failed to open the assets.", e))
+}
+
+#[cfg(any(windows, target_os = "linux"))]
+fn load_asset(assets: &AssetManager, id: &str) -> Result<Vec<u8>> {
+    assets
+        .load(id)
+        .map(|data| data.into_owned())
+        .map_err(|e| Error::other(format!("Failed to load asset '{id}'."), e))
+}
+
+/// Loads the player's key bindings, falling back to the default ones if they cannot be loaded.
+#[cfg(any(windows, target_os = "linux"))]
+fn load_bindings() -> Bindings {
+    Bindings::load(&Bindings::default_path()).unwrap_or_else(|e| {
+        error!(Category::Input, "{e}");
+        Bindings::default()
+    })
+}
+
+/// Registers the equivalents of the sprite shaders with the software device, which cannot execute shader byte code.
+/// The shader assets compiled for the software backend refer to these programs by name. The programs are translated
+/// from the HLSL source by hand, so changes to the shaders have to be carried over to them; changes to the shaders'
+/// interfaces are detected by `check_software_sprite_shader`.
+#[cfg(any(test, target_os = "linux"))]
+fn register_software_sprite_programs(graphics_device: &SoftwareGraphicsDevice) {
+    graphics_device.register_vertex_program(b"sprite.vs", |input| {
+        let [[x, y, _, _], [u, v, _, _], color] = input.attributes[..] else {
+            unreachable!()
+        };
+        let world = input.constants::<Matrix>(1).transform([x, y, 0., 1.]);
+
+        VertexOutput {
+            position: input.constants::<Matrix>(0).transform(world),
+            varyings: [[u, v], [color[0], color[1]], [color[2], color[3]]].concat(),
+        }
+    });
+    graphics_device.register_pixel_program(b"sprite.ps", |input| {
+        let [u, v, r, g, b, a] = input.varyings[..] else {
+            unreachable!()
+        };
+        let [tex_r, tex_g, tex_b, tex_a] = input.sample(0, 0, [u, v]);
+        [tex_r * r, tex_g * g, tex_b * b, tex_a * a]
+    });
+}
+
+/// Gets the interface of the sprite shader with the given id that the software program has been written for, i.e.
+/// the inputs, constant buffers, textures and samplers the program reads.
+#[cfg(any(test, target_os = "linux"))]
+fn software_sprite_interface(id: &str) -> Option<Reflection> {
+    let input = |semantic: &str, components| ShaderInput {
+        semantic: semantic.to_string(),
+        semantic_index: 0,
+        scalar_type: ScalarType::Float,
+        components,
+    };
+    let matrix = |name: &str, slot, member: &str| ConstantBufferLayout {
+        name: name.to_string(),
+        slot,
+        size: 64,
+        members: vec![ConstantBufferMember {
+            name: member.to_string(),
+            offset: 0,
+            size: 64,
         }],
-    );
-    let pixel_shader = graphics_device.create_pixel_shader(include_bytes!("../target/assets/debug/shaders/sprite.ps.hlsl"));
+    };
+    let binding = |name: &str| ResourceBinding {
+        name: name.to_string(),
+        slot: 0,
+    };
 
-    graphics_device.set_vertex_shader(&vertex_shader);
-    graphics_device.set_pixel_shader(&pixel_shader);
-    graphics_device.set_primitive_type(PrimitiveType::Triangles);
+    match id {
+        SPRITE_VERTEX_SHADER => Some(Reflection {
+            inputs: vec![input("POSITION", 2), input("TEXCOORD", 2), input("COLOR", 4)],
+            constant_buffers: vec![
+                matrix("PerFrameConstants", 0, "Projection"),
+                matrix("PerObjectConstants", 1, "World"),
+            ],
+            ..Reflection::default()
+        }),
+        SPRITE_PIXEL_SHADER => Some(Reflection {
+            inputs: vec![input("TEXCOORD", 2), input("COLOR", 4)],
+            textures: vec![binding("Tex")],
+            samplers: vec![binding("TexSampler")],
+            ..Reflection::default()
+        }),
+        _ => None,
+    }
+}
+
+/// Checks that the compiled sprite shader, whose reflection data has been extracted from the HLSL source, still has the
+/// interface the software program has been written for, so that changes to the source are not silently ignored by
+/// the software backend. Other shaders are not checked.
+#[cfg(any(test, target_os = "linux"))]
+fn check_software_sprite_shader(id: &str, data: &[u8]) -> Result<()> {
+    let Some(interface) = software_sprite_interface(id) else {
+        return Ok(());
+    };
+
+    let shader = assets::shader::load(data).map_err(|e| Error::other(format!("Failed to load shader '{id}'."), e.to_string()))?;
+    if shader.reflection != interface {
+        return Err(Error::other(
+            format!("The software program of shader '{id}' is out of date."),
+            "The inputs, constant buffers or resources of the shader have changed; update the program in \
+             `register_software_sprite_programs` to match.",
+        ));
+    }
+
+    Ok(())
+}
+
+/// Registers the software sprite programs and compiles the sprite shaders for them, as the build script does for the
+/// software backend.
+#[cfg(test)]
+fn software_sprite_shaders(graphics_device: &SoftwareGraphicsDevice) -> Result<(Vec<u8>, Vec<u8>)> {
+    register_software_sprite_programs(graphics_device);
+
+    let shader = |id, source, program: &[u8]| {
+        let reflection = assets::hlsl::reflect(source).map_err(|e| Error::other("Failed to reflect sprite shader.", e))?;
+        let shader = assets::shader::compile(&reflection, program);
+        check_software_sprite_shader(id, &shader)?;
+        Ok::<_, Error>(shader)
+    };
+
+    Ok((
+        shader(
+            SPRITE_VERTEX_SHADER,
+            include_str!("../assets/shaders/sprite.vs.hlsl"),
+            b"sprite.vs",
+        )?,
+        shader(
+            SPRITE_PIXEL_SHADER,
+            include_str!("../assets/shaders/sprite.ps.hlsl"),
+            b"sprite.ps",
+        )?,
+    ))
+}
+
+#[cfg(not(any(windows, target_os = "linux")))]
+pub fn run() -> Result<()> {
+    Err(Error::other("There is no window implementation for this platform.", ""))
+}
+
+/// Watches the asset sources in debug builds that are run from the repository, so that changed assets are picked up
+/// without restarting the game. Returns the function that polls for recompiled assets, which never returns any assets
+/// if the sources are not watched.
+fn hot_reloader() -> impl FnMut() -> Vec<(String, Vec<u8>)> {
+    let source = Path::new("assets");
+    let mut hot_reloader = (cfg!(debug_assertions) && source.join(assets::pipeline::MANIFEST).is_file())
+        .then(|| HotReloader::new(source, "target/assets/debug", shader_compiler::for_platform()));
+
+    move || hot_reloader.as_mut().map(HotReloader::poll).unwrap_or_default()
+}
+
+/// Runs the game until the window requests to be closed, loading the compiled shaders and textures with `load_asset`.
+/// The game is simulated in fixed ticks, reading the player's input through the bindings, and drawn between the
+/// last two ticks. `poll_assets` is called every frame and returns the ids and contents of the assets that have been
+/// recompiled since, e.g. by a hot reloader; the shaders and textures created from them are replaced. If the graphics
+/// device is lost, it is recreated along with all resources; other errors are returned.
+pub fn run_with<W: Window, G: GraphicsDevice + 'static, T: TimeSource>(
+    window: &mut W,
+    mut graphics_device: G,
+    time_source: T,
+    bindings: &Bindings,
+    load_asset: impl Fn(&str) -> Result<Vec<u8>>,
+    mut poll_assets: impl FnMut() -> Vec<(String, Vec<u8>)>,
+) -> Result<()> {
+    let mut should_exit = false;
+    let mut clock = GameClock::new(time_source, TICKS_PER_SECOND);
+    let mut input = InputState::new();
+
+    let (width, height) = window.size();
+    let mut ship = Ship::new(Vector2::new(width as f32 / 2., height as f32 / 2.));
+    crash_report::set_context("Graphics device", std::any::type_name::<G>());
+    crash_report::set_context("Window size", format!("{width}x{height}"));
+
+    let registry = ResourceRegistry::new();
+    let mut sprite_renderer = SpriteRenderer::new(
+        &graphics_device,
+        &registry,
+        &load_asset(SPRITE_VERTEX_SHADER)?,
+        &load_asset(SPRITE_PIXEL_SHADER)?,
+    )?;
+    let mut sprite_batch = SpriteBatch::<usize>::new();
+    // The textures are stored along with the ids of their assets, so that they can be replaced when reloaded.
+    let mut textures = TEXTURES
+        .into_iter()
+        .map(|id| Ok((id.to_string(), create_texture(&graphics_device, &registry, &load_asset(id)?)?)))
+        .collect::<Result<Vec<_>>>()?;
 
     while !should_exit {
-        window.handle_events(|event| match event {
-            Event::CloseRequested => should_exit = true,
-            Event::Resized(width, height) => {
-                graphics_device.resize_back_buffer(width, height);
-                graphics_device.set_viewport(&Rectangle {
-                    left: 0,
-                    top: 0,
-                    width,
-                    height,
-                })
+        // Events cannot return errors, so the first error is kept until all events have been handled.
+        let mut error = None;
+        input.begin_frame();
+        window.handle_events(|event| {
+            input.handle_event(event);
+            match event {
+                Event::CloseRequested => should_exit = true,
+                Event::Resized(width, height) => {
+                    crash_report::set_context("Window size", format!("{width}x{height}"));
+                    if let Err(e) = graphics_device.resize_back_buffer(width, height) {
+                        error.get_or_insert(e);
+                    }
+                }
+                Event::KeyPressed(key, scan_code) => debug!(Category::Input, "{key:?} has been pressed ({scan_code})."),
+                _ => {}
             }
-            Event::KeyPressed(key, sc) => println!("{key:?}, {sc}"),
-            _ => {}
         });
 
-        graphics_device.clear(graphics_device.back_buffer(), Color::new(0, 0, 0, 255));
-        graphics_device.present();
+        for (id, data) in poll_assets() {
+            if let Err(e) = reload_asset(&graphics_device, &registry, &mut sprite_renderer, &mut textures, &id, &data) {
+                error!(Category::Assets, "Failed to reload asset '{id}': {e}");
+            }
+        }
+
+        let frame = clock.advance();
+        for _ in 0..frame.ticks {
+            ship.update(bindings, &input, clock.tick_duration());
+        }
+
+        let result = match error {
+            Some(e) => Err(e),
+            None => draw_frame(
+                &graphics_device,
+                window.size(),
+                &sprite_renderer,
+                &mut sprite_batch,
+                &textures,
+                ship.interpolate(frame.alpha),
+            ),
+        };
+
+        match result {
+            Err(e) if e.is_device_lost() => recover(&mut graphics_device, &registry, window.size(), &e)?,
+            result => result?,
+        }
+    }
+
+    Ok(())
+}
+
+/// Draws the frame into the back buffer and presents it, showing the ship at the given position and rotation.
+fn draw_frame<G: GraphicsDevice + 'static>(
+    graphics_device: &G,
+    (width, height): (u32, u32),
+    sprite_renderer: &SpriteRenderer<G>,
+    sprite_batch: &mut SpriteBatch<usize>,
+    textures: &[(String, Resource<G::Texture2D>)],
+    (ship_position, ship_rotation): (Vector2, f32),
+) -> Result<()> {
+    // Resizing the back buffer resets all device state, so the pipeline is set up again every frame.
+    graphics_device.set_render_target(graphics_device.back_buffer());
+    graphics_device.set_viewport(&Rectangle {
+        left: 0,
+        top: 0,
+        width,
+        height,
+    });
+    graphics_device.clear(graphics_device.back_buffer(), Color::new(0, 0, 0, 255));
+
+    sprite_batch.draw(Sprite {
+        texture: SHIP_TEXTURE,
+        layer: 0,
+        position: ship_position,
+        size: Vector2::new(SHIP_SIZE, SHIP_SIZE),
+        origin: Vector2::new(0.5, 0.5),
+        rotation: ship_rotation,
+        source: Rectangle {
+            left: 0.,
+            top: 0.,
+            width: 1.,
+            height: 1.,
+        },
+        color: Color::new(255, 255, 255, 255),
+    });
+
+    let projection = Matrix::orthographic(width as f32, height as f32);
+    sprite_renderer.draw(graphics_device, sprite_batch, &projection, |texture| &textures[texture].1)?;
+    graphics_device.present()
+}
+
+/// Recreates the graphics device and all resources after the device has been lost, e.g. because the graphics driver
+/// has been updated. The frame that has been lost is not drawn again.
+fn recover<G: GraphicsDevice + 'static>(
+    graphics_device: &mut G,
+    registry: &ResourceRegistry<G>,
+    (width, height): (u32, u32),
+    error: &Error,
+) -> Result<()> {
+    warn!(Category::Graphics, "{error}");
+    crash_report::set_context("Last device loss", error.to_string());
+    graphics_device.recreate()?;
+    // The window might have been resized while the device was lost.
+    graphics_device.resize_back_buffer(width, height)?;
+    let count = registry.recreate(graphics_device)?;
+    info!(
+        Category::Graphics,
+        "The graphics device and {count} resources have been recreated."
+    );
+    Ok(())
+}
+
+/// Replaces the graphics resources created from a recompiled asset, keeping the previous ones if that fails.
+fn reload_asset<G: GraphicsDevice + 'static>(
+    graphics_device: &G,
+    registry: &ResourceRegistry<G>,
+    sprite_renderer: &mut SpriteRenderer<G>,
+    textures: &mut [(String, Resource<G::Texture2D>)],
+    id: &str,
+    data: &[u8],
+) -> Result<()> {
+    match id {
+        SPRITE_VERTEX_SHADER => sprite_renderer.set_vertex_shader(graphics_device, registry, data),
+        SPRITE_PIXEL_SHADER => sprite_renderer.set_pixel_shader(graphics_device, registry, data),
+        _ => {
+            if let Some((_, texture)) = textures.iter_mut().find(|(texture_id, _)| texture_id == id) {
+                *texture = create_texture(graphics_device, registry, data)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Creates a mipmapped texture from a compiled texture asset.
+fn create_texture<G: GraphicsDevice + 'static>(
+    graphics_device: &G,
+    registry: &ResourceRegistry<G>,
+    data: &[u8],
+) -> Result<Resource<G::Texture2D>> {
+    let texture = texture::load(data).map_err(|e| Error::other("Failed to create texture.", e.to_string()))?;
+    let description = TextureDescription {
+        width: texture.width,
+        height: texture.height,
+        format: TextureFormat::Rgba8,
+        mipmaps: true,
+        render_target: false,
+    };
+
+    registry.create_texture(graphics_device, &description, Some(texture.pixels))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clock::ManualTimeSource;
+    use platform::window::headless::HeadlessWindow;
+    use std::{
+        cell::{Cell, RefCell},
+        rc::Rc,
+        time::Duration,
+    };
+
+    /// A time source that advances by a tick whenever it is read, so that the game clock runs one tick per frame.
+    struct TickingTimeSource(Cell<Duration>);
+
+    impl TimeSource for TickingTimeSource {
+        fn now(&self) -> Duration {
+            self.0.set(self.0.get() + Duration::from_secs(1) / TICKS_PER_SECOND);
+            self.0.get()
+        }
+    }
+
+    /// Loads the game's assets as compiled for the software device.
+    fn software_assets(graphics_device: &SoftwareGraphicsDevice) -> impl Fn(&str) -> Result<Vec<u8>> {
+        let (vertex_shader, pixel_shader) = software_sprite_shaders(graphics_device).unwrap();
+        let ship = texture::compile(include_bytes!("../assets/textures/ship.png")).unwrap();
+
+        move |id| match id {
+            SPRITE_VERTEX_SHADER => Ok(vertex_shader.clone()),
+            SPRITE_PIXEL_SHADER => Ok(pixel_shader.clone()),
+            "textures/ship.texture" => Ok(ship.clone()),
+            _ => Err(Error::other(
+                format!("Failed to load asset '{id}'."),
+                "The asset does not exist.",
+            )),
+        }
+    }
+
+    /// The pixels, width and height of the frames that have been presented.
+    type Frames = Rc<RefCell<Vec<(Vec<u8>, u32, u32)>>>;
+
+    /// Creates a software device that records the frames it presents.
+    fn recording_device(width: u32, height: u32) -> (SoftwareGraphicsDevice, Frames) {
+        let frames = Rc::new(RefCell::new(vec![]));
+        let mut graphics_device = SoftwareGraphicsDevice::new(width, height);
+        graphics_device.set_presenter({
+            let frames = frames.clone();
+            move |pixels, width, height| {
+                frames.borrow_mut().push((pixels.to_vec(), width, height));
+                Ok(())
+            }
+        });
+
+        (graphics_device, frames)
+    }
+
+    fn pixel((pixels, width, _): &(Vec<u8>, u32, u32), x: u32, y: u32) -> [u8; 4] {
+        let offset = (y * width + x) as usize * 4;
+        pixels[offset..offset + 4].try_into().unwrap()
+    }
+
+    #[test]
+    fn the_game_loop_is_driven_by_scripted_events() {
+        let mut window = HeadlessWindow::from_script(
+            8,
+            8,
+            "Frame 2
+            Resized 4 2
+            KeyPressed Escape 1
+            Frame
+            MouseMoved 1 1",
+        )
+        .unwrap();
+
+        let (graphics_device, frames) = recording_device(8, 8);
+        let assets = software_assets(&graphics_device);
+        run_with(
+            &mut window,
+            graphics_device,
+            ManualTimeSource::new(),
+            &Bindings::default(),
+            assets,
+            Vec::new,
+        )
+        .unwrap();
+
+        // The frame during which the window requests to be closed is still drawn.
+        let sizes: Vec<_> = frames.borrow().iter().map(|&(_, width, height)| (width, height)).collect();
+        assert_eq!(sizes, [(8, 8), (4, 2), (4, 2), (4, 2)]);
+    }
+
+    #[test]
+    fn the_ship_is_drawn_with_its_texture() {
+        let (graphics_device, frames) = recording_device(64, 64);
+        let assets = software_assets(&graphics_device);
+        run_with(
+            &mut HeadlessWindow::new(64, 64),
+            graphics_device,
+            ManualTimeSource::new(),
+            &Bindings::default(),
+            assets,
+            Vec::new,
+        )
+        .unwrap();
+
+        // The ship is centered in the window, pointing to the right, with its cockpit in front of its center.
+        let frame = &frames.borrow()[0];
+        assert_eq!(pixel(frame, 0, 0), [0, 0, 0, 255]);
+        assert_eq!(pixel(frame, 34, 32), [120, 200, 255, 255]);
+        assert_eq!(pixel(frame, 26, 32), [200, 200, 210, 255]);
+        assert_eq!(pixel(frame, 21, 32), [255, 160, 40, 255]);
+        assert_eq!(pixel(frame, 32, 20), [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn the_ship_is_turned_by_the_player() {
+        // Turning for half a second rotates the ship by 90 degrees, so that it points down.
+        let mut window = HeadlessWindow::from_script(
+            64,
+            64,
+            "KeyPressed Right 0
+            Frame 30
+            KeyReleased Right 0",
+        )
+        .unwrap();
+
+        let (graphics_device, frames) = recording_device(64, 64);
+        let assets = software_assets(&graphics_device);
+        run_with(
+            &mut window,
+            graphics_device,
+            TickingTimeSource(Cell::new(Duration::ZERO)),
+            &Bindings::default(),
+            assets,
+            Vec::new,
+        )
+        .unwrap();
+
+        let frames = frames.borrow();
+        assert_eq!(pixel(&frames[0], 34, 32), [120, 200, 255, 255]);
+        let frame = frames.last().unwrap();
+        assert_eq!(pixel(frame, 34, 32), [200, 200, 210, 255]);
+        assert_eq!(pixel(frame, 31, 34), [120, 200, 255, 255]);
+        assert_eq!(pixel(frame, 31, 21), [255, 160, 40, 255]);
+    }
+
+    #[test]
+    fn textures_are_replaced_when_reloaded() {
+        let mut window = HeadlessWindow::from_script(64, 64, "Frame 3").unwrap();
+        let (graphics_device, frames) = recording_device(64, 64);
+        let assets = software_assets(&graphics_device);
+
+        // The invalid texture is reported and the previous one kept; the valid one replaces the ship's texture.
+        let mut reloads = vec![
+            vec![("textures/ship.texture".to_string(), b"invalid".to_vec())],
+            vec![(
+                "textures/ship.texture".to_string(),
+                texture::compile(include_bytes!("../tests/images/rgb24.bmp")).unwrap(),
+            )],
+        ]
+        .into_iter();
+
+        run_with(
+            &mut window,
+            graphics_device,
+            ManualTimeSource::new(),
+            &Bindings::default(),
+            assets,
+            || reloads.next().unwrap_or_default(),
+        )
+        .unwrap();
+
+        // The reloaded texture is opaque, so the corner of the ship's quad is no longer transparent.
+        let frames = frames.borrow();
+        assert_eq!(pixel(&frames[0], 34, 32), [120, 200, 255, 255]);
+        assert_eq!(pixel(&frames[0], 46, 46), [0, 0, 0, 255]);
+        assert_ne!(pixel(&frames[1], 46, 46), [0, 0, 0, 255]);
+        assert_eq!(pixel(&frames[1], 46, 46), pixel(&frames[2], 46, 46));
+    }
+
+    #[test]
+    fn software_programs_are_checked_against_the_shaders() {
+        let shader = |id, source: &str| {
+            let compiled = assets::shader::compile(&assets::hlsl::reflect(source).unwrap(), b"program");
+            check_software_sprite_shader(id, &compiled)
+        };
+
+        let pixel_shader = include_str!("../assets/shaders/sprite.ps.hlsl");
+        assert_eq!(shader(SPRITE_PIXEL_SHADER, pixel_shader), Ok(()));
+        assert_eq!(
+            shader(SPRITE_PIXEL_SHADER, &pixel_shader.replace("register(t0)", "register(t1)"))
+                .unwrap_err()
+                .operation(),
+            "The software program of shader 'shaders/sprite.ps.hlsl' is out of date."
+        );
+        assert!(shader(SPRITE_VERTEX_SHADER, pixel_shader).is_err());
+        assert_eq!(shader("shaders/other.ps.hlsl", pixel_shader), Ok(()));
+    }
+
+    #[test]
+    fn missing_assets_are_reported() {
+        let graphics_device = SoftwareGraphicsDevice::new(8, 8);
+        let assets = software_assets(&graphics_device);
+        let error = run_with(
+            &mut HeadlessWindow::new(8, 8),
+            graphics_device,
+            ManualTimeSource::new(),
+            &Bindings::default(),
+            |id| {
+                if id == "textures/ship.texture" {
+                    assets("missing")
+                } else {
+                    assets(id)
+                }
+            },
+            Vec::new,
+        )
+        .unwrap_err();
+        assert_eq!(error.operation(), "Failed to load asset 'missing'.");
     }
 }
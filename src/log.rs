@@ -0,0 +1,452 @@
+//! Collects the diagnostic messages of the game and the build script. Messages are filtered by their severity per
+//! subsystem, kept in a ring buffer for the in-game console and forwarded to sinks such as stderr or a log file, as
+//! stderr is not attached to anything when the game runs as a Windows application.
+
+use std::{
+    collections::VecDeque,
+    env, fmt,
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::{Mutex, MutexGuard, PoisonError, TryLockError},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The number of entries kept for the in-game console.
+pub const RECENT_CAPACITY: usize = 256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Debug,
+    Info,
+    Warning,
+    Error,
+}
+
+impl Level {
+    pub fn name(self) -> &'static str {
+        match self {
+            Level::Debug => "debug",
+            Level::Info => "info",
+            Level::Warning => "warning",
+            Level::Error => "error",
+        }
+    }
+}
+
+/// The subsystem a message originates from; the minimum level of the messages that are logged can be set per category.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Category {
+    General,
+    Platform,
+    Input,
+    Graphics,
+    Assets,
+}
+
+impl Category {
+    const COUNT: usize = 5;
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Category::General => "general",
+            Category::Platform => "platform",
+            Category::Input => "input",
+            Category::Graphics => "graphics",
+            Category::Assets => "assets",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub time: SystemTime,
+    pub level: Level,
+    pub category: Category,
+    pub message: String,
+}
+
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} [{}] [{}] {}",
+            format_time(self.time),
+            self.level.name(),
+            self.category.name(),
+            self.message
+        )
+    }
+}
+
+/// Receives all entries that pass the logger's filter. Sinks cannot report failures, as there would be nowhere to
+/// report them to; entries that cannot be written are dropped.
+pub trait Sink: Send {
+    fn write(&mut self, entry: &Entry);
+}
+
+pub struct StderrSink;
+
+impl Sink for StderrSink {
+    fn write(&mut self, entry: &Entry) {
+        // `eprintln!` would panic if stderr is closed.
+        let _ = writeln!(io::stderr(), "{entry}");
+    }
+}
+
+/// Appends the entries to a log file. Whenever the file is opened or exceeds its maximum size, it is renamed from
+/// e.g. `lwar.log` to `lwar.1.log`, with the older files being renamed accordingly and the oldest one being removed.
+pub struct FileSink {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    max_size: u64,
+    max_files: usize,
+}
+
+impl FileSink {
+    /// Opens a new log file, keeping at most `max_files` previous ones.
+    pub fn open(path: &Path, max_size: u64, max_files: usize) -> io::Result<FileSink> {
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory)?;
+        }
+
+        rotate(path, max_files);
+        Ok(FileSink {
+            path: path.to_path_buf(),
+            file: File::create(path)?,
+            size: 0,
+            max_size,
+            max_files,
+        })
+    }
+}
+
+impl Sink for FileSink {
+    fn write(&mut self, entry: &Entry) {
+        if self.size >= self.max_size {
+            rotate(&self.path, self.max_files);
+            match OpenOptions::new().write(true).create(true).truncate(true).open(&self.path) {
+                Ok(file) => self.file = file,
+                Err(_) => return,
+            }
+            self.size = 0;
+        }
+
+        let line = format!("{entry}\n");
+        if self.file.write_all(line.as_bytes()).is_ok() {
+            self.size += line.len() as u64;
+        }
+    }
+}
+
+/// Moves the file at `path` and its older versions one version back. Files that do not exist are skipped.
+fn rotate(path: &Path, max_files: usize) {
+    if max_files == 0 {
+        let _ = fs::remove_file(path);
+        return;
+    }
+
+    let _ = fs::remove_file(rotated_path(path, max_files));
+    for index in (1..max_files).rev() {
+        let _ = fs::rename(rotated_path(path, index), rotated_path(path, index + 1));
+    }
+    let _ = fs::rename(path, rotated_path(path, 1));
+}
+
+/// Inserts the index in front of the file's extension, e.g. `lwar.2.log` for `lwar.log`.
+fn rotated_path(path: &Path, index: usize) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    match path.extension() {
+        Some(extension) => path.with_file_name(format!("{stem}.{index}.{}", extension.to_string_lossy())),
+        None => path.with_file_name(format!("{stem}.{index}")),
+    }
+}
+
+pub struct Logger {
+    levels: [Level; Category::COUNT],
+    recent: VecDeque<Entry>,
+    sinks: Vec<Box<dyn Sink>>,
+}
+
+impl Logger {
+    /// Creates a logger without sinks that logs debug messages in debug builds only.
+    pub const fn new() -> Logger {
+        let level = if cfg!(debug_assertions) { Level::Debug } else { Level::Info };
+        Logger {
+            levels: [level; Category::COUNT],
+            recent: VecDeque::new(),
+            sinks: vec![],
+        }
+    }
+
+    /// Sets the minimum level of the messages that are logged for all categories.
+    pub fn set_level(&mut self, level: Level) {
+        self.levels = [level; Category::COUNT];
+    }
+
+    pub fn set_category_level(&mut self, category: Category, level: Level) {
+        self.levels[category as usize] = level;
+    }
+
+    pub fn is_enabled(&self, level: Level, category: Category) -> bool {
+        level >= self.levels[category as usize]
+    }
+
+    pub fn add_sink(&mut self, sink: impl Sink + 'static) {
+        self.sinks.push(Box::new(sink));
+    }
+
+    pub fn log(&mut self, level: Level, category: Category, message: fmt::Arguments) {
+        if !self.is_enabled(level, category) {
+            return;
+        }
+
+        let entry = Entry {
+            time: SystemTime::now(),
+            level,
+            category,
+            message: message.to_string(),
+        };
+
+        for sink in &mut self.sinks {
+            sink.write(&entry);
+        }
+
+        if self.recent.len() == RECENT_CAPACITY {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(entry);
+    }
+
+    /// The most recent entries, oldest first.
+    pub fn recent(&self) -> impl Iterator<Item = &Entry> {
+        self.recent.iter()
+    }
+}
+
+static LOGGER: Mutex<Logger> = Mutex::new(Logger::new());
+
+/// The logger used by the logging macros. A panic while the logger is locked does not disable logging, so that the
+/// panic itself can still be logged.
+pub fn logger() -> MutexGuard<'static, Logger> {
+    LOGGER.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Logs the message with the global logger; use the `debug!`, `info!`, `warn!` and `error!` macros instead, which
+/// only format the message if it is logged.
+pub fn log(level: Level, category: Category, message: fmt::Arguments) {
+    logger().log(level, category, message);
+}
+
+/// Logs the message with the global logger unless it is locked, in which case the message is dropped. This is used
+/// while handling panics, as waiting for the logger would dead-lock if the game has panicked while logging.
+pub fn try_log(level: Level, category: Category, message: fmt::Arguments) {
+    if let Some(mut logger) = try_logger() {
+        logger.log(level, category, message);
+    }
+}
+
+/// The most recent entries of the global logger, oldest first. No entries are returned if the logger is locked, as
+/// waiting for it could dead-lock if the game has panicked while logging.
+pub fn recent() -> Vec<Entry> {
+    try_logger().map_or(vec![], |logger| logger.recent().cloned().collect())
+}
+
+fn try_logger() -> Option<MutexGuard<'static, Logger>> {
+    match LOGGER.try_lock() {
+        Ok(logger) => Some(logger),
+        Err(TryLockError::Poisoned(e)) => Some(e.into_inner()),
+        Err(TryLockError::WouldBlock) => None,
+    }
+}
+
+/// The log file of the game, which is stored in the data directory.
+pub fn default_path() -> PathBuf {
+    data_directory().join("lwar.log")
+}
+
+/// The directory the game writes its files to: `%LOCALAPPDATA%\lwar` on Windows and `$XDG_STATE_HOME/lwar` or
+/// `~/.local/state/lwar` elsewhere. Falls back to the temporary directory if neither is set.
+pub fn data_directory() -> PathBuf {
+    let directory = if cfg!(windows) {
+        env::var_os("LOCALAPPDATA").map(PathBuf::from)
+    } else {
+        env::var_os("XDG_STATE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".local/state")))
+    };
+
+    directory.unwrap_or_else(env::temp_dir).join("lwar")
+}
+
+/// Formats the time as a UTC date and time with millisecond precision, e.g. `2021-03-14 15:09:26.535`.
+pub fn format_time(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let (days, seconds) = (since_epoch.as_secs() / 86400, since_epoch.as_secs() % 86400);
+
+    // Converts the days since 1970-01-01 into a date of the Gregorian calendar, using eras of 400 years, which start
+    // on March 1st so that leap days are the last day of a year.
+    let days = days + 719468;
+    let (era, day_of_era) = (days / 146097, days % 146097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = era * 400 + year_of_era + u64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02}.{:03}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60,
+        since_epoch.subsec_millis()
+    )
+}
+
+#[macro_export]
+macro_rules! debug {
+    ($category:expr, $($arg:tt)+) => {
+        $crate::log::log($crate::log::Level::Debug, $category, format_args!($($arg)+))
+    };
+}
+
+#[macro_export]
+macro_rules! info {
+    ($category:expr, $($arg:tt)+) => {
+        $crate::log::log($crate::log::Level::Info, $category, format_args!($($arg)+))
+    };
+}
+
+#[macro_export]
+macro_rules! warn {
+    ($category:expr, $($arg:tt)+) => {
+        $crate::log::log($crate::log::Level::Warning, $category, format_args!($($arg)+))
+    };
+}
+
+#[macro_export]
+macro_rules! error {
+    ($category:expr, $($arg:tt)+) => {
+        $crate::log::log($crate::log::Level::Error, $category, format_args!($($arg)+))
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::directory;
+    use std::{sync::Arc, time::Duration};
+
+    struct Messages(Arc<Mutex<Vec<String>>>);
+
+    impl Sink for Messages {
+        fn write(&mut self, entry: &Entry) {
+            self.0.lock().unwrap().push(entry.message.clone());
+        }
+    }
+
+    fn entry(message: &str) -> Entry {
+        Entry {
+            time: UNIX_EPOCH,
+            level: Level::Info,
+            category: Category::General,
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn messages_are_filtered_by_level_and_category() {
+        let messages = Arc::new(Mutex::new(vec![]));
+        let mut logger = Logger::new();
+        logger.add_sink(Messages(messages.clone()));
+        logger.set_level(Level::Info);
+        logger.set_category_level(Category::Input, Level::Warning);
+
+        logger.log(Level::Debug, Category::General, format_args!("debug"));
+        logger.log(Level::Info, Category::General, format_args!("info {}", 1));
+        logger.log(Level::Info, Category::Input, format_args!("unknown key"));
+        logger.log(Level::Error, Category::Input, format_args!("no keyboard"));
+
+        assert_eq!(*messages.lock().unwrap(), ["info 1", "no keyboard"]);
+        let recent: Vec<_> = logger.recent().map(|entry| entry.message.as_str()).collect();
+        assert_eq!(recent, ["info 1", "no keyboard"]);
+    }
+
+    #[test]
+    fn only_the_most_recent_entries_are_kept() {
+        let mut logger = Logger::new();
+        for i in 0..RECENT_CAPACITY + 10 {
+            logger.log(Level::Error, Category::Assets, format_args!("{i}"));
+        }
+
+        let recent: Vec<_> = logger.recent().map(|entry| entry.message.clone()).collect();
+        assert_eq!(recent.len(), RECENT_CAPACITY);
+        assert_eq!(recent[0], "10");
+        assert_eq!(recent[RECENT_CAPACITY - 1], (RECENT_CAPACITY + 9).to_string());
+    }
+
+    #[test]
+    fn entries_are_formatted_with_utc_times() {
+        let mut entry = entry("Compiling texture 'ship.png'.");
+        entry.time = UNIX_EPOCH + Duration::from_millis(1_615_734_566_535);
+        entry.category = Category::Assets;
+        assert_eq!(
+            entry.to_string(),
+            "2021-03-14 15:09:26.535 [info] [assets] Compiling texture 'ship.png'."
+        );
+
+        assert_eq!(format_time(UNIX_EPOCH), "1970-01-01 00:00:00.000");
+        assert_eq!(
+            format_time(UNIX_EPOCH + Duration::from_secs(951_782_400)),
+            "2000-02-29 00:00:00.000"
+        );
+        assert_eq!(
+            format_time(UNIX_EPOCH + Duration::from_secs(4_102_444_799)),
+            "2099-12-31 23:59:59.000"
+        );
+    }
+
+    #[test]
+    fn messages_are_dropped_while_the_logger_is_locked() {
+        let logger = logger();
+        try_log(Level::Error, Category::General, format_args!("Dropped while locked."));
+        assert!(recent().is_empty());
+        drop(logger);
+
+        try_log(Level::Error, Category::General, format_args!("Logged once unlocked."));
+        let recent = recent();
+        assert!(recent.iter().any(|entry| entry.message == "Logged once unlocked."));
+        assert!(!recent.iter().any(|entry| entry.message == "Dropped while locked."));
+    }
+
+    #[test]
+    fn log_files_are_rotated() {
+        let directory = directory("log");
+        let path = directory.join("game.log");
+        let read = |name: &str| fs::read_to_string(directory.join(name)).ok();
+
+        let mut sink = FileSink::open(&path, 20, 2).unwrap();
+        sink.write(&entry("first"));
+        drop(sink);
+
+        // Opening the file starts a new one; entries are written until the maximum size has been exceeded.
+        let mut sink = FileSink::open(&path, 80, 2).unwrap();
+        for message in ["second", "third", "fourth"] {
+            sink.write(&entry(message));
+        }
+
+        let line = |message: &str| format!("1970-01-01 00:00:00.000 [info] [general] {message}\n");
+        assert_eq!(read("game.log"), Some(line("fourth")));
+        assert_eq!(read("game.1.log"), Some(line("second") + &line("third")));
+        assert_eq!(read("game.2.log"), Some(line("first")));
+        assert_eq!(read("game.3.log"), None);
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -1,20 +1,85 @@
 #![warn(clippy::all)]
 #![windows_subsystem = "windows"]
 
-use lwar::platform::error::on_panic;
-use lwar::platform::graphics::report_d3d11_leaks;
-use lwar::platform::show_message_box;
-use std::process::exit;
+#[cfg(windows)]
+use lwar::platform::graphics::d3d11::report_d3d11_leaks;
+use lwar::{
+    crash_report::CrashReport,
+    error,
+    log::{self, Category, FileSink, Level, StderrSink},
+    platform::{
+        error::on_panic,
+        message_box::{show_message_box, Buttons, Severity},
+    },
+};
+use std::{backtrace::Backtrace, process::exit};
+
+/// The size at which the log file is rotated and the number of previous log files that are kept.
+const MAX_LOG_FILE_SIZE: u64 = 4 * 1024 * 1024;
+const MAX_LOG_FILES: usize = 4;
 
 fn main() {
-    on_panic(|error_message| {
-        show_message_box(format!(
-            "The application has been terminated after a fatal error.\n\nThe error was: {error_message}"
+    init_logging();
+    on_panic(|error_message, location| {
+        let backtrace = Backtrace::force_capture().to_string();
+        show_fatal_error(&CrashReport::new(
+            error_message,
+            location.map(ToString::to_string),
+            Some(backtrace),
         ));
     });
 
-    lwar::run();
+    let exit_code = match lwar::run() {
+        Ok(()) => 0,
+        Err(e) => {
+            error!(Category::General, "{e}");
+            show_fatal_error(&CrashReport::new(&e.to_string(), None, None));
+            -1
+        }
+    };
 
+    #[cfg(windows)]
     report_d3d11_leaks();
-    exit(0);
+    exit(exit_code);
+}
+
+/// Logs to stderr, which is only attached when the game is started from a console, and to the log file.
+fn init_logging() {
+    log::logger().add_sink(StderrSink);
+
+    let path = log::default_path();
+    match FileSink::open(&path, MAX_LOG_FILE_SIZE, MAX_LOG_FILES) {
+        Ok(sink) => log::logger().add_sink(sink),
+        Err(e) => error!(Category::General, "Failed to open log file '{}': {e}", path.display()),
+    }
+}
+
+/// Writes the crash report and tells the player about the error and where to find the report. As this also handles
+/// panics, which might have occurred while the logger was locked, messages are only logged if the logger is available.
+fn show_fatal_error(report: &CrashReport) {
+    let mut message = format!(
+        "The application has been terminated after a fatal error.\n\nThe error was: {}",
+        report.message
+    );
+
+    match report.write() {
+        Ok(path) => {
+            log::try_log(
+                Level::Info,
+                Category::General,
+                format_args!("A crash report has been written to '{}'.", path.display()),
+            );
+            message += &format!(
+                "\n\nA crash report has been written to '{}'. Please attach it when reporting the error.",
+                path.display()
+            );
+        }
+        Err(e) => log::try_log(
+            Level::Error,
+            Category::General,
+            format_args!("Failed to write the crash report: {e}"),
+        ),
+    }
+
+    show_message_box(Severity::Error, Buttons::Ok, &message);
 }
@@ -1,6 +1,9 @@
 pub mod error;
 pub mod graphics;
 pub mod input;
-mod window;
+pub mod memory_map;
+pub mod message_box;
+pub mod window;
 
-pub use window::{show_message_box, Event, Window};
+pub use message_box::show_message_box;
+pub use window::{Event, Window};
@@ -1,18 +1,24 @@
+use crate::log::Category;
+use std::{fmt, panic, process::exit};
+#[cfg(windows)]
 use std::{
-    panic,
-    process::exit,
     ptr::{self, null_mut},
     slice,
 };
-use winapi::um::{
-    errhandlingapi::GetLastError,
-    winbase::{
-        FormatMessageW, LocalFree, FORMAT_MESSAGE_ALLOCATE_BUFFER, FORMAT_MESSAGE_FROM_SYSTEM, FORMAT_MESSAGE_IGNORE_INSERTS,
-        FORMAT_MESSAGE_MAX_WIDTH_MASK,
+#[cfg(windows)]
+use winapi::{
+    shared::winerror::{DXGI_ERROR_DEVICE_REMOVED, DXGI_ERROR_DEVICE_RESET},
+    um::{
+        errhandlingapi::GetLastError,
+        winbase::{
+            FormatMessageW, LocalFree, FORMAT_MESSAGE_ALLOCATE_BUFFER, FORMAT_MESSAGE_FROM_SYSTEM, FORMAT_MESSAGE_IGNORE_INSERTS,
+            FORMAT_MESSAGE_MAX_WIDTH_MASK,
+        },
+        winnt::HRESULT,
     },
-    winnt::HRESULT,
 };
 
+#[cfg(windows)]
 pub fn get_error_message_for(error: u32) -> String {
     unsafe {
         let mut buffer: *mut u16 = null_mut();
@@ -43,22 +49,104 @@ pub fn get_error_message_for(error: u32) -> String {
     }
 }
 
-pub fn get_last_error() -> String {
-    unsafe { get_error_message_for(GetLastError()) }
+/// A failure the game can recover from or report to the user, e.g. a lost graphics device or a missing asset. Bugs
+/// are still reported by panicking.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// A Windows function failed with the error code returned by `GetLastError`.
+    Os { operation: String, code: u32, message: String },
+    /// A COM function, e.g. one of Direct3D or DXGI, failed with the given `HRESULT`.
+    HResult { operation: String, code: i32, message: String },
+    /// A failure without an error code, e.g. an invalid asset or a missing X server.
+    Other { operation: String, message: String },
+    /// The graphics device has been removed or reset, e.g. because the driver has been updated or has crashed. The
+    /// device and all of its resources have to be recreated.
+    DeviceLost { operation: String, reason: String },
 }
 
-pub fn handle_hresult_error(hr: HRESULT, error_message: &str) {
-    if hr < 0 {
-        panic!("{} {}", error_message, get_error_message_for(hr as u32));
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    /// Describes a failure without an error code. The operation is a sentence such as `Failed to create window.`,
+    /// while the message explains the cause, if known.
+    pub fn other(operation: impl Into<String>, message: impl Into<String>) -> Error {
+        Error::Other {
+            operation: operation.into(),
+            message: message.into(),
+        }
+    }
+
+    #[cfg(windows)]
+    pub fn last_os_error(operation: impl Into<String>) -> Error {
+        let code = unsafe { GetLastError() };
+        Error::Os {
+            operation: operation.into(),
+            code,
+            message: get_error_message_for(code),
+        }
+    }
+
+    #[cfg(windows)]
+    pub fn from_hresult(hr: HRESULT, operation: impl Into<String>) -> Error {
+        Error::HResult {
+            operation: operation.into(),
+            code: hr,
+            message: get_error_message_for(hr as u32),
+        }
+    }
+
+    pub fn operation(&self) -> &str {
+        match self {
+            Error::Os { operation, .. }
+            | Error::HResult { operation, .. }
+            | Error::Other { operation, .. }
+            | Error::DeviceLost { operation, .. } => operation,
+        }
+    }
+
+    pub fn is_device_lost(&self) -> bool {
+        matches!(self, Error::DeviceLost { .. })
     }
 }
 
-pub enum ShowMessageBox {
-    Yes,
-    No,
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Os {
+                operation,
+                code,
+                message,
+            } => write!(f, "{operation} {message} (error {code})"),
+            Error::HResult {
+                operation,
+                code,
+                message,
+            } => write!(f, "{operation} {message} (HRESULT 0x{code:08X})"),
+            Error::Other { operation, message } if message.is_empty() => write!(f, "{operation}"),
+            Error::Other { operation, message } => write!(f, "{operation} {message}"),
+            Error::DeviceLost { operation, reason } => write!(f, "{operation} The graphics device has been lost: {reason}"),
+        }
+    }
 }
 
-pub fn on_panic(callback: fn(error_message: &str)) {
+impl std::error::Error for Error {}
+
+/// Turns failed `HRESULT`s into errors; the errors indicating that the graphics device has been removed or reset are
+/// reported as device loss.
+#[cfg(windows)]
+pub fn check_hresult(hr: HRESULT, operation: &str) -> Result<()> {
+    match hr {
+        DXGI_ERROR_DEVICE_REMOVED | DXGI_ERROR_DEVICE_RESET => Err(Error::DeviceLost {
+            operation: operation.to_string(),
+            reason: get_error_message_for(hr as u32),
+        }),
+        hr if hr < 0 => Err(Error::from_hresult(hr, operation)),
+        _ => Ok(()),
+    }
+}
+
+/// Logs panics and passes their messages and locations to the callback before terminating the process.
+pub fn on_panic(callback: fn(error_message: &str, location: Option<&panic::Location>)) {
     panic::set_hook(Box::new(move |panic_info| {
         let error_message = {
             // Formatted strings such as `panic!("{}", 1)` are `String` instances.
@@ -72,9 +160,38 @@ pub fn on_panic(callback: fn(error_message: &str)) {
             }
         };
 
-        eprintln!("{error_message}");
-        callback(error_message);
+        // The panic might have occurred while the logger was locked, so waiting for it would dead-lock.
+        crate::log::try_log(crate::log::Level::Error, Category::General, format_args!("{error_message}"));
+        callback(error_message, panic_info.location());
 
         exit(-1);
     }));
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn errors_describe_the_operation_and_cause() {
+        let error = Error::HResult {
+            operation: "Failed to present back buffer.".to_string(),
+            code: 0x887A0005u32 as i32,
+            message: "The GPU device instance has been suspended.".to_string(),
+        };
+        assert_eq!(error.operation(), "Failed to present back buffer.");
+        assert_eq!(
+            error.to_string(),
+            "Failed to present back buffer. The GPU device instance has been suspended. (HRESULT 0x887A0005)"
+        );
+
+        assert_eq!(
+            Error::other("Failed to load asset 'ship.texture'.", "File not found.").to_string(),
+            "Failed to load asset 'ship.texture'. File not found."
+        );
+        assert_eq!(
+            Error::other("Failed to create window.", "").to_string(),
+            "Failed to create window."
+        );
+    }
+}
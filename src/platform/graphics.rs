@@ -1,58 +1,118 @@
-use com_ptr::ComPtr;
-use winapi::{
-    ctypes::c_void,
-    shared::{dxgi1_2::IDXGISwapChain1, dxgi1_3::DXGIGetDebugInterface1},
-    um::{
-        d3d11::*,
-        dxgidebug::{IDXGIDebug, DXGI_DEBUG_ALL, DXGI_DEBUG_RLO_ALL},
-    },
-    Interface,
-};
+use super::error::Result;
+use crate::primitives::{Color, Rectangle};
+use buffer::{BufferElement, BufferUsage, IndexElement, MapMode};
+use shader::VertexAttribute;
+use state::{BlendMode, DepthStencilDescription, PrimitiveType, RasterizerDescription, SamplerDescription};
+use texture::TextureDescription;
 
-mod com_ptr;
-pub mod graphics_device;
-pub mod render_target;
+pub mod buffer;
+#[cfg(windows)]
+pub mod d3d11;
+pub mod registry;
 pub mod shader;
+pub mod software;
 pub mod state;
-pub mod swap_chain;
 pub mod texture;
 
-pub struct GraphicsDevice {
-    device: ComPtr<ID3D11Device>,
-    context: ComPtr<ID3D11DeviceContext>,
-    swap_chain: ComPtr<IDXGISwapChain1>,
-    back_buffer: Option<RenderTarget>,
-}
+/// The operations the game needs from a rendering backend. Resources are owned by the caller and must only be used
+/// with the device that created them. Operations that might fail at runtime, e.g. because the device has been lost,
+/// return an error; errors caused by misuse of the API panic.
+///
+/// State objects are immutable and cached by the device: Creating a state object with the same description as an
+/// existing one returns the existing object, and binding a state object that is already bound is skipped.
+pub trait GraphicsDevice {
+    type RenderTarget;
+    type Texture2D;
+    type VertexShader;
+    type PixelShader;
+    type BlendState;
+    type DepthStencilState;
+    type RasterizerState;
+    type SamplerState;
+    type VertexBuffer<T: BufferElement>;
+    type IndexBuffer<T: IndexElement>;
+    type ConstantBuffer<T: BufferElement>;
 
-pub struct RenderTarget {
-    p: ComPtr<ID3D11RenderTargetView>,
-    pub width: u32,
-    pub height: u32,
-}
+    fn back_buffer(&self) -> &Self::RenderTarget;
+    fn resize_back_buffer(&mut self, width: u32, height: u32) -> Result<()>;
+    /// Recreates the device and its back buffer after an operation has failed with `Error::DeviceLost`. All resources
+    /// created before are lost along with the device and must not be used anymore; a `ResourceRegistry` can be used
+    /// to create them again.
+    fn recreate(&mut self) -> Result<()>;
+    fn present(&self) -> Result<()>;
 
-pub struct Texture2D {
-    p: ComPtr<ID3D11Texture2D>,
-    pub width: u32,
-    pub height: u32,
-}
+    /// Creates a render target that draws into the texture's largest mipmap. The texture must have been created with
+    /// `render_target` set.
+    fn create_render_target(&self, texture: &Self::Texture2D) -> Result<Self::RenderTarget>;
+    /// Creates a texture whose largest mipmap is initialized with `data`, if any, which is expected to contain the
+    /// rows of pixels from top to bottom without any padding. The other mipmaps are generated from it.
+    fn create_texture(&self, description: &TextureDescription, data: Option<&[u8]>) -> Result<Self::Texture2D>;
+    fn create_blend_state(&self, blend_mode: BlendMode) -> Result<Self::BlendState>;
+    fn create_depth_stencil_state(&self, description: &DepthStencilDescription) -> Result<Self::DepthStencilState>;
+    fn create_rasterizer_state(&self, description: &RasterizerDescription) -> Result<Self::RasterizerState>;
+    fn create_sampler_state(&self, description: &SamplerDescription) -> Result<Self::SamplerState>;
+    fn create_vertex_shader(&self, byte_code: &[u8], attributes: &[VertexAttribute]) -> Result<Self::VertexShader>;
+    fn create_pixel_shader(&self, byte_code: &[u8]) -> Result<Self::PixelShader>;
+    fn create_vertex_buffer<T: BufferElement>(&self, usage: BufferUsage<T>) -> Result<Self::VertexBuffer<T>>;
+    fn create_index_buffer<T: IndexElement>(&self, usage: BufferUsage<T>) -> Result<Self::IndexBuffer<T>>;
+    fn create_constant_buffer<T: BufferElement>(&self, usage: BufferUsage<T>) -> Result<Self::ConstantBuffer<T>>;
 
-pub struct VertexShader {
-    p: ComPtr<ID3D11VertexShader>,
-    input_layout: ComPtr<ID3D11InputLayout>,
-}
+    /// Copies `data` into the dynamic buffer, starting at the element with index `offset`.
+    fn update_vertex_buffer<T: BufferElement>(
+        &self,
+        buffer: &Self::VertexBuffer<T>,
+        mode: MapMode,
+        offset: usize,
+        data: &[T],
+    ) -> Result<()>;
+    /// Copies `data` into the dynamic buffer, starting at the element with index `offset`.
+    fn update_index_buffer<T: IndexElement>(
+        &self,
+        buffer: &Self::IndexBuffer<T>,
+        mode: MapMode,
+        offset: usize,
+        data: &[T],
+    ) -> Result<()>;
+    /// Replaces the contents of the dynamic buffer, discarding the previous ones.
+    fn update_constant_buffer<T: BufferElement>(&self, buffer: &Self::ConstantBuffer<T>, data: &T) -> Result<()>;
+    /// Replaces the pixels of the given region of the texture's largest mipmap. The other mipmaps are not updated
+    /// until `generate_mipmaps` is called.
+    fn update_texture(&self, texture: &Self::Texture2D, region: &Rectangle<u32>, data: &[u8]);
+    fn generate_mipmaps(&self, texture: &Self::Texture2D);
 
-pub struct PixelShader {
-    p: ComPtr<ID3D11PixelShader>,
-}
+    fn set_render_target(&self, render_target: &Self::RenderTarget);
+    fn set_vertex_shader(&self, vertex_shader: &Self::VertexShader);
+    fn set_pixel_shader(&self, pixel_shader: &Self::PixelShader);
+    fn set_vertex_buffer<T: BufferElement>(&self, vertex_buffer: &Self::VertexBuffer<T>);
+    /// Sets the buffer the per-instance attributes of the vertex shader are read from.
+    fn set_instance_buffer<T: BufferElement>(&self, instance_buffer: &Self::VertexBuffer<T>);
+    fn set_index_buffer<T: IndexElement>(&self, index_buffer: &Self::IndexBuffer<T>);
+    fn set_vertex_constant_buffer<T: BufferElement>(&self, slot: u32, constant_buffer: &Self::ConstantBuffer<T>);
+    fn set_pixel_constant_buffer<T: BufferElement>(&self, slot: u32, constant_buffer: &Self::ConstantBuffer<T>);
+    fn set_pixel_texture(&self, slot: u32, texture: &Self::Texture2D);
+    fn set_pixel_sampler_state(&self, slot: u32, sampler_state: &Self::SamplerState);
+    fn set_blend_state(&self, blend_state: &Self::BlendState);
+    fn set_depth_stencil_state(&self, depth_stencil_state: &Self::DepthStencilState);
+    fn set_rasterizer_state(&self, rasterizer_state: &Self::RasterizerState);
+    fn set_viewport(&self, viewport: &Rectangle<u32>);
+    fn set_scissor_rect(&self, rectangle: &Rectangle<u32>);
+    fn set_primitive_type(&self, primitive_type: PrimitiveType);
 
-pub fn report_d3d11_leaks() {
-    if cfg!(debug_assertions) {
-        unsafe {
-            let debug = ComPtr::<IDXGIDebug>::new(
-                |debug| DXGIGetDebugInterface1(0, &IDXGIDebug::uuidof(), debug as *mut *mut c_void),
-                "Failed to instantiate the IDXGIDebug interface.",
-            );
-            debug.ReportLiveObjects(DXGI_DEBUG_ALL, DXGI_DEBUG_RLO_ALL);
-        }
-    }
+    fn clear(&self, render_target: &Self::RenderTarget, color: Color);
+    fn draw(&self, vertex_count: u32, first_vertex: u32);
+    /// Draws the vertices referenced by the indices in the given range of the index buffer; `base_vertex` is added to
+    /// each index before the vertex is fetched from the vertex buffer.
+    fn draw_indexed(&self, index_count: u32, first_index: u32, base_vertex: i32);
+    /// Draws the vertices once for each instance, reading the per-instance attributes of the instances starting at
+    /// `first_instance` from the instance buffer.
+    fn draw_instanced(&self, vertex_count: u32, instance_count: u32, first_vertex: u32, first_instance: u32);
+    /// Combines `draw_indexed` and `draw_instanced`.
+    fn draw_indexed_instanced(
+        &self,
+        index_count: u32,
+        instance_count: u32,
+        first_index: u32,
+        base_vertex: i32,
+        first_instance: u32,
+    );
 }
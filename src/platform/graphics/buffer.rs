@@ -0,0 +1,57 @@
+use std::{mem, slice};
+
+/// A type whose values can be copied byte by byte into GPU buffers.
+///
+/// # Safety
+///
+/// Implementors must be `#[repr(C)]` (or primitives) without any padding bytes, and every bit pattern must be a valid
+/// value of the type.
+pub unsafe trait BufferElement: Copy + 'static {}
+
+/// The element types of index buffers.
+pub trait IndexElement: BufferElement {
+    const FORMAT: IndexFormat;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexFormat {
+    UInt16,
+    UInt32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BufferUsage<'a, T> {
+    /// The buffer holds the given elements, which can never be changed afterwards.
+    Static(&'a [T]),
+    /// The buffer holds the given number of elements, which are updated by the CPU, typically once per frame.
+    Dynamic(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MapMode {
+    /// The previous contents of the buffer are discarded entirely, so the GPU can keep using them while the buffer is
+    /// updated.
+    Discard,
+    /// The previous contents are kept; the caller guarantees not to overwrite any elements that might still be used by
+    /// pending draw calls.
+    NoOverwrite,
+}
+
+unsafe impl BufferElement for u8 {}
+unsafe impl BufferElement for u16 {}
+unsafe impl BufferElement for u32 {}
+unsafe impl BufferElement for i32 {}
+unsafe impl BufferElement for f32 {}
+unsafe impl<T: BufferElement, const N: usize> BufferElement for [T; N] {}
+
+impl IndexElement for u16 {
+    const FORMAT: IndexFormat = IndexFormat::UInt16;
+}
+
+impl IndexElement for u32 {
+    const FORMAT: IndexFormat = IndexFormat::UInt32;
+}
+
+pub fn as_bytes<T: BufferElement>(data: &[T]) -> &[u8] {
+    unsafe { slice::from_raw_parts(data.as_ptr() as *const u8, mem::size_of_val(data)) }
+}
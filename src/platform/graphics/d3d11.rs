@@ -0,0 +1,323 @@
+use super::{
+    buffer::{BufferElement, BufferUsage, IndexElement, MapMode},
+    shader::VertexAttribute,
+    state::{
+        BlendMode, BoundStates, DepthStencilDescription, PrimitiveType, RasterizerDescription, SamplerDescription, StateCache,
+    },
+    texture::{TextureDescription, TextureFormat},
+    GraphicsDevice,
+};
+use crate::{
+    platform::error::Result,
+    primitives::{Color, Rectangle},
+};
+use com_ptr::ComPtr;
+use std::{marker::PhantomData, rc::Rc};
+use winapi::{
+    ctypes::c_void,
+    shared::{dxgi1_2::IDXGISwapChain1, dxgi1_3::DXGIGetDebugInterface1, windef::HWND},
+    um::{
+        d3d11::*,
+        dxgidebug::{IDXGIDebug, DXGI_DEBUG_ALL, DXGI_DEBUG_RLO_ALL},
+    },
+    Interface,
+};
+
+pub mod buffer;
+mod com_ptr;
+pub mod graphics_device;
+pub mod render_target;
+pub mod shader;
+pub mod state;
+pub mod swap_chain;
+pub mod texture;
+
+pub struct D3D11GraphicsDevice {
+    hwnd: HWND,
+    device: ComPtr<ID3D11Device>,
+    context: ComPtr<ID3D11DeviceContext>,
+    /// Only missing if the device has been lost and the swap chain could not be recreated.
+    swap_chain: Option<ComPtr<IDXGISwapChain1>>,
+    back_buffer: Option<RenderTarget>,
+    blend_states: StateCache<BlendMode, BlendState>,
+    depth_stencil_states: StateCache<DepthStencilDescription, DepthStencilState>,
+    rasterizer_states: StateCache<RasterizerDescription, RasterizerState>,
+    sampler_states: StateCache<SamplerDescription, SamplerState>,
+    bound_states: BoundStates,
+}
+
+pub struct RenderTarget {
+    p: ComPtr<ID3D11RenderTargetView>,
+    pub width: u32,
+    pub height: u32,
+}
+
+pub struct Texture2D {
+    p: ComPtr<ID3D11Texture2D>,
+    // The swap chain's back buffer cannot be used as a shader resource.
+    view: Option<ComPtr<ID3D11ShaderResourceView>>,
+    pub width: u32,
+    pub height: u32,
+    pub format: TextureFormat,
+}
+
+// State objects are shared between all identical descriptions; the description identifies the bound state object.
+#[derive(Clone)]
+pub struct BlendState {
+    p: Rc<ComPtr<ID3D11BlendState>>,
+    blend_mode: BlendMode,
+}
+
+#[derive(Clone)]
+pub struct DepthStencilState {
+    p: Rc<ComPtr<ID3D11DepthStencilState>>,
+    description: DepthStencilDescription,
+}
+
+#[derive(Clone)]
+pub struct RasterizerState {
+    p: Rc<ComPtr<ID3D11RasterizerState>>,
+    description: RasterizerDescription,
+}
+
+#[derive(Clone)]
+pub struct SamplerState {
+    p: Rc<ComPtr<ID3D11SamplerState>>,
+    description: SamplerDescription,
+}
+
+pub struct VertexShader {
+    p: ComPtr<ID3D11VertexShader>,
+    input_layout: ComPtr<ID3D11InputLayout>,
+}
+
+pub struct PixelShader {
+    p: ComPtr<ID3D11PixelShader>,
+}
+
+pub struct VertexBuffer<T> {
+    p: ComPtr<ID3D11Buffer>,
+    element: PhantomData<T>,
+}
+
+pub struct IndexBuffer<T> {
+    p: ComPtr<ID3D11Buffer>,
+    element: PhantomData<T>,
+}
+
+pub struct ConstantBuffer<T> {
+    p: ComPtr<ID3D11Buffer>,
+    element: PhantomData<T>,
+}
+
+impl GraphicsDevice for D3D11GraphicsDevice {
+    type RenderTarget = RenderTarget;
+    type Texture2D = Texture2D;
+    type VertexShader = VertexShader;
+    type PixelShader = PixelShader;
+    type BlendState = BlendState;
+    type DepthStencilState = DepthStencilState;
+    type RasterizerState = RasterizerState;
+    type SamplerState = SamplerState;
+    type VertexBuffer<T: BufferElement> = VertexBuffer<T>;
+    type IndexBuffer<T: IndexElement> = IndexBuffer<T>;
+    type ConstantBuffer<T: BufferElement> = ConstantBuffer<T>;
+
+    fn back_buffer(&self) -> &RenderTarget {
+        D3D11GraphicsDevice::back_buffer(self)
+    }
+
+    fn resize_back_buffer(&mut self, width: u32, height: u32) -> Result<()> {
+        D3D11GraphicsDevice::resize_back_buffer(self, width, height)
+    }
+
+    fn recreate(&mut self) -> Result<()> {
+        D3D11GraphicsDevice::recreate(self)
+    }
+
+    fn present(&self) -> Result<()> {
+        D3D11GraphicsDevice::present(self)
+    }
+
+    fn create_render_target(&self, texture: &Texture2D) -> Result<RenderTarget> {
+        D3D11GraphicsDevice::create_render_target(self, texture)
+    }
+
+    fn create_texture(&self, description: &TextureDescription, data: Option<&[u8]>) -> Result<Texture2D> {
+        D3D11GraphicsDevice::create_texture(self, description, data)
+    }
+
+    fn create_blend_state(&self, blend_mode: BlendMode) -> Result<BlendState> {
+        D3D11GraphicsDevice::create_blend_state(self, blend_mode)
+    }
+
+    fn create_depth_stencil_state(&self, description: &DepthStencilDescription) -> Result<DepthStencilState> {
+        D3D11GraphicsDevice::create_depth_stencil_state(self, description)
+    }
+
+    fn create_rasterizer_state(&self, description: &RasterizerDescription) -> Result<RasterizerState> {
+        D3D11GraphicsDevice::create_rasterizer_state(self, description)
+    }
+
+    fn create_sampler_state(&self, description: &SamplerDescription) -> Result<SamplerState> {
+        D3D11GraphicsDevice::create_sampler_state(self, description)
+    }
+
+    fn create_vertex_shader(&self, byte_code: &[u8], attributes: &[VertexAttribute]) -> Result<VertexShader> {
+        D3D11GraphicsDevice::create_vertex_shader(self, byte_code, attributes)
+    }
+
+    fn create_pixel_shader(&self, byte_code: &[u8]) -> Result<PixelShader> {
+        D3D11GraphicsDevice::create_pixel_shader(self, byte_code)
+    }
+
+    fn create_vertex_buffer<T: BufferElement>(&self, usage: BufferUsage<T>) -> Result<VertexBuffer<T>> {
+        D3D11GraphicsDevice::create_vertex_buffer(self, usage)
+    }
+
+    fn create_index_buffer<T: IndexElement>(&self, usage: BufferUsage<T>) -> Result<IndexBuffer<T>> {
+        D3D11GraphicsDevice::create_index_buffer(self, usage)
+    }
+
+    fn create_constant_buffer<T: BufferElement>(&self, usage: BufferUsage<T>) -> Result<ConstantBuffer<T>> {
+        D3D11GraphicsDevice::create_constant_buffer(self, usage)
+    }
+
+    fn update_vertex_buffer<T: BufferElement>(
+        &self,
+        buffer: &VertexBuffer<T>,
+        mode: MapMode,
+        offset: usize,
+        data: &[T],
+    ) -> Result<()> {
+        D3D11GraphicsDevice::update_vertex_buffer(self, buffer, mode, offset, data)
+    }
+
+    fn update_index_buffer<T: IndexElement>(
+        &self,
+        buffer: &IndexBuffer<T>,
+        mode: MapMode,
+        offset: usize,
+        data: &[T],
+    ) -> Result<()> {
+        D3D11GraphicsDevice::update_index_buffer(self, buffer, mode, offset, data)
+    }
+
+    fn update_constant_buffer<T: BufferElement>(&self, buffer: &ConstantBuffer<T>, data: &T) -> Result<()> {
+        D3D11GraphicsDevice::update_constant_buffer(self, buffer, data)
+    }
+
+    fn update_texture(&self, texture: &Texture2D, region: &Rectangle<u32>, data: &[u8]) {
+        D3D11GraphicsDevice::update_texture(self, texture, region, data)
+    }
+
+    fn generate_mipmaps(&self, texture: &Texture2D) {
+        D3D11GraphicsDevice::generate_mipmaps(self, texture)
+    }
+
+    fn set_render_target(&self, render_target: &RenderTarget) {
+        D3D11GraphicsDevice::set_render_target(self, render_target)
+    }
+
+    fn set_vertex_shader(&self, vertex_shader: &VertexShader) {
+        D3D11GraphicsDevice::set_vertex_shader(self, vertex_shader)
+    }
+
+    fn set_pixel_shader(&self, pixel_shader: &PixelShader) {
+        D3D11GraphicsDevice::set_pixel_shader(self, pixel_shader)
+    }
+
+    fn set_vertex_buffer<T: BufferElement>(&self, vertex_buffer: &VertexBuffer<T>) {
+        D3D11GraphicsDevice::set_vertex_buffer(self, vertex_buffer)
+    }
+
+    fn set_instance_buffer<T: BufferElement>(&self, instance_buffer: &VertexBuffer<T>) {
+        D3D11GraphicsDevice::set_instance_buffer(self, instance_buffer)
+    }
+
+    fn set_index_buffer<T: IndexElement>(&self, index_buffer: &IndexBuffer<T>) {
+        D3D11GraphicsDevice::set_index_buffer(self, index_buffer)
+    }
+
+    fn set_vertex_constant_buffer<T: BufferElement>(&self, slot: u32, constant_buffer: &ConstantBuffer<T>) {
+        D3D11GraphicsDevice::set_vertex_constant_buffer(self, slot, constant_buffer)
+    }
+
+    fn set_pixel_constant_buffer<T: BufferElement>(&self, slot: u32, constant_buffer: &ConstantBuffer<T>) {
+        D3D11GraphicsDevice::set_pixel_constant_buffer(self, slot, constant_buffer)
+    }
+
+    fn set_pixel_texture(&self, slot: u32, texture: &Texture2D) {
+        D3D11GraphicsDevice::set_pixel_texture(self, slot, texture)
+    }
+
+    fn set_pixel_sampler_state(&self, slot: u32, sampler_state: &SamplerState) {
+        D3D11GraphicsDevice::set_pixel_sampler_state(self, slot, sampler_state)
+    }
+
+    fn set_blend_state(&self, blend_state: &BlendState) {
+        D3D11GraphicsDevice::set_blend_state(self, blend_state)
+    }
+
+    fn set_depth_stencil_state(&self, depth_stencil_state: &DepthStencilState) {
+        D3D11GraphicsDevice::set_depth_stencil_state(self, depth_stencil_state)
+    }
+
+    fn set_rasterizer_state(&self, rasterizer_state: &RasterizerState) {
+        D3D11GraphicsDevice::set_rasterizer_state(self, rasterizer_state)
+    }
+
+    fn set_viewport(&self, viewport: &Rectangle<u32>) {
+        D3D11GraphicsDevice::set_viewport(self, viewport)
+    }
+
+    fn set_scissor_rect(&self, rectangle: &Rectangle<u32>) {
+        D3D11GraphicsDevice::set_scissor_rect(self, rectangle)
+    }
+
+    fn set_primitive_type(&self, primitive_type: PrimitiveType) {
+        D3D11GraphicsDevice::set_primitive_type(self, primitive_type)
+    }
+
+    fn clear(&self, render_target: &RenderTarget, color: Color) {
+        D3D11GraphicsDevice::clear(self, render_target, color)
+    }
+
+    fn draw(&self, vertex_count: u32, first_vertex: u32) {
+        D3D11GraphicsDevice::draw(self, vertex_count, first_vertex)
+    }
+
+    fn draw_indexed(&self, index_count: u32, first_index: u32, base_vertex: i32) {
+        D3D11GraphicsDevice::draw_indexed(self, index_count, first_index, base_vertex)
+    }
+
+    fn draw_instanced(&self, vertex_count: u32, instance_count: u32, first_vertex: u32, first_instance: u32) {
+        D3D11GraphicsDevice::draw_instanced(self, vertex_count, instance_count, first_vertex, first_instance)
+    }
+
+    fn draw_indexed_instanced(
+        &self,
+        index_count: u32,
+        instance_count: u32,
+        first_index: u32,
+        base_vertex: i32,
+        first_instance: u32,
+    ) {
+        D3D11GraphicsDevice::draw_indexed_instanced(self, index_count, instance_count, first_index, base_vertex, first_instance)
+    }
+}
+
+pub fn report_d3d11_leaks() {
+    if cfg!(debug_assertions) {
+        unsafe {
+            // The debug interface is only available if the graphics tools are installed, in which case there is
+            // nothing to report.
+            if let Ok(debug) = ComPtr::<IDXGIDebug>::new(
+                |debug| DXGIGetDebugInterface1(0, &IDXGIDebug::uuidof(), debug as *mut *mut c_void),
+                "Failed to instantiate the IDXGIDebug interface.",
+            ) {
+                debug.ReportLiveObjects(DXGI_DEBUG_ALL, DXGI_DEBUG_RLO_ALL);
+            }
+        }
+    }
+}
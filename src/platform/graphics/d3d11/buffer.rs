@@ -0,0 +1,188 @@
+use super::{com_ptr::ComPtr, ConstantBuffer, D3D11GraphicsDevice, IndexBuffer, VertexBuffer};
+use crate::platform::{
+    error::Result,
+    graphics::buffer::{as_bytes, BufferElement, BufferUsage, IndexElement, IndexFormat, MapMode},
+};
+use std::{marker::PhantomData, mem, ptr};
+use winapi::{
+    shared::dxgiformat::{DXGI_FORMAT_R16_UINT, DXGI_FORMAT_R32_UINT},
+    um::d3d11::*,
+};
+
+impl D3D11GraphicsDevice {
+    pub fn create_vertex_buffer<T: BufferElement>(&self, usage: BufferUsage<T>) -> Result<VertexBuffer<T>> {
+        Ok(VertexBuffer {
+            p: self.create_buffer(D3D11_BIND_VERTEX_BUFFER, usage, "Failed to create vertex buffer.")?,
+            element: PhantomData,
+        })
+    }
+
+    pub fn create_index_buffer<T: IndexElement>(&self, usage: BufferUsage<T>) -> Result<IndexBuffer<T>> {
+        Ok(IndexBuffer {
+            p: self.create_buffer(D3D11_BIND_INDEX_BUFFER, usage, "Failed to create index buffer.")?,
+            element: PhantomData,
+        })
+    }
+
+    pub fn create_constant_buffer<T: BufferElement>(&self, usage: BufferUsage<T>) -> Result<ConstantBuffer<T>> {
+        Ok(ConstantBuffer {
+            p: self.create_buffer(D3D11_BIND_CONSTANT_BUFFER, usage, "Failed to create constant buffer.")?,
+            element: PhantomData,
+        })
+    }
+
+    pub fn update_vertex_buffer<T: BufferElement>(
+        &self,
+        buffer: &VertexBuffer<T>,
+        mode: MapMode,
+        offset: usize,
+        data: &[T],
+    ) -> Result<()> {
+        self.update_buffer(&buffer.p, mode, offset, data)
+    }
+
+    pub fn update_index_buffer<T: IndexElement>(
+        &self,
+        buffer: &IndexBuffer<T>,
+        mode: MapMode,
+        offset: usize,
+        data: &[T],
+    ) -> Result<()> {
+        self.update_buffer(&buffer.p, mode, offset, data)
+    }
+
+    pub fn update_constant_buffer<T: BufferElement>(&self, buffer: &ConstantBuffer<T>, data: &T) -> Result<()> {
+        self.update_buffer(&buffer.p, MapMode::Discard, 0, &[*data])
+    }
+
+    pub fn set_vertex_buffer<T: BufferElement>(&self, vertex_buffer: &VertexBuffer<T>) {
+        unsafe {
+            self.context
+                .IASetVertexBuffers(0, 1, &vertex_buffer.p.as_ptr(), &(mem::size_of::<T>() as u32), &0);
+        }
+    }
+
+    pub fn set_instance_buffer<T: BufferElement>(&self, instance_buffer: &VertexBuffer<T>) {
+        unsafe {
+            self.context
+                .IASetVertexBuffers(1, 1, &instance_buffer.p.as_ptr(), &(mem::size_of::<T>() as u32), &0);
+        }
+    }
+
+    pub fn set_index_buffer<T: IndexElement>(&self, index_buffer: &IndexBuffer<T>) {
+        let format = match T::FORMAT {
+            IndexFormat::UInt16 => DXGI_FORMAT_R16_UINT,
+            IndexFormat::UInt32 => DXGI_FORMAT_R32_UINT,
+        };
+
+        unsafe {
+            self.context.IASetIndexBuffer(index_buffer.p.as_ptr(), format, 0);
+        }
+    }
+
+    pub fn set_vertex_constant_buffer<T: BufferElement>(&self, slot: u32, constant_buffer: &ConstantBuffer<T>) {
+        unsafe {
+            self.context.VSSetConstantBuffers(slot, 1, &constant_buffer.p.as_ptr());
+        }
+    }
+
+    pub fn set_pixel_constant_buffer<T: BufferElement>(&self, slot: u32, constant_buffer: &ConstantBuffer<T>) {
+        unsafe {
+            self.context.PSSetConstantBuffers(slot, 1, &constant_buffer.p.as_ptr());
+        }
+    }
+
+    fn create_buffer<T: BufferElement>(
+        &self,
+        bind_flags: u32,
+        usage: BufferUsage<T>,
+        error_message: &str,
+    ) -> Result<ComPtr<ID3D11Buffer>> {
+        let (len, data) = match usage {
+            BufferUsage::Static(data) => (data.len(), Some(as_bytes(data))),
+            BufferUsage::Dynamic(len) => (len, None),
+        };
+
+        let mut size = len * mem::size_of::<T>();
+        if bind_flags == D3D11_BIND_CONSTANT_BUFFER {
+            // The size of constant buffers must be a multiple of 16 bytes.
+            size = (size + 15) & !15;
+        }
+
+        let desc = D3D11_BUFFER_DESC {
+            ByteWidth: size as u32,
+            Usage: if data.is_some() {
+                D3D11_USAGE_IMMUTABLE
+            } else {
+                D3D11_USAGE_DYNAMIC
+            },
+            BindFlags: bind_flags,
+            CPUAccessFlags: if data.is_some() { 0 } else { D3D11_CPU_ACCESS_WRITE },
+            MiscFlags: 0,
+            StructureByteStride: 0,
+        };
+
+        // Direct3D reads the entire buffer from the initial data, including any padding.
+        let data = data.map(|data| {
+            let mut padded = data.to_vec();
+            padded.resize(size, 0);
+            padded
+        });
+        let initial_data = data.as_ref().map(|data| D3D11_SUBRESOURCE_DATA {
+            pSysMem: data.as_ptr() as _,
+            SysMemPitch: 0,
+            SysMemSlicePitch: 0,
+        });
+
+        unsafe {
+            ComPtr::new(
+                |buffer| {
+                    self.device.CreateBuffer(
+                        &desc,
+                        initial_data.as_ref().map_or(ptr::null(), |data| data as *const _),
+                        buffer,
+                    )
+                },
+                error_message,
+            )
+        }
+    }
+
+    fn update_buffer<T: BufferElement>(
+        &self,
+        buffer: &ComPtr<ID3D11Buffer>,
+        mode: MapMode,
+        offset: usize,
+        data: &[T],
+    ) -> Result<()> {
+        let map_type = match mode {
+            MapMode::Discard => D3D11_MAP_WRITE_DISCARD,
+            MapMode::NoOverwrite => D3D11_MAP_WRITE_NO_OVERWRITE,
+        };
+
+        unsafe {
+            let mut desc = D3D11_BUFFER_DESC::default();
+            buffer.GetDesc(&mut desc);
+
+            let data = as_bytes(data);
+            let offset = offset * mem::size_of::<T>();
+            if desc.Usage != D3D11_USAGE_DYNAMIC {
+                panic!("Static buffers cannot be updated.");
+            }
+            if offset + data.len() > desc.ByteWidth as usize {
+                panic!("The data exceeds the size of the buffer.");
+            }
+
+            let resource = buffer.as_ptr() as *mut ID3D11Resource;
+            let mut mapped = D3D11_MAPPED_SUBRESOURCE::default();
+            self.check_device(
+                self.context.Map(resource, 0, map_type, 0, &mut mapped),
+                "Failed to map buffer.",
+            )?;
+
+            ptr::copy_nonoverlapping(data.as_ptr(), (mapped.pData as *mut u8).add(offset), data.len());
+            self.context.Unmap(resource, 0);
+            Ok(())
+        }
+    }
+}
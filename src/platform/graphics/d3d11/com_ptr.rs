@@ -1,4 +1,4 @@
-use crate::platform::error::handle_hresult_error;
+use crate::platform::error::{check_hresult, Error, Result};
 use std::{
     ops::Deref,
     ptr::{null_mut, NonNull},
@@ -13,12 +13,13 @@ pub struct ComPtr<T: Interface> {
 }
 
 impl<T: Interface> ComPtr<T> {
-    pub fn new(func: impl FnOnce(*mut *mut T) -> HRESULT, error_message: &str) -> ComPtr<T> {
+    pub fn new(func: impl FnOnce(*mut *mut T) -> HRESULT, error_message: &str) -> Result<ComPtr<T>> {
         let mut ptr: *mut T = null_mut();
-        handle_hresult_error(func(&mut ptr), error_message);
+        check_hresult(func(&mut ptr), error_message)?;
 
-        ComPtr {
-            p: NonNull::new(ptr).expect("Failed to allocate COM object."),
+        match NonNull::new(ptr) {
+            Some(p) => Ok(ComPtr { p }),
+            None => Err(Error::other(error_message, "No COM object has been returned.")),
         }
     }
 
@@ -26,7 +27,7 @@ impl<T: Interface> ComPtr<T> {
         self.p.as_ptr()
     }
 
-    pub fn convert<U: Interface>(&self) -> ComPtr<U> {
+    pub fn convert<U: Interface>(&self) -> Result<ComPtr<U>> {
         ComPtr::<U>::new(
             |obj| unsafe { (*self.p.as_ptr().cast::<IUnknown>()).QueryInterface(&U::uuidof(), obj as *mut *mut _) },
             "COM interface not implemented.",
@@ -0,0 +1,177 @@
+use super::{com_ptr::ComPtr, D3D11GraphicsDevice};
+use crate::platform::{
+    error::{check_hresult, get_error_message_for, Error, Result},
+    graphics::state::{BoundStates, StateCache},
+    window::win32::Win32Window,
+    Window,
+};
+use std::ptr::{null, null_mut};
+use winapi::{
+    shared::{
+        dxgi::{IDXGIAdapter, IDXGIDevice1, DXGI_SWAP_EFFECT_FLIP_DISCARD},
+        dxgi1_2::{IDXGIFactory2, IDXGISwapChain1, DXGI_SWAP_CHAIN_DESC1},
+        dxgiformat::DXGI_FORMAT_B8G8R8A8_UNORM,
+        dxgitype::{DXGI_SAMPLE_DESC, DXGI_USAGE_RENDER_TARGET_OUTPUT},
+        windef::{HWND, RECT},
+        winerror::S_OK,
+    },
+    um::{d3d11::*, d3dcommon::*, winnt::HRESULT, winuser::GetClientRect},
+    Interface,
+};
+
+impl D3D11GraphicsDevice {
+    pub fn new(window: &Win32Window) -> Result<D3D11GraphicsDevice> {
+        let (width, height) = window.size();
+        let (device, context, swap_chain) = unsafe { create_device(window.hwnd(), width, height)? };
+
+        let mut device = D3D11GraphicsDevice {
+            hwnd: window.hwnd(),
+            device,
+            context,
+            swap_chain: Some(swap_chain),
+            back_buffer: None,
+            blend_states: StateCache::default(),
+            depth_stencil_states: StateCache::default(),
+            rasterizer_states: StateCache::default(),
+            sampler_states: StateCache::default(),
+            bound_states: BoundStates::default(),
+        };
+
+        device.resize_back_buffer(width, height)?;
+        Ok(device)
+    }
+
+    /// Creates a new device, context, and swap chain after the device has been lost. The cached state objects belong
+    /// to the lost device, so they are discarded as well.
+    pub fn recreate(&mut self) -> Result<()> {
+        unsafe {
+            let mut rect = RECT::default();
+            GetClientRect(self.hwnd, &mut rect);
+            let (width, height) = ((rect.right - rect.left) as u32, (rect.bottom - rect.top) as u32);
+
+            self.back_buffer = None;
+            self.context.ClearState();
+            self.context.Flush();
+
+            // Only a single flip model swap chain can be associated with the window, so the lost one is released
+            // before the new one is created.
+            self.swap_chain = None;
+            self.blend_states = StateCache::default();
+            self.depth_stencil_states = StateCache::default();
+            self.rasterizer_states = StateCache::default();
+            self.sampler_states = StateCache::default();
+            self.bound_states = BoundStates::default();
+
+            let (device, context, swap_chain) = create_device(self.hwnd, width, height)?;
+            self.device = device;
+            self.context = context;
+            self.swap_chain = Some(swap_chain);
+        }
+
+        self.resize_back_buffer(width, height)
+    }
+
+    pub(super) fn swap_chain(&self) -> Result<&ComPtr<IDXGISwapChain1>> {
+        self.swap_chain.as_ref().ok_or_else(|| Error::DeviceLost {
+            operation: "Failed to access swap chain.".to_string(),
+            reason: "The swap chain has not been recreated.".to_string(),
+        })
+    }
+
+    /// Like `check_hresult`, but reports the reason why the device has been lost, if it has.
+    pub(super) fn check_device(&self, hr: HRESULT, operation: &str) -> Result<()> {
+        check_hresult(hr, operation).map_err(|e| match e {
+            Error::DeviceLost { operation, .. } => Error::DeviceLost {
+                operation,
+                reason: get_error_message_for(unsafe { self.device.GetDeviceRemovedReason() } as u32),
+            },
+            e => e,
+        })
+    }
+}
+
+unsafe fn create_device(
+    hwnd: HWND,
+    width: u32,
+    height: u32,
+) -> Result<(ComPtr<ID3D11Device>, ComPtr<ID3D11DeviceContext>, ComPtr<IDXGISwapChain1>)> {
+    let mut feature_level = D3D_FEATURE_LEVEL_11_0;
+    let device = ComPtr::<ID3D11Device>::new(
+        |device| {
+            D3D11CreateDevice(
+                null_mut(),
+                D3D_DRIVER_TYPE_HARDWARE,
+                null_mut(),
+                if cfg!(debug_assertions) {
+                    D3D11_CREATE_DEVICE_SINGLETHREADED | D3D11_CREATE_DEVICE_DEBUG
+                } else {
+                    D3D11_CREATE_DEVICE_SINGLETHREADED
+                },
+                null(),
+                0,
+                D3D11_SDK_VERSION,
+                device,
+                &mut feature_level,
+                null_mut(),
+            )
+        },
+        "Failed to create Direct3D 11 device.",
+    )?;
+
+    if feature_level < D3D_FEATURE_LEVEL_11_0 {
+        return Err(Error::other(
+            "Failed to create Direct3D 11 device.",
+            "Incompatible graphics card: Feature level 11.0 is required.",
+        ));
+    }
+
+    let context = ComPtr::<ID3D11DeviceContext>::new(
+        |context| {
+            device.GetImmediateContext(context);
+            S_OK
+        },
+        "Failed to get context.",
+    )?;
+
+    let factory = {
+        let device = device.convert::<IDXGIDevice1>()?;
+        let adapter = ComPtr::<IDXGIAdapter>::new(|adapter| device.GetAdapter(adapter), "Failed to retrieve DXGI adapter.")?;
+
+        ComPtr::<IDXGIFactory2>::new(
+            |factory| adapter.GetParent(&IDXGIFactory2::uuidof(), factory as *mut *mut _),
+            "Failed to retrieve DXGI factory.",
+        )?
+    };
+
+    let swap_chain_desc = DXGI_SWAP_CHAIN_DESC1 {
+        Width: width,
+        Height: height,
+        Format: DXGI_FORMAT_B8G8R8A8_UNORM,
+        SampleDesc: DXGI_SAMPLE_DESC { Count: 1, Quality: 0 },
+        BufferUsage: DXGI_USAGE_RENDER_TARGET_OUTPUT,
+        BufferCount: 2,
+        SwapEffect: DXGI_SWAP_EFFECT_FLIP_DISCARD,
+        ..Default::default()
+    };
+
+    let swap_chain = ComPtr::<IDXGISwapChain1>::new(
+        |swap_chain| {
+            factory.CreateSwapChainForHwnd(
+                device.as_ptr() as *mut _,
+                hwnd,
+                &swap_chain_desc,
+                null(),
+                null_mut(),
+                swap_chain,
+            )
+        },
+        "Unable to initialize swap chain.",
+    )?;
+
+    // Do not allow DXGI to make fullscreen mode transitions on ALT + Enter because we handle fullscreen mode
+    // ourselves with a borderless fullscreen window.
+    let dxgi_mwa_no_alt_enter = 1 << 1; // this is missing in the winapi crate
+    factory.MakeWindowAssociation(hwnd, dxgi_mwa_no_alt_enter);
+
+    Ok((device, context, swap_chain))
+}
@@ -1,16 +1,16 @@
-use super::{com_ptr::ComPtr, GraphicsDevice, RenderTarget, Texture2D};
-use crate::primitives::Color;
-use std::ptr::null;
+use super::{com_ptr::ComPtr, D3D11GraphicsDevice, RenderTarget, Texture2D};
+use crate::{platform::error::Result, primitives::Color};
+use std::ptr::{null, null_mut};
 use winapi::um::d3d11::{ID3D11RenderTargetView, ID3D11Resource};
 
-impl GraphicsDevice {
+impl D3D11GraphicsDevice {
     pub fn back_buffer(&self) -> &RenderTarget {
         self.back_buffer.as_ref().expect("Back buffer not initialized.")
     }
 
-    pub fn create_render_target(&self, texture: &Texture2D) -> RenderTarget {
+    pub fn create_render_target(&self, texture: &Texture2D) -> Result<RenderTarget> {
         unsafe {
-            RenderTarget {
+            Ok(RenderTarget {
                 width: texture.width,
                 height: texture.height,
                 p: ComPtr::<ID3D11RenderTargetView>::new(
@@ -19,8 +19,8 @@ impl GraphicsDevice {
                             .CreateRenderTargetView(texture.p.as_ptr() as *mut ID3D11Resource, null(), back_buffer)
                     },
                     "Failed to create render target.",
-                ),
-            }
+                )?,
+            })
         }
     }
 
@@ -37,4 +37,10 @@ impl GraphicsDevice {
             );
         }
     }
+
+    pub fn set_render_target(&self, render_target: &RenderTarget) {
+        unsafe {
+            self.context.OMSetRenderTargets(1, &render_target.p.as_ptr(), null_mut());
+        }
+    }
 }
@@ -0,0 +1,93 @@
+use super::{com_ptr::ComPtr, D3D11GraphicsDevice, PixelShader, VertexShader};
+use crate::platform::{
+    error::Result,
+    graphics::shader::{AttributeFormat, Semantic, VertexAttribute},
+};
+use std::ptr;
+use winapi::{
+    shared::dxgiformat::*,
+    um::d3d11::{D3D11_INPUT_ELEMENT_DESC, D3D11_INPUT_PER_INSTANCE_DATA, D3D11_INPUT_PER_VERTEX_DATA},
+};
+
+impl D3D11GraphicsDevice {
+    pub fn create_vertex_shader(&self, byte_code: &[u8], attributes: &[VertexAttribute]) -> Result<VertexShader> {
+        let input_elements = attributes.iter().map(input_element).collect::<Vec<_>>();
+
+        unsafe {
+            Ok(VertexShader {
+                p: ComPtr::new(
+                    |vertex_shader| {
+                        self.device
+                            .CreateVertexShader(byte_code.as_ptr() as _, byte_code.len(), ptr::null_mut(), vertex_shader)
+                    },
+                    "Failed to create vertex shader.",
+                )?,
+                input_layout: ComPtr::new(
+                    |layout| {
+                        self.device.CreateInputLayout(
+                            input_elements.as_ptr(),
+                            input_elements.len() as u32,
+                            byte_code.as_ptr() as _,
+                            byte_code.len(),
+                            layout,
+                        )
+                    },
+                    "Failed to create input layout.",
+                )?,
+            })
+        }
+    }
+
+    pub fn create_pixel_shader(&self, byte_code: &[u8]) -> Result<PixelShader> {
+        unsafe {
+            Ok(PixelShader {
+                p: ComPtr::new(
+                    |pixel_shader| {
+                        self.device
+                            .CreatePixelShader(byte_code.as_ptr() as _, byte_code.len(), ptr::null_mut(), pixel_shader)
+                    },
+                    "Failed to create pixel shader.",
+                )?,
+            })
+        }
+    }
+
+    pub fn set_vertex_shader(&self, vertex_shader: &VertexShader) {
+        unsafe {
+            self.context.VSSetShader(vertex_shader.p.as_ptr(), ptr::null(), 0);
+            self.context.IASetInputLayout(vertex_shader.input_layout.as_ptr());
+        }
+    }
+
+    pub fn set_pixel_shader(&self, pixel_shader: &PixelShader) {
+        unsafe {
+            self.context.PSSetShader(pixel_shader.p.as_ptr(), ptr::null(), 0);
+        }
+    }
+}
+
+fn input_element(attribute: &VertexAttribute) -> D3D11_INPUT_ELEMENT_DESC {
+    D3D11_INPUT_ELEMENT_DESC {
+        SemanticName: match attribute.semantic {
+            Semantic::Position => b"POSITION\0".as_ptr() as _,
+            Semantic::TexCoord => b"TEXCOORD\0".as_ptr() as _,
+            Semantic::Color => b"COLOR\0".as_ptr() as _,
+        },
+        SemanticIndex: attribute.semantic_index,
+        Format: match attribute.format {
+            AttributeFormat::Float => DXGI_FORMAT_R32_FLOAT,
+            AttributeFormat::Float2 => DXGI_FORMAT_R32G32_FLOAT,
+            AttributeFormat::Float3 => DXGI_FORMAT_R32G32B32_FLOAT,
+            AttributeFormat::Float4 => DXGI_FORMAT_R32G32B32A32_FLOAT,
+            AttributeFormat::UByte4Norm => DXGI_FORMAT_R8G8B8A8_UNORM,
+        },
+        // Per-instance attributes are read from the instance buffer in the second input slot.
+        InputSlot: attribute.per_instance as u32,
+        AlignedByteOffset: attribute.offset,
+        InputSlotClass: match attribute.per_instance {
+            true => D3D11_INPUT_PER_INSTANCE_DATA,
+            false => D3D11_INPUT_PER_VERTEX_DATA,
+        },
+        InstanceDataStepRate: attribute.per_instance as u32,
+    }
+}
@@ -0,0 +1,316 @@
+use super::{com_ptr::ComPtr, BlendState, D3D11GraphicsDevice, DepthStencilState, RasterizerState, SamplerState};
+use crate::{
+    platform::error::Result,
+    platform::graphics::state::{
+        AddressMode, BlendMode, Comparison, CullMode, DepthStencilDescription, PrimitiveType, RasterizerDescription,
+        SamplerDescription, StencilOperation, TextureFilter,
+    },
+    primitives::Rectangle,
+};
+use std::rc::Rc;
+use winapi::um::{
+    d3d11::*,
+    d3dcommon::{
+        D3D11_PRIMITIVE_TOPOLOGY_LINELIST, D3D11_PRIMITIVE_TOPOLOGY_LINESTRIP, D3D11_PRIMITIVE_TOPOLOGY_POINTLIST,
+        D3D11_PRIMITIVE_TOPOLOGY_TRIANGLELIST, D3D11_PRIMITIVE_TOPOLOGY_TRIANGLESTRIP,
+    },
+};
+
+impl D3D11GraphicsDevice {
+    pub fn create_blend_state(&self, blend_mode: BlendMode) -> Result<BlendState> {
+        self.blend_states.get_or_create(&blend_mode, || {
+            let (source, destination, source_alpha, destination_alpha) = match blend_mode {
+                BlendMode::Opaque => (D3D11_BLEND_ONE, D3D11_BLEND_ZERO, D3D11_BLEND_ONE, D3D11_BLEND_ZERO),
+                BlendMode::Alpha => (
+                    D3D11_BLEND_SRC_ALPHA,
+                    D3D11_BLEND_INV_SRC_ALPHA,
+                    D3D11_BLEND_ONE,
+                    D3D11_BLEND_INV_SRC_ALPHA,
+                ),
+                BlendMode::Additive => (D3D11_BLEND_SRC_ALPHA, D3D11_BLEND_ONE, D3D11_BLEND_ONE, D3D11_BLEND_ONE),
+                BlendMode::Premultiplied => (
+                    D3D11_BLEND_ONE,
+                    D3D11_BLEND_INV_SRC_ALPHA,
+                    D3D11_BLEND_ONE,
+                    D3D11_BLEND_INV_SRC_ALPHA,
+                ),
+            };
+
+            let render_target = D3D11_RENDER_TARGET_BLEND_DESC {
+                BlendEnable: (blend_mode != BlendMode::Opaque) as i32,
+                SrcBlend: source,
+                DestBlend: destination,
+                BlendOp: D3D11_BLEND_OP_ADD,
+                SrcBlendAlpha: source_alpha,
+                DestBlendAlpha: destination_alpha,
+                BlendOpAlpha: D3D11_BLEND_OP_ADD,
+                RenderTargetWriteMask: D3D11_COLOR_WRITE_ENABLE_ALL as u8,
+            };
+
+            let desc = D3D11_BLEND_DESC {
+                AlphaToCoverageEnable: 0,
+                IndependentBlendEnable: 0,
+                RenderTarget: [render_target; 8],
+            };
+
+            unsafe {
+                Ok(BlendState {
+                    p: Rc::new(ComPtr::new(
+                        |blend_state| self.device.CreateBlendState(&desc, blend_state),
+                        "Failed to create blend state.",
+                    )?),
+                    blend_mode,
+                })
+            }
+        })
+    }
+
+    pub fn create_depth_stencil_state(&self, description: &DepthStencilDescription) -> Result<DepthStencilState> {
+        self.depth_stencil_states.get_or_create(description, || {
+            let stencil = description.stencil.map_or(
+                D3D11_DEPTH_STENCILOP_DESC {
+                    StencilFailOp: D3D11_STENCIL_OP_KEEP,
+                    StencilDepthFailOp: D3D11_STENCIL_OP_KEEP,
+                    StencilPassOp: D3D11_STENCIL_OP_KEEP,
+                    StencilFunc: D3D11_COMPARISON_ALWAYS,
+                },
+                |stencil| D3D11_DEPTH_STENCILOP_DESC {
+                    StencilFailOp: D3D11_STENCIL_OP_KEEP,
+                    StencilDepthFailOp: D3D11_STENCIL_OP_KEEP,
+                    StencilPassOp: match stencil.operation {
+                        StencilOperation::Keep => D3D11_STENCIL_OP_KEEP,
+                        StencilOperation::Zero => D3D11_STENCIL_OP_ZERO,
+                        StencilOperation::Replace => D3D11_STENCIL_OP_REPLACE,
+                        StencilOperation::Increment => D3D11_STENCIL_OP_INCR_SAT,
+                        StencilOperation::Decrement => D3D11_STENCIL_OP_DECR_SAT,
+                    },
+                    StencilFunc: comparison_func(stencil.comparison),
+                },
+            );
+
+            let desc = D3D11_DEPTH_STENCIL_DESC {
+                DepthEnable: description.depth_test.is_some() as i32,
+                DepthWriteMask: match description.depth_write {
+                    true => D3D11_DEPTH_WRITE_MASK_ALL,
+                    false => D3D11_DEPTH_WRITE_MASK_ZERO,
+                },
+                DepthFunc: comparison_func(description.depth_test.unwrap_or(Comparison::Always)),
+                StencilEnable: description.stencil.is_some() as i32,
+                StencilReadMask: 0xff,
+                StencilWriteMask: 0xff,
+                FrontFace: stencil,
+                BackFace: stencil,
+            };
+
+            unsafe {
+                Ok(DepthStencilState {
+                    p: Rc::new(ComPtr::new(
+                        |depth_stencil_state| self.device.CreateDepthStencilState(&desc, depth_stencil_state),
+                        "Failed to create depth stencil state.",
+                    )?),
+                    description: *description,
+                })
+            }
+        })
+    }
+
+    pub fn create_rasterizer_state(&self, description: &RasterizerDescription) -> Result<RasterizerState> {
+        self.rasterizer_states.get_or_create(description, || {
+            let desc = D3D11_RASTERIZER_DESC {
+                FillMode: match description.wireframe {
+                    true => D3D11_FILL_WIREFRAME,
+                    false => D3D11_FILL_SOLID,
+                },
+                CullMode: match description.cull_mode {
+                    CullMode::None => D3D11_CULL_NONE,
+                    CullMode::Front => D3D11_CULL_FRONT,
+                    CullMode::Back => D3D11_CULL_BACK,
+                },
+                FrontCounterClockwise: 0,
+                DepthBias: 0,
+                DepthBiasClamp: 0.,
+                SlopeScaledDepthBias: 0.,
+                DepthClipEnable: 1,
+                ScissorEnable: description.scissor as i32,
+                MultisampleEnable: 0,
+                AntialiasedLineEnable: 0,
+            };
+
+            unsafe {
+                Ok(RasterizerState {
+                    p: Rc::new(ComPtr::new(
+                        |rasterizer_state| self.device.CreateRasterizerState(&desc, rasterizer_state),
+                        "Failed to create rasterizer state.",
+                    )?),
+                    description: *description,
+                })
+            }
+        })
+    }
+
+    pub fn create_sampler_state(&self, description: &SamplerDescription) -> Result<SamplerState> {
+        self.sampler_states
+            .get_or_create(description, || self.create_uncached_sampler_state(description))
+    }
+
+    fn create_uncached_sampler_state(&self, description: &SamplerDescription) -> Result<SamplerState> {
+        let address_mode = match description.address_mode {
+            AddressMode::Wrap => D3D11_TEXTURE_ADDRESS_WRAP,
+            AddressMode::Clamp => D3D11_TEXTURE_ADDRESS_CLAMP,
+            AddressMode::Mirror => D3D11_TEXTURE_ADDRESS_MIRROR,
+        };
+
+        let desc = D3D11_SAMPLER_DESC {
+            Filter: match description.filter {
+                TextureFilter::Nearest => D3D11_FILTER_MIN_MAG_MIP_POINT,
+                TextureFilter::Bilinear => D3D11_FILTER_MIN_MAG_LINEAR_MIP_POINT,
+                TextureFilter::Trilinear => D3D11_FILTER_MIN_MAG_MIP_LINEAR,
+            },
+            AddressU: address_mode,
+            AddressV: address_mode,
+            AddressW: address_mode,
+            MipLODBias: 0.,
+            MaxAnisotropy: 1,
+            ComparisonFunc: D3D11_COMPARISON_NEVER,
+            BorderColor: [0.; 4],
+            MinLOD: 0.,
+            MaxLOD: D3D11_FLOAT32_MAX,
+        };
+
+        unsafe {
+            Ok(SamplerState {
+                p: Rc::new(ComPtr::new(
+                    |sampler_state| self.device.CreateSamplerState(&desc, sampler_state),
+                    "Failed to create sampler state.",
+                )?),
+                description: *description,
+            })
+        }
+    }
+
+    pub fn set_pixel_sampler_state(&self, slot: u32, sampler_state: &SamplerState) {
+        if self.bound_states.samplers[slot as usize].bind(&sampler_state.description) {
+            unsafe {
+                self.context.PSSetSamplers(slot, 1, &sampler_state.p.as_ptr());
+            }
+        }
+    }
+
+    pub fn set_blend_state(&self, blend_state: &BlendState) {
+        if self.bound_states.blend.bind(&blend_state.blend_mode) {
+            unsafe {
+                self.context.OMSetBlendState(blend_state.p.as_ptr(), &[1.; 4], 0xffffffff);
+            }
+        }
+    }
+
+    pub fn set_depth_stencil_state(&self, depth_stencil_state: &DepthStencilState) {
+        if self.bound_states.depth_stencil.bind(&depth_stencil_state.description) {
+            let reference = depth_stencil_state.description.stencil.map_or(0, |stencil| stencil.reference);
+            unsafe {
+                self.context
+                    .OMSetDepthStencilState(depth_stencil_state.p.as_ptr(), reference as u32);
+            }
+        }
+    }
+
+    pub fn set_rasterizer_state(&self, rasterizer_state: &RasterizerState) {
+        if self.bound_states.rasterizer.bind(&rasterizer_state.description) {
+            unsafe {
+                self.context.RSSetState(rasterizer_state.p.as_ptr());
+            }
+        }
+    }
+
+    pub fn set_viewport(&self, viewport: &Rectangle<u32>) {
+        unsafe {
+            self.context.RSSetViewports(
+                1,
+                &D3D11_VIEWPORT {
+                    TopLeftX: viewport.left as f32,
+                    TopLeftY: viewport.top as f32,
+                    Width: viewport.width as f32,
+                    Height: viewport.height as f32,
+                    MaxDepth: 1.,
+                    MinDepth: 0.,
+                },
+            );
+        }
+    }
+
+    pub fn set_scissor_rect(&self, rectangle: &Rectangle<u32>) {
+        unsafe {
+            self.context.RSSetScissorRects(
+                1,
+                &D3D11_RECT {
+                    left: rectangle.left as i32,
+                    top: rectangle.top as i32,
+                    right: (rectangle.left + rectangle.width) as i32,
+                    bottom: (rectangle.top + rectangle.height) as i32,
+                },
+            );
+        }
+    }
+
+    pub fn set_primitive_type(&self, primitive_type: PrimitiveType) {
+        unsafe {
+            self.context.IASetPrimitiveTopology(primitive_topology(primitive_type));
+        }
+    }
+
+    pub fn draw(&self, vertex_count: u32, first_vertex: u32) {
+        unsafe {
+            self.context.Draw(vertex_count, first_vertex);
+        }
+    }
+
+    pub fn draw_indexed(&self, index_count: u32, first_index: u32, base_vertex: i32) {
+        unsafe {
+            self.context.DrawIndexed(index_count, first_index, base_vertex);
+        }
+    }
+
+    pub fn draw_instanced(&self, vertex_count: u32, instance_count: u32, first_vertex: u32, first_instance: u32) {
+        unsafe {
+            self.context
+                .DrawInstanced(vertex_count, instance_count, first_vertex, first_instance);
+        }
+    }
+
+    pub fn draw_indexed_instanced(
+        &self,
+        index_count: u32,
+        instance_count: u32,
+        first_index: u32,
+        base_vertex: i32,
+        first_instance: u32,
+    ) {
+        unsafe {
+            self.context
+                .DrawIndexedInstanced(index_count, instance_count, first_index, base_vertex, first_instance);
+        }
+    }
+}
+
+fn primitive_topology(primitive_type: PrimitiveType) -> D3D11_PRIMITIVE_TOPOLOGY {
+    match primitive_type {
+        PrimitiveType::Points => D3D11_PRIMITIVE_TOPOLOGY_POINTLIST,
+        PrimitiveType::Lines => D3D11_PRIMITIVE_TOPOLOGY_LINELIST,
+        PrimitiveType::LineStrip => D3D11_PRIMITIVE_TOPOLOGY_LINESTRIP,
+        PrimitiveType::Triangles => D3D11_PRIMITIVE_TOPOLOGY_TRIANGLELIST,
+        PrimitiveType::TriangleStrip => D3D11_PRIMITIVE_TOPOLOGY_TRIANGLESTRIP,
+    }
+}
+
+fn comparison_func(comparison: Comparison) -> D3D11_COMPARISON_FUNC {
+    match comparison {
+        Comparison::Never => D3D11_COMPARISON_NEVER,
+        Comparison::Less => D3D11_COMPARISON_LESS,
+        Comparison::LessEqual => D3D11_COMPARISON_LESS_EQUAL,
+        Comparison::Equal => D3D11_COMPARISON_EQUAL,
+        Comparison::GreaterEqual => D3D11_COMPARISON_GREATER_EQUAL,
+        Comparison::Greater => D3D11_COMPARISON_GREATER,
+        Comparison::NotEqual => D3D11_COMPARISON_NOT_EQUAL,
+        Comparison::Always => D3D11_COMPARISON_ALWAYS,
+    }
+}
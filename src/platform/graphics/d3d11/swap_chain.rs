@@ -1,46 +1,51 @@
-use super::{com_ptr::ComPtr, GraphicsDevice, Texture2D};
-use crate::platform::error::handle_hresult_error;
+use super::{com_ptr::ComPtr, D3D11GraphicsDevice, Texture2D};
+use crate::platform::{
+    error::Result,
+    graphics::{state::BoundStates, texture::TextureFormat},
+};
 use winapi::{shared::dxgi1_2::DXGI_SWAP_CHAIN_DESC1, um::d3d11::*, Interface};
 
-impl GraphicsDevice {
-    pub fn resize_back_buffer(&mut self, width: u32, height: u32) {
+impl D3D11GraphicsDevice {
+    pub fn resize_back_buffer(&mut self, width: u32, height: u32) -> Result<()> {
         unsafe {
             // We're not allowed to reference the old buffers anymore anywhere, so let's reset all
             // D3D11 state to the default values.
             self.back_buffer = None;
             self.context.ClearState();
             self.context.Flush();
+            self.bound_states = BoundStates::default();
 
+            let swap_chain = self.swap_chain()?;
             let mut desc = DXGI_SWAP_CHAIN_DESC1::default();
-            self.swap_chain.GetDesc1(&mut desc);
+            swap_chain.GetDesc1(&mut desc);
 
-            handle_hresult_error(
-                self.swap_chain.ResizeBuffers(desc.BufferCount, width, height, desc.Format, 0),
+            self.check_device(
+                swap_chain.ResizeBuffers(desc.BufferCount, width, height, desc.Format, 0),
                 "Failed to resize swap chain buffers.",
-            );
+            )?;
 
             let texture = Texture2D {
                 width,
                 height,
+                format: TextureFormat::Bgra8,
+                view: None,
                 p: ComPtr::<ID3D11Texture2D>::new(
-                    |texture| {
-                        self.swap_chain
-                            .GetBuffer(0, &ID3D11Texture2D::uuidof(), texture as *mut *mut _)
-                    },
+                    |texture| swap_chain.GetBuffer(0, &ID3D11Texture2D::uuidof(), texture as *mut *mut _),
                     "Failed to retrieve back buffer texture.",
-                ),
+                )?,
             };
 
-            self.back_buffer = Some(self.create_render_target(&texture));
+            self.back_buffer = Some(self.create_render_target(&texture)?);
+            Ok(())
         }
     }
 
-    pub fn present(&self) {
+    pub fn present(&self) -> Result<()> {
         unsafe {
-            handle_hresult_error(
-                self.swap_chain.Present(1 /* wait for VSYNC */, 0),
+            self.check_device(
+                self.swap_chain()?.Present(1 /* wait for VSYNC */, 0),
                 "Failed to present back buffer.",
-            );
+            )
         }
     }
 }
@@ -0,0 +1,137 @@
+use super::{com_ptr::ComPtr, D3D11GraphicsDevice, Texture2D};
+use crate::{
+    platform::{
+        error::Result,
+        graphics::texture::{TextureDescription, TextureFormat},
+    },
+    primitives::Rectangle,
+};
+use std::ptr::null;
+use winapi::{
+    shared::{dxgiformat::*, dxgitype::DXGI_SAMPLE_DESC},
+    um::d3d11::*,
+};
+
+impl D3D11GraphicsDevice {
+    pub fn create_texture(&self, description: &TextureDescription, data: Option<&[u8]>) -> Result<Texture2D> {
+        description.validate(data)?;
+
+        // Generating mipmaps on the GPU requires the texture to be bindable as a render target.
+        let mut bind_flags = D3D11_BIND_SHADER_RESOURCE;
+        if description.mipmaps || description.render_target {
+            bind_flags |= D3D11_BIND_RENDER_TARGET;
+        }
+
+        let desc = D3D11_TEXTURE2D_DESC {
+            Width: description.width,
+            Height: description.height,
+            MipLevels: if description.mipmaps {
+                0 /* full mipmap chain */
+            } else {
+                1
+            },
+            ArraySize: 1,
+            Format: match description.format {
+                TextureFormat::Rgba8 => DXGI_FORMAT_R8G8B8A8_UNORM,
+                TextureFormat::R8 => DXGI_FORMAT_R8_UNORM,
+                TextureFormat::Bgra8 => DXGI_FORMAT_B8G8R8A8_UNORM,
+            },
+            SampleDesc: DXGI_SAMPLE_DESC { Count: 1, Quality: 0 },
+            Usage: D3D11_USAGE_DEFAULT,
+            BindFlags: bind_flags,
+            CPUAccessFlags: 0,
+            MiscFlags: if description.mipmaps {
+                D3D11_RESOURCE_MISC_GENERATE_MIPS
+            } else {
+                0
+            },
+        };
+
+        unsafe {
+            let p = ComPtr::<ID3D11Texture2D>::new(
+                |texture| self.device.CreateTexture2D(&desc, null(), texture),
+                "Failed to create texture.",
+            )?;
+            let view = ComPtr::<ID3D11ShaderResourceView>::new(
+                |view| {
+                    self.device
+                        .CreateShaderResourceView(p.as_ptr() as *mut ID3D11Resource, null(), view)
+                },
+                "Failed to create shader resource view.",
+            )?;
+
+            let texture = Texture2D {
+                p,
+                view: Some(view),
+                width: description.width,
+                height: description.height,
+                format: description.format,
+            };
+
+            if let Some(data) = data {
+                let region = Rectangle {
+                    left: 0,
+                    top: 0,
+                    width: texture.width,
+                    height: texture.height,
+                };
+
+                self.update_texture(&texture, &region, data);
+                if description.mipmaps {
+                    self.generate_mipmaps(&texture);
+                }
+            }
+
+            Ok(texture)
+        }
+    }
+
+    pub fn update_texture(&self, texture: &Texture2D, region: &Rectangle<u32>, data: &[u8]) {
+        if region.left + region.width > texture.width || region.top + region.height > texture.height {
+            panic!("The region exceeds the size of the texture.");
+        }
+
+        let row_pitch = region.width * texture.format.bytes_per_pixel();
+        if data.len() != (row_pitch * region.height) as usize {
+            panic!("The size of the texture data does not match the region and the texture format.");
+        }
+
+        unsafe {
+            self.context.UpdateSubresource(
+                texture.p.as_ptr() as *mut ID3D11Resource,
+                0,
+                &D3D11_BOX {
+                    left: region.left,
+                    top: region.top,
+                    front: 0,
+                    right: region.left + region.width,
+                    bottom: region.top + region.height,
+                    back: 1,
+                },
+                data.as_ptr() as _,
+                row_pitch,
+                0,
+            );
+        }
+    }
+
+    pub fn generate_mipmaps(&self, texture: &Texture2D) {
+        unsafe {
+            self.context.GenerateMips(shader_resource_view(texture));
+        }
+    }
+
+    pub fn set_pixel_texture(&self, slot: u32, texture: &Texture2D) {
+        unsafe {
+            self.context.PSSetShaderResources(slot, 1, &shader_resource_view(texture));
+        }
+    }
+}
+
+fn shader_resource_view(texture: &Texture2D) -> *mut ID3D11ShaderResourceView {
+    texture
+        .view
+        .as_ref()
+        .expect("The texture cannot be used as a shader resource.")
+        .as_ptr()
+}
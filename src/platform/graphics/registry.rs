@@ -0,0 +1,398 @@
+use super::{
+    buffer::{BufferElement, BufferUsage, IndexElement},
+    shader::VertexAttribute,
+    state::{BlendMode, DepthStencilDescription, RasterizerDescription, SamplerDescription},
+    texture::TextureDescription,
+    GraphicsDevice,
+};
+use crate::platform::error::Result;
+use std::{
+    any::Any,
+    cell::{Ref, RefCell},
+    rc::{Rc, Weak},
+};
+
+/// Replaces the resource with a new one created by the given device, unless the resource has been dropped. It is
+/// reference counted so that it can be called without borrowing the registry, which `create` might use to register
+/// further resources.
+type Recreate<G> = Rc<dyn Fn(&G) -> Result<()>>;
+
+struct Entry<G> {
+    resource: Weak<dyn Any>,
+    recreate: Recreate<G>,
+}
+
+/// Creates graphics resources and remembers how they have been created, so that they can be created again after the
+/// device has been lost. The registry only keeps weak references to the resources; dropping a resource unregisters it.
+///
+/// Only the initial contents of resources are restored: Dynamic buffers are recreated with unspecified contents, and
+/// updates of textures are lost.
+pub struct ResourceRegistry<G: GraphicsDevice> {
+    resources: RefCell<Vec<Entry<G>>>,
+}
+
+/// A resource created by a `ResourceRegistry`, which is replaced whenever the registry recreates its resources.
+pub struct Resource<R> {
+    resource: Rc<RefCell<R>>,
+}
+
+impl<R> Resource<R> {
+    /// Borrows the resource; the borrow must not be held while the registry recreates its resources, which panics
+    /// otherwise.
+    pub fn get(&self) -> Ref<'_, R> {
+        self.resource.borrow()
+    }
+}
+
+impl<R> Clone for Resource<R> {
+    fn clone(&self) -> Resource<R> {
+        Resource {
+            resource: self.resource.clone(),
+        }
+    }
+}
+
+/// The owned counterpart of `BufferUsage`, as the data of static buffers is needed whenever they are recreated.
+enum Usage<T> {
+    Static(Vec<T>),
+    Dynamic(usize),
+}
+
+impl<T: BufferElement> Usage<T> {
+    fn new(usage: BufferUsage<T>) -> Usage<T> {
+        match usage {
+            BufferUsage::Static(data) => Usage::Static(data.to_vec()),
+            BufferUsage::Dynamic(len) => Usage::Dynamic(len),
+        }
+    }
+
+    fn get(&self) -> BufferUsage<'_, T> {
+        match self {
+            Usage::Static(data) => BufferUsage::Static(data),
+            Usage::Dynamic(len) => BufferUsage::Dynamic(*len),
+        }
+    }
+}
+
+impl<G: GraphicsDevice + 'static> ResourceRegistry<G> {
+    pub fn new() -> ResourceRegistry<G> {
+        ResourceRegistry {
+            resources: RefCell::new(vec![]),
+        }
+    }
+
+    /// Creates a resource and registers `create` to create it again after the device has been lost. `create` must
+    /// therefore own all data it needs; resources it depends on must have been registered before.
+    pub fn register<R: 'static>(&self, graphics_device: &G, create: impl Fn(&G) -> Result<R> + 'static) -> Result<Resource<R>> {
+        let resource = Rc::new(RefCell::new(create(graphics_device)?));
+        let weak = Rc::downgrade(&resource);
+
+        self.resources.borrow_mut().push(Entry {
+            resource: weak.clone(),
+            recreate: Rc::new(move |graphics_device| {
+                if let Some(resource) = weak.upgrade() {
+                    let created = create(graphics_device)?;
+                    *resource
+                        .try_borrow_mut()
+                        .expect("A resource is still borrowed while it is recreated.") = created;
+                }
+                Ok(())
+            }),
+        });
+
+        Ok(Resource { resource })
+    }
+
+    /// Recreates all resources that are still alive in the order they have been registered, returning their number.
+    /// The device is expected to have been recreated already. Resources registered while recreating others are
+    /// created by the new device and are therefore not recreated again.
+    pub fn recreate(&self, graphics_device: &G) -> Result<usize> {
+        let recreate: Vec<_> = {
+            let mut resources = self.resources.borrow_mut();
+
+            // Resources that have been dropped are unregistered first. Unregistering a resource releases the resources
+            // it depends on, which have been registered before it, so the resources are visited in reverse order.
+            for index in (0..resources.len()).rev() {
+                if resources[index].resource.strong_count() == 0 {
+                    resources.remove(index);
+                }
+            }
+
+            resources.iter().map(|entry| entry.recreate.clone()).collect()
+        };
+
+        // The resources are kept even if recreating them fails, so that they can be recreated once the device is
+        // available again.
+        for recreate in &recreate {
+            recreate(graphics_device)?;
+        }
+
+        Ok(recreate.len())
+    }
+
+    pub fn create_render_target(
+        &self,
+        graphics_device: &G,
+        texture: &Resource<G::Texture2D>,
+    ) -> Result<Resource<G::RenderTarget>> {
+        let texture = texture.clone();
+        self.register(graphics_device, move |graphics_device| {
+            graphics_device.create_render_target(&texture.get())
+        })
+    }
+
+    pub fn create_texture(
+        &self,
+        graphics_device: &G,
+        description: &TextureDescription,
+        data: Option<&[u8]>,
+    ) -> Result<Resource<G::Texture2D>> {
+        let (description, data) = (*description, data.map(<[u8]>::to_vec));
+        self.register(graphics_device, move |graphics_device| {
+            graphics_device.create_texture(&description, data.as_deref())
+        })
+    }
+
+    pub fn create_blend_state(&self, graphics_device: &G, blend_mode: BlendMode) -> Result<Resource<G::BlendState>> {
+        self.register(graphics_device, move |graphics_device| {
+            graphics_device.create_blend_state(blend_mode)
+        })
+    }
+
+    pub fn create_depth_stencil_state(
+        &self,
+        graphics_device: &G,
+        description: &DepthStencilDescription,
+    ) -> Result<Resource<G::DepthStencilState>> {
+        let description = *description;
+        self.register(graphics_device, move |graphics_device| {
+            graphics_device.create_depth_stencil_state(&description)
+        })
+    }
+
+    pub fn create_rasterizer_state(
+        &self,
+        graphics_device: &G,
+        description: &RasterizerDescription,
+    ) -> Result<Resource<G::RasterizerState>> {
+        let description = *description;
+        self.register(graphics_device, move |graphics_device| {
+            graphics_device.create_rasterizer_state(&description)
+        })
+    }
+
+    pub fn create_sampler_state(
+        &self,
+        graphics_device: &G,
+        description: &SamplerDescription,
+    ) -> Result<Resource<G::SamplerState>> {
+        let description = *description;
+        self.register(graphics_device, move |graphics_device| {
+            graphics_device.create_sampler_state(&description)
+        })
+    }
+
+    pub fn create_vertex_shader(
+        &self,
+        graphics_device: &G,
+        byte_code: &[u8],
+        attributes: &[VertexAttribute],
+    ) -> Result<Resource<G::VertexShader>> {
+        let (byte_code, attributes) = (byte_code.to_vec(), attributes.to_vec());
+        self.register(graphics_device, move |graphics_device| {
+            graphics_device.create_vertex_shader(&byte_code, &attributes)
+        })
+    }
+
+    pub fn create_pixel_shader(&self, graphics_device: &G, byte_code: &[u8]) -> Result<Resource<G::PixelShader>> {
+        let byte_code = byte_code.to_vec();
+        self.register(graphics_device, move |graphics_device| {
+            graphics_device.create_pixel_shader(&byte_code)
+        })
+    }
+
+    pub fn create_vertex_buffer<T: BufferElement>(
+        &self,
+        graphics_device: &G,
+        usage: BufferUsage<T>,
+    ) -> Result<Resource<G::VertexBuffer<T>>> {
+        let usage = Usage::new(usage);
+        self.register(graphics_device, move |graphics_device| {
+            graphics_device.create_vertex_buffer(usage.get())
+        })
+    }
+
+    pub fn create_index_buffer<T: IndexElement>(
+        &self,
+        graphics_device: &G,
+        usage: BufferUsage<T>,
+    ) -> Result<Resource<G::IndexBuffer<T>>> {
+        let usage = Usage::new(usage);
+        self.register(graphics_device, move |graphics_device| {
+            graphics_device.create_index_buffer(usage.get())
+        })
+    }
+
+    pub fn create_constant_buffer<T: BufferElement>(
+        &self,
+        graphics_device: &G,
+        usage: BufferUsage<T>,
+    ) -> Result<Resource<G::ConstantBuffer<T>>> {
+        let usage = Usage::new(usage);
+        self.register(graphics_device, move |graphics_device| {
+            graphics_device.create_constant_buffer(usage.get())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::graphics::{
+        buffer::MapMode,
+        software::{SoftwareGraphicsDevice, VertexOutput},
+        state::{AddressMode, PrimitiveType, TextureFilter},
+        texture::TextureFormat,
+    };
+
+    const TEXTURE: TextureDescription = TextureDescription {
+        width: 2,
+        height: 1,
+        format: TextureFormat::Rgba8,
+        mipmaps: false,
+        render_target: true,
+    };
+
+    fn device() -> SoftwareGraphicsDevice {
+        let device = SoftwareGraphicsDevice::new(4, 4);
+        device.register_vertex_program(b"vs", |_| VertexOutput {
+            position: [0., 0., 0., 1.],
+            varyings: vec![],
+        });
+        device.register_pixel_program(b"ps", |_| [1., 1., 1., 1.]);
+        device
+    }
+
+    #[test]
+    fn resources_are_recreated_after_device_loss() {
+        let mut device = device();
+        let registry = ResourceRegistry::new();
+        let pixels = [1, 2, 3, 4, 5, 6, 7, 8];
+        let texture = registry.create_texture(&device, &TEXTURE, Some(&pixels)).unwrap();
+        let render_target = registry.create_render_target(&device, &texture).unwrap();
+        let vertex_shader = registry.create_vertex_shader(&device, b"vs", &[]).unwrap();
+        let pixel_shader = registry.create_pixel_shader(&device, b"ps").unwrap();
+        let vertex_buffer = registry
+            .create_vertex_buffer::<f32>(&device, BufferUsage::Dynamic(4))
+            .unwrap();
+        let index_buffer = registry
+            .create_index_buffer::<u16>(&device, BufferUsage::Static(&[0, 1, 2]))
+            .unwrap();
+        let constant_buffer = registry
+            .create_constant_buffer::<f32>(&device, BufferUsage::Dynamic(1))
+            .unwrap();
+        let blend_state = registry.create_blend_state(&device, BlendMode::Alpha).unwrap();
+        let sampler_state = registry
+            .create_sampler_state(
+                &device,
+                &SamplerDescription {
+                    filter: TextureFilter::Bilinear,
+                    address_mode: AddressMode::Wrap,
+                },
+            )
+            .unwrap();
+
+        device.lose("The graphics driver has been updated.");
+        let error = device.present().unwrap_err();
+        assert!(error.is_device_lost());
+        assert_eq!(
+            error.to_string(),
+            "Failed to present back buffer. The graphics device has been lost: The graphics driver has been updated."
+        );
+
+        device.recreate().unwrap();
+        assert_eq!(registry.recreate(&device).unwrap(), 9);
+
+        // Resources created by the lost device would panic when used.
+        assert_eq!(texture.get().capture(0).pixels, pixels);
+        device.set_render_target(&render_target.get());
+        device.set_vertex_shader(&vertex_shader.get());
+        device.set_pixel_shader(&pixel_shader.get());
+        device.set_vertex_buffer(&vertex_buffer.get());
+        device.set_index_buffer(&index_buffer.get());
+        device.set_vertex_constant_buffer(0, &constant_buffer.get());
+        device.set_blend_state(&blend_state.get());
+        device.set_pixel_sampler_state(0, &sampler_state.get());
+        device
+            .update_vertex_buffer(&vertex_buffer.get(), MapMode::Discard, 0, &[1.; 4])
+            .unwrap();
+        device.set_primitive_type(PrimitiveType::Points);
+        device.draw_indexed(3, 0, 0);
+        device.present().unwrap();
+    }
+
+    #[test]
+    fn dropped_resources_are_unregistered() {
+        let device = device();
+        let registry = ResourceRegistry::new();
+        let blend_state = registry.create_blend_state(&device, BlendMode::Additive).unwrap();
+        let texture = registry.create_texture(&device, &TEXTURE, None).unwrap();
+        let render_target = registry.create_render_target(&device, &texture).unwrap();
+
+        // The render target keeps the texture alive, as the texture is needed to recreate it.
+        drop(blend_state);
+        drop(texture);
+        assert_eq!(registry.recreate(&device).unwrap(), 2);
+
+        drop(render_target);
+        assert_eq!(registry.recreate(&device).unwrap(), 0);
+    }
+
+    #[test]
+    fn resources_can_be_registered_while_recreating_others() {
+        let device = device();
+        let registry = Rc::new(ResourceRegistry::new());
+        let shaders = Rc::new(RefCell::new(vec![]));
+        let _blend_state = registry
+            .register(&device, {
+                let (registry, shaders) = (Rc::downgrade(&registry), shaders.clone());
+                move |device: &SoftwareGraphicsDevice| {
+                    if let Some(registry) = registry.upgrade() {
+                        shaders.borrow_mut().push(registry.create_pixel_shader(device, b"ps")?);
+                    }
+                    device.create_blend_state(BlendMode::Alpha)
+                }
+            })
+            .unwrap();
+
+        // Each time the blend state is created, another shader is registered, which is only recreated afterwards.
+        assert_eq!(registry.recreate(&device).unwrap(), 2);
+        assert_eq!(registry.recreate(&device).unwrap(), 3);
+        assert_eq!(shaders.borrow().len(), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "A resource is still borrowed while it is recreated.")]
+    fn borrowed_resources_cannot_be_recreated() {
+        let device = device();
+        let registry = ResourceRegistry::new();
+        let texture = registry.create_texture(&device, &TEXTURE, None).unwrap();
+
+        let _borrowed = texture.get();
+        let _ = registry.recreate(&device);
+    }
+
+    #[test]
+    fn resources_stay_registered_if_recreating_them_fails() {
+        let mut device = device();
+        let registry = ResourceRegistry::new();
+        let _shader = registry.create_pixel_shader(&device, b"ps").unwrap();
+
+        device.lose("The graphics card has been removed.");
+        let error = registry.recreate(&device).unwrap_err();
+        assert_eq!(error.operation(), "Failed to create pixel shader.");
+
+        device.recreate().unwrap();
+        assert_eq!(registry.recreate(&device).unwrap(), 1);
+    }
+}
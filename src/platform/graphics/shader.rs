@@ -1,58 +1,280 @@
-use super::{com_ptr::ComPtr, GraphicsDevice, PixelShader, VertexShader};
-use std::ptr;
-use winapi::um::d3d11::D3D11_INPUT_ELEMENT_DESC;
-
-impl GraphicsDevice {
-    pub fn create_vertex_shader(&self, byte_code: &[u8], input_elements: &[D3D11_INPUT_ELEMENT_DESC]) -> VertexShader {
-        unsafe {
-            VertexShader {
-                p: ComPtr::new(
-                    |vertex_shader| {
-                        self.device
-                            .CreateVertexShader(byte_code.as_ptr() as _, byte_code.len(), ptr::null_mut(), vertex_shader)
-                    },
-                    "Failed to create vertex shader.",
-                ),
-                input_layout: ComPtr::new(
-                    |layout| {
-                        self.device.CreateInputLayout(
-                            input_elements.as_ptr(),
-                            input_elements.len() as u32,
-                            byte_code.as_ptr() as _,
-                            byte_code.len(),
-                            layout,
-                        )
-                    },
-                    "Failed to create input layout.",
-                ),
-            }
+use super::buffer::BufferElement;
+use crate::assets::shader::{ScalarType, ShaderInput};
+use std::mem;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Semantic {
+    Position,
+    TexCoord,
+    Color,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttributeFormat {
+    Float,
+    Float2,
+    Float3,
+    Float4,
+    /// Four normalized unsigned bytes, e.g. a packed RGBA color.
+    UByte4Norm,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VertexAttribute {
+    pub semantic: Semantic,
+    pub semantic_index: u32,
+    pub format: AttributeFormat,
+    /// The byte offset of the attribute within a vertex.
+    pub offset: u32,
+    /// Whether the attribute is read from the instance buffer, advancing once per instance instead of once per vertex.
+    pub per_instance: bool,
+}
+
+/// A `#[repr(C)]` struct that is stored in vertex buffers and read by vertex shaders. The attributes are typically
+/// declared with [`vertex_attribute`], which derives their formats and offsets from the struct's fields.
+pub trait Vertex: BufferElement {
+    const ATTRIBUTES: &'static [VertexAttribute];
+}
+
+/// The field types of vertex structs that can be read by vertex shaders.
+pub trait AttributeType: BufferElement {
+    const FORMAT: AttributeFormat;
+}
+
+impl AttributeType for f32 {
+    const FORMAT: AttributeFormat = AttributeFormat::Float;
+}
+
+impl AttributeType for [f32; 2] {
+    const FORMAT: AttributeFormat = AttributeFormat::Float2;
+}
+
+impl AttributeType for [f32; 3] {
+    const FORMAT: AttributeFormat = AttributeFormat::Float3;
+}
+
+impl AttributeType for [f32; 4] {
+    const FORMAT: AttributeFormat = AttributeFormat::Float4;
+}
+
+impl AttributeType for [u8; 4] {
+    const FORMAT: AttributeFormat = AttributeFormat::UByte4Norm;
+}
+
+/// Determines the format of a vertex struct's field from the type returned by the accessor, which is never called.
+pub const fn attribute_format<V, T: AttributeType>(_field: fn(&V) -> &T) -> AttributeFormat {
+    T::FORMAT
+}
+
+/// Describes a field of a vertex struct as a vertex attribute with the given semantic and semantic index, e.g.
+/// `vertex_attribute!(SpriteVertex, color, Semantic::Color, 0)`. Fields whose types cannot be read by vertex shaders
+/// are rejected at compile time.
+macro_rules! vertex_attribute {
+    ($vertex:ty, $field:ident, $semantic:expr, $semantic_index:expr) => {
+        $crate::platform::graphics::shader::VertexAttribute {
+            semantic: $semantic,
+            semantic_index: $semantic_index,
+            format: $crate::platform::graphics::shader::attribute_format(|vertex: &$vertex| &vertex.$field),
+            offset: ::std::mem::offset_of!($vertex, $field) as u32,
+            per_instance: false,
+        }
+    };
+}
+
+pub(crate) use vertex_attribute;
+
+impl Semantic {
+    /// The semantic's name as used in HLSL.
+    pub fn name(self) -> &'static str {
+        match self {
+            Semantic::Position => "POSITION",
+            Semantic::TexCoord => "TEXCOORD",
+            Semantic::Color => "COLOR",
         }
     }
+}
 
-    pub fn create_pixel_shader(&self, byte_code: &[u8]) -> PixelShader {
-        unsafe {
-            PixelShader {
-                p: ComPtr::new(
-                    |pixel_shader| {
-                        self.device
-                            .CreatePixelShader(byte_code.as_ptr() as _, byte_code.len(), ptr::null_mut(), pixel_shader)
-                    },
-                    "Failed to create pixel shader.",
-                ),
-            }
+impl AttributeFormat {
+    pub fn components(self) -> u8 {
+        match self {
+            AttributeFormat::Float => 1,
+            AttributeFormat::Float2 => 2,
+            AttributeFormat::Float3 => 3,
+            AttributeFormat::Float4 | AttributeFormat::UByte4Norm => 4,
         }
     }
 
-    pub fn set_vertex_shader(&self, vertex_shader: &VertexShader) {
-        unsafe {
-            self.context.VSSetShader(vertex_shader.p.as_ptr(), ptr::null(), 0);
-            self.context.IASetInputLayout(vertex_shader.input_layout.as_ptr());
+    pub fn size(self) -> u32 {
+        match self {
+            AttributeFormat::UByte4Norm => 4,
+            format => format.components() as u32 * 4,
         }
     }
+}
 
-    pub fn set_pixel_shader(&self, pixel_shader: &PixelShader) {
-        unsafe {
-            self.context.PSSetShader(pixel_shader.p.as_ptr(), ptr::null(), 0);
+/// Selects the attributes of the vertex type that are read by a vertex shader with the given inputs, in the order of
+/// the inputs. Fails if the vertex type lacks an input or provides it with fewer components or a different type.
+pub fn input_layout<V: Vertex>(inputs: &[ShaderInput]) -> Result<Vec<VertexAttribute>, String> {
+    select_attributes(inputs, &attributes::<V>(false))
+}
+
+/// Like [`input_layout`], but the inputs not provided by the vertex type are read from the instance type, whose
+/// attributes advance once per instance of an instanced draw.
+pub fn instanced_input_layout<V: Vertex, I: Vertex>(inputs: &[ShaderInput]) -> Result<Vec<VertexAttribute>, String> {
+    select_attributes(inputs, &[attributes::<V>(false), attributes::<I>(true)].concat())
+}
+
+fn attributes<V: Vertex>(per_instance: bool) -> Vec<VertexAttribute> {
+    debug_assert!(
+        V::ATTRIBUTES
+            .iter()
+            .all(|attribute| attribute.offset + attribute.format.size() <= mem::size_of::<V>() as u32),
+        "The vertex attributes exceed the size of the vertex."
+    );
+
+    V::ATTRIBUTES
+        .iter()
+        .map(|attribute| VertexAttribute {
+            per_instance,
+            ..*attribute
+        })
+        .collect()
+}
+
+fn select_attributes(inputs: &[ShaderInput], attributes: &[VertexAttribute]) -> Result<Vec<VertexAttribute>, String> {
+    inputs
+        .iter()
+        .map(|input| {
+            let name = format!("{}{}", input.semantic, input.semantic_index);
+            let attribute = attributes
+                .iter()
+                .find(|attribute| attribute.semantic.name() == input.semantic && attribute.semantic_index == input.semantic_index)
+                .ok_or_else(|| format!("The vertex format does not provide the shader input '{name}'."))?;
+
+            // All attribute formats are read as floating point values; missing components would silently be
+            // filled with defaults.
+            if input.scalar_type != ScalarType::Float || attribute.format.components() < input.components {
+                return Err(format!(
+                    "The shader input '{name}' does not match the vertex format's {:?} attribute.",
+                    attribute.format
+                ));
+            }
+
+            Ok(*attribute)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    struct TestVertex {
+        position: [f32; 3],
+        tex_coords: [[f32; 2]; 2],
+        color: [u8; 4],
+    }
+
+    unsafe impl BufferElement for TestVertex {}
+
+    impl Vertex for TestVertex {
+        const ATTRIBUTES: &'static [VertexAttribute] = &[
+            vertex_attribute!(TestVertex, position, Semantic::Position, 0),
+            VertexAttribute {
+                semantic: Semantic::TexCoord,
+                semantic_index: 0,
+                format: AttributeFormat::Float2,
+                offset: 12,
+                per_instance: false,
+            },
+            VertexAttribute {
+                semantic: Semantic::TexCoord,
+                semantic_index: 1,
+                format: AttributeFormat::Float2,
+                offset: 20,
+                per_instance: false,
+            },
+            vertex_attribute!(TestVertex, color, Semantic::Color, 0),
+        ];
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    struct TestInstance {
+        offset: [f32; 2],
+        tex_coords: [f32; 2],
+    }
+
+    unsafe impl BufferElement for TestInstance {}
+
+    impl Vertex for TestInstance {
+        const ATTRIBUTES: &'static [VertexAttribute] = &[
+            vertex_attribute!(TestInstance, offset, Semantic::Position, 1),
+            vertex_attribute!(TestInstance, tex_coords, Semantic::TexCoord, 0),
+        ];
+    }
+
+    fn input(semantic: &str, semantic_index: u32, scalar_type: ScalarType, components: u8) -> ShaderInput {
+        ShaderInput {
+            semantic: semantic.to_string(),
+            semantic_index,
+            scalar_type,
+            components,
         }
     }
+
+    #[test]
+    fn attributes_are_derived_from_fields() {
+        let [position, _, _, color] = TestVertex::ATTRIBUTES else {
+            unreachable!()
+        };
+
+        assert_eq!((position.format, position.offset), (AttributeFormat::Float3, 0));
+        assert_eq!((color.format, color.offset), (AttributeFormat::UByte4Norm, 28));
+    }
+
+    #[test]
+    fn input_layouts_are_derived_from_the_shader_inputs() {
+        let attributes = TestVertex::ATTRIBUTES;
+        let inputs = [
+            input("COLOR", 0, ScalarType::Float, 4),
+            input("TEXCOORD", 1, ScalarType::Float, 2),
+            input("POSITION", 0, ScalarType::Float, 2),
+        ];
+        assert_eq!(
+            input_layout::<TestVertex>(&inputs),
+            Ok(vec![attributes[3], attributes[2], attributes[0]])
+        );
+
+        assert_eq!(
+            input_layout::<TestVertex>(&[input("TEXCOORD", 2, ScalarType::Float, 2)]),
+            Err("The vertex format does not provide the shader input 'TEXCOORD2'.".to_string())
+        );
+        assert_eq!(
+            input_layout::<TestVertex>(&[input("POSITION", 0, ScalarType::Float, 4)]),
+            Err("The shader input 'POSITION0' does not match the vertex format's Float3 attribute.".to_string())
+        );
+        assert!(input_layout::<TestVertex>(&[input("COLOR", 0, ScalarType::Uint, 4)]).is_err());
+    }
+
+    #[test]
+    fn instanced_input_layouts_prefer_vertex_attributes() {
+        let inputs = [
+            input("POSITION", 1, ScalarType::Float, 2),
+            input("TEXCOORD", 0, ScalarType::Float, 2),
+            input("POSITION", 0, ScalarType::Float, 3),
+        ];
+        let instance_offset = VertexAttribute {
+            per_instance: true,
+            ..TestInstance::ATTRIBUTES[0]
+        };
+
+        assert_eq!(
+            instanced_input_layout::<TestVertex, TestInstance>(&inputs),
+            Ok(vec![instance_offset, TestVertex::ATTRIBUTES[1], TestVertex::ATTRIBUTES[0]])
+        );
+        assert!(input_layout::<TestVertex>(&inputs).is_err());
+    }
 }
@@ -0,0 +1,1409 @@
+use super::{
+    buffer::{as_bytes, BufferElement, BufferUsage, IndexElement, IndexFormat, MapMode},
+    shader::VertexAttribute,
+    state::{
+        BlendMode, BoundStates, DepthStencilDescription, PrimitiveType, RasterizerDescription, SamplerDescription, StateCache,
+    },
+    texture::{TextureDescription, TextureFormat},
+    GraphicsDevice,
+};
+use crate::{
+    platform::error::{Error, Result},
+    primitives::{Color, Rectangle},
+};
+use program::{fetch_attributes, ConstantBuffers};
+use rasterizer::Target;
+use std::{cell::RefCell, collections::HashMap, marker::PhantomData, mem, ops::Range, rc::Rc};
+use texture::{downsample, to_rgba, SamplerSlots, TextureSlots};
+
+mod frame;
+mod program;
+mod rasterizer;
+mod texture;
+
+pub use frame::Frame;
+pub use program::{PixelInput, VertexInput};
+pub use rasterizer::VertexOutput;
+
+type VertexProgram = Rc<dyn Fn(&VertexInput) -> VertexOutput>;
+type PixelProgram = Rc<dyn Fn(&PixelInput) -> [f32; 4]>;
+type Pixels = Rc<RefCell<Vec<u8>>>;
+type Bytes = Rc<RefCell<Vec<u8>>>;
+type Presenter = Box<dyn FnMut(&[u8], u32, u32) -> Result<()>>;
+
+/// Renders into RGBA8 buffers in main memory. As shader byte code cannot be executed on the CPU, the byte code passed
+/// to `create_vertex_shader` and `create_pixel_shader` is only used to look up programs that have been registered
+/// for it beforehand. Presenting hands the back buffer's pixels to the presenter, if any, e.g. to show them in a
+/// window.
+///
+/// The device never loses its memory, but it can simulate device loss with `lose`, so that the recovery from device
+/// loss can be tested. Resources remember the generation of the device they have been created by, and using them after
+/// the device has been recreated panics.
+pub struct SoftwareGraphicsDevice {
+    back_buffer: RenderTarget,
+    generation: u32,
+    lost: RefCell<Option<String>>,
+    presenter: RefCell<Option<Presenter>>,
+    vertex_programs: RefCell<HashMap<Vec<u8>, VertexProgram>>,
+    pixel_programs: RefCell<HashMap<Vec<u8>, PixelProgram>>,
+    state: RefCell<PipelineState>,
+    blend_states: StateCache<BlendMode, BlendState>,
+    depth_stencil_states: StateCache<DepthStencilDescription, DepthStencilState>,
+    rasterizer_states: StateCache<RasterizerDescription, RasterizerState>,
+    sampler_states: StateCache<SamplerDescription, SamplerState>,
+    bound_states: BoundStates,
+}
+
+pub struct RenderTarget {
+    generation: u32,
+    pixels: Pixels,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Regardless of its format, a texture's pixels are stored as RGBA8.
+pub struct Texture2D {
+    generation: u32,
+    pixels: Pixels,
+    mipmaps: Rc<RefCell<Vec<Vec<u8>>>>,
+    pub width: u32,
+    pub height: u32,
+    pub format: TextureFormat,
+}
+
+// State objects are shared between all identical descriptions, like those of the Direct3D backend.
+#[derive(Clone)]
+pub struct BlendState {
+    generation: u32,
+    blend_mode: BlendMode,
+}
+
+/// Render targets have no depth stencil buffer, so depth stencil states have no effect.
+#[derive(Clone)]
+pub struct DepthStencilState {
+    generation: u32,
+    description: DepthStencilDescription,
+}
+
+#[derive(Clone)]
+pub struct RasterizerState {
+    generation: u32,
+    description: RasterizerDescription,
+}
+
+#[derive(Clone)]
+pub struct SamplerState {
+    generation: u32,
+    description: SamplerDescription,
+}
+
+pub struct VertexShader {
+    generation: u32,
+    program: VertexProgram,
+    attributes: Rc<[VertexAttribute]>,
+}
+
+pub struct PixelShader {
+    generation: u32,
+    program: PixelProgram,
+}
+
+pub struct VertexBuffer<T> {
+    buffer: Buffer,
+    element: PhantomData<T>,
+}
+
+pub struct IndexBuffer<T> {
+    buffer: Buffer,
+    element: PhantomData<T>,
+}
+
+pub struct ConstantBuffer<T> {
+    buffer: Buffer,
+    element: PhantomData<T>,
+}
+
+struct Buffer {
+    generation: u32,
+    bytes: Bytes,
+    dynamic: bool,
+}
+
+#[derive(Default)]
+struct PipelineState {
+    render_target: Option<(Pixels, u32, u32)>,
+    vertex_shader: Option<(VertexProgram, Rc<[VertexAttribute]>)>,
+    pixel_program: Option<PixelProgram>,
+    vertex_buffer: Option<(Bytes, usize)>,
+    instance_buffer: Option<(Bytes, usize)>,
+    index_buffer: Option<(Bytes, IndexFormat)>,
+    vertex_constant_buffers: ConstantBuffers,
+    pixel_constant_buffers: ConstantBuffers,
+    pixel_textures: TextureSlots,
+    pixel_samplers: SamplerSlots,
+    primitive_type: Option<PrimitiveType>,
+    viewport: Rectangle<u32>,
+    scissor_rect: Rectangle<u32>,
+    blend_mode: BlendMode,
+    rasterizer: RasterizerDescription,
+}
+
+impl SoftwareGraphicsDevice {
+    pub fn new(width: u32, height: u32) -> SoftwareGraphicsDevice {
+        SoftwareGraphicsDevice {
+            back_buffer: RenderTarget::new(width, height, 0),
+            generation: 0,
+            lost: RefCell::new(None),
+            presenter: RefCell::new(None),
+            vertex_programs: RefCell::new(HashMap::new()),
+            pixel_programs: RefCell::new(HashMap::new()),
+            state: RefCell::new(PipelineState::default()),
+            blend_states: StateCache::default(),
+            depth_stencil_states: StateCache::default(),
+            rasterizer_states: StateCache::default(),
+            sampler_states: StateCache::default(),
+            bound_states: BoundStates::default(),
+        }
+    }
+
+    pub fn set_presenter(&mut self, presenter: impl FnMut(&[u8], u32, u32) -> Result<()> + 'static) {
+        self.presenter = RefCell::new(Some(Box::new(presenter)));
+    }
+
+    /// Simulates the loss of the device, e.g. because the graphics driver has been updated. Until the device has been
+    /// recreated, all operations that can report device loss fail with the given reason.
+    pub fn lose(&self, reason: impl Into<String>) {
+        *self.lost.borrow_mut() = Some(reason.into());
+    }
+
+    pub fn register_vertex_program(&self, byte_code: &[u8], program: impl Fn(&VertexInput) -> VertexOutput + 'static) {
+        self.vertex_programs.borrow_mut().insert(byte_code.to_vec(), Rc::new(program));
+    }
+
+    pub fn register_pixel_program(&self, byte_code: &[u8], program: impl Fn(&PixelInput) -> [f32; 4] + 'static) {
+        self.pixel_programs.borrow_mut().insert(byte_code.to_vec(), Rc::new(program));
+    }
+
+    fn check_lost(&self, operation: &str) -> Result<()> {
+        match &*self.lost.borrow() {
+            Some(reason) => Err(Error::DeviceLost {
+                operation: operation.to_string(),
+                reason: reason.clone(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Panics if the resource has been created before the device has been recreated, which is an error on GPUs as well.
+    fn check_generation(&self, generation: u32) {
+        if generation != self.generation {
+            panic!("The resource has been created by the device before it has been lost.");
+        }
+    }
+
+    fn draw_vertices(&self, vertex_ids: impl Iterator<Item = u32> + Clone, instance_ids: Range<u32>) {
+        let state = self.state.borrow();
+        let (Some((pixels, width, height)), Some((vertex_program, attributes)), Some(pixel_program), Some(primitive_type)) = (
+            &state.render_target,
+            &state.vertex_shader,
+            &state.pixel_program,
+            state.primitive_type,
+        ) else {
+            panic!("Incomplete pipeline state: A render target, shaders, and a primitive type must be set before drawing.");
+        };
+
+        let vertex_buffer = state.vertex_buffer.as_ref().map(|(bytes, stride)| (bytes.borrow(), *stride));
+        let instance_buffer = state
+            .instance_buffer
+            .as_ref()
+            .map(|(bytes, stride)| (bytes.borrow(), *stride));
+        let reads_vertices = attributes.iter().any(|attribute| !attribute.per_instance);
+        let reads_instances = attributes.iter().any(|attribute| attribute.per_instance);
+
+        let pixel_program = |varyings: &[f32]| {
+            pixel_program(&PixelInput {
+                varyings,
+                constant_buffers: &state.pixel_constant_buffers,
+                textures: &state.pixel_textures,
+                samplers: &state.pixel_samplers,
+            })
+        };
+
+        let mut pixels = pixels.borrow_mut();
+        let mut target = Target {
+            pixels: &mut pixels,
+            width: *width,
+            height: *height,
+            viewport: state.viewport,
+            scissor: state.rasterizer.scissor.then_some(state.scissor_rect),
+            cull_mode: state.rasterizer.cull_mode,
+            wireframe: state.rasterizer.wireframe,
+            blend_mode: state.blend_mode,
+        };
+
+        for instance_id in instance_ids {
+            let instance = match (&instance_buffer, reads_instances) {
+                (_, false) => &[][..],
+                (None, true) => panic!("The vertex shader has per-instance attributes, but no instance buffer has been set."),
+                (Some((bytes, stride)), true) => element(bytes, *stride, instance_id)
+                    .unwrap_or_else(|| panic!("Instance {instance_id} lies outside of the instance buffer.")),
+            };
+
+            let vertices = vertex_ids
+                .clone()
+                .map(|vertex_id| {
+                    let vertex = match (&vertex_buffer, reads_vertices) {
+                        (_, false) => &[][..],
+                        (None, true) => panic!("The vertex shader has attributes, but no vertex buffer has been set."),
+                        (Some((bytes, stride)), true) => element(bytes, *stride, vertex_id)
+                            .unwrap_or_else(|| panic!("Vertex {vertex_id} lies outside of the vertex buffer.")),
+                    };
+
+                    vertex_program(&VertexInput {
+                        vertex_id,
+                        instance_id,
+                        attributes: fetch_attributes(vertex, instance, attributes),
+                        constant_buffers: &state.vertex_constant_buffers,
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            target.draw(primitive_type, &vertices, &pixel_program);
+        }
+    }
+
+    fn indices(&self, index_count: u32, first_index: u32) -> Vec<u32> {
+        let state = self.state.borrow();
+        let (bytes, format) = state
+            .index_buffer
+            .as_ref()
+            .expect("An index buffer must be set before drawing indexed primitives.");
+        let bytes = bytes.borrow();
+
+        let index_size = match format {
+            IndexFormat::UInt16 => 2,
+            IndexFormat::UInt32 => 4,
+        };
+        let start = first_index as usize * index_size;
+        let end = start + index_count as usize * index_size;
+
+        bytes
+            .get(start..end)
+            .expect("The indices lie outside of the index buffer.")
+            .chunks_exact(index_size)
+            .map(|index| match format {
+                IndexFormat::UInt16 => u16::from_ne_bytes([index[0], index[1]]) as u32,
+                IndexFormat::UInt32 => u32::from_ne_bytes([index[0], index[1], index[2], index[3]]),
+            })
+            .collect()
+    }
+}
+
+/// Returns the bytes of the element with the given index, if the buffer contains it.
+fn element(bytes: &[u8], stride: usize, index: u32) -> Option<&[u8]> {
+    let offset = index as usize * stride;
+    bytes.get(offset..offset + stride)
+}
+
+impl RenderTarget {
+    fn new(width: u32, height: u32, generation: u32) -> RenderTarget {
+        RenderTarget {
+            generation,
+            pixels: Rc::new(RefCell::new(vec![0; (width * height * 4) as usize])),
+            width,
+            height,
+        }
+    }
+
+    pub fn capture(&self) -> Frame {
+        Frame {
+            width: self.width,
+            height: self.height,
+            pixels: self.pixels.borrow().clone(),
+        }
+    }
+}
+
+impl Texture2D {
+    /// Captures the mipmap at the given level, where level 0 is the largest one.
+    pub fn capture(&self, level: usize) -> Frame {
+        let (mut width, mut height) = (self.width, self.height);
+        for _ in 0..level {
+            (width, height) = ((width / 2).max(1), (height / 2).max(1));
+        }
+
+        Frame {
+            width,
+            height,
+            pixels: match level {
+                0 => self.pixels.borrow().clone(),
+                _ => self.mipmaps.borrow()[level - 1].clone(),
+            },
+        }
+    }
+}
+
+impl Buffer {
+    fn new<T: BufferElement>(usage: BufferUsage<T>, generation: u32) -> Buffer {
+        match usage {
+            BufferUsage::Static(data) => Buffer {
+                generation,
+                bytes: Rc::new(RefCell::new(as_bytes(data).to_vec())),
+                dynamic: false,
+            },
+            BufferUsage::Dynamic(len) => Buffer {
+                generation,
+                bytes: Rc::new(RefCell::new(vec![0; len * mem::size_of::<T>()])),
+                dynamic: true,
+            },
+        }
+    }
+
+    fn update<T: BufferElement>(&self, offset: usize, data: &[T]) {
+        if !self.dynamic {
+            panic!("Static buffers cannot be updated.");
+        }
+
+        // Discarding keeps the previous contents, which is one of the possible behaviors of a GPU as well.
+        let offset = offset * mem::size_of::<T>();
+        let data = as_bytes(data);
+        self.bytes
+            .borrow_mut()
+            .get_mut(offset..offset + data.len())
+            .expect("The data exceeds the size of the buffer.")
+            .copy_from_slice(data);
+    }
+}
+
+impl GraphicsDevice for SoftwareGraphicsDevice {
+    type RenderTarget = RenderTarget;
+    type Texture2D = Texture2D;
+    type VertexShader = VertexShader;
+    type PixelShader = PixelShader;
+    type BlendState = BlendState;
+    type DepthStencilState = DepthStencilState;
+    type RasterizerState = RasterizerState;
+    type SamplerState = SamplerState;
+    type VertexBuffer<T: BufferElement> = VertexBuffer<T>;
+    type IndexBuffer<T: IndexElement> = IndexBuffer<T>;
+    type ConstantBuffer<T: BufferElement> = ConstantBuffer<T>;
+
+    fn back_buffer(&self) -> &RenderTarget {
+        &self.back_buffer
+    }
+
+    fn resize_back_buffer(&mut self, width: u32, height: u32) -> Result<()> {
+        self.check_lost("Failed to resize back buffer.")?;
+
+        // Mirror the Direct3D backend, which has to reset all state when the swap chain is resized.
+        self.back_buffer = RenderTarget::new(width, height, self.generation);
+        *self.state.borrow_mut() = PipelineState::default();
+        self.bound_states = BoundStates::default();
+        Ok(())
+    }
+
+    fn recreate(&mut self) -> Result<()> {
+        *self.lost.borrow_mut() = None;
+        self.generation += 1;
+        // The cached state objects belong to the lost device.
+        self.blend_states = StateCache::default();
+        self.depth_stencil_states = StateCache::default();
+        self.rasterizer_states = StateCache::default();
+        self.sampler_states = StateCache::default();
+        let (width, height) = (self.back_buffer.width, self.back_buffer.height);
+        self.resize_back_buffer(width, height)
+    }
+
+    fn present(&self) -> Result<()> {
+        self.check_lost("Failed to present back buffer.")?;
+        if let Some(presenter) = self.presenter.borrow_mut().as_mut() {
+            presenter(
+                &self.back_buffer.pixels.borrow(),
+                self.back_buffer.width,
+                self.back_buffer.height,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn create_render_target(&self, texture: &Texture2D) -> Result<RenderTarget> {
+        self.check_lost("Failed to create render target.")?;
+        self.check_generation(texture.generation);
+        Ok(RenderTarget {
+            generation: self.generation,
+            pixels: texture.pixels.clone(),
+            width: texture.width,
+            height: texture.height,
+        })
+    }
+
+    fn create_texture(&self, description: &TextureDescription, data: Option<&[u8]>) -> Result<Texture2D> {
+        self.check_lost("Failed to create texture.")?;
+        description.validate(data)?;
+        let (width, height) = (description.width, description.height);
+        let mut mipmap_count = 0;
+        if description.mipmaps {
+            mipmap_count = 31 - width.max(height).leading_zeros();
+        }
+
+        let texture = Texture2D {
+            generation: self.generation,
+            pixels: Rc::new(RefCell::new(vec![0; (width * height * 4) as usize])),
+            mipmaps: Rc::new(RefCell::new(vec![vec![]; mipmap_count as usize])),
+            width,
+            height,
+            format: description.format,
+        };
+
+        if let Some(data) = data {
+            self.update_texture(
+                &texture,
+                &Rectangle {
+                    left: 0,
+                    top: 0,
+                    width,
+                    height,
+                },
+                data,
+            );
+        }
+
+        self.generate_mipmaps(&texture);
+
+        Ok(texture)
+    }
+
+    fn create_blend_state(&self, blend_mode: BlendMode) -> Result<BlendState> {
+        self.check_lost("Failed to create blend state.")?;
+        self.blend_states.get_or_create(&blend_mode, || {
+            Ok(BlendState {
+                generation: self.generation,
+                blend_mode,
+            })
+        })
+    }
+
+    fn create_depth_stencil_state(&self, description: &DepthStencilDescription) -> Result<DepthStencilState> {
+        self.check_lost("Failed to create depth stencil state.")?;
+        self.depth_stencil_states.get_or_create(description, || {
+            Ok(DepthStencilState {
+                generation: self.generation,
+                description: *description,
+            })
+        })
+    }
+
+    fn create_rasterizer_state(&self, description: &RasterizerDescription) -> Result<RasterizerState> {
+        self.check_lost("Failed to create rasterizer state.")?;
+        self.rasterizer_states.get_or_create(description, || {
+            Ok(RasterizerState {
+                generation: self.generation,
+                description: *description,
+            })
+        })
+    }
+
+    fn create_sampler_state(&self, description: &SamplerDescription) -> Result<SamplerState> {
+        self.check_lost("Failed to create sampler state.")?;
+        self.sampler_states.get_or_create(description, || {
+            Ok(SamplerState {
+                generation: self.generation,
+                description: *description,
+            })
+        })
+    }
+
+    fn create_vertex_shader(&self, byte_code: &[u8], attributes: &[VertexAttribute]) -> Result<VertexShader> {
+        self.check_lost("Failed to create vertex shader.")?;
+        Ok(VertexShader {
+            generation: self.generation,
+            program: self
+                .vertex_programs
+                .borrow()
+                .get(byte_code)
+                .expect("No software vertex program has been registered for the shader.")
+                .clone(),
+            attributes: attributes.into(),
+        })
+    }
+
+    fn create_pixel_shader(&self, byte_code: &[u8]) -> Result<PixelShader> {
+        self.check_lost("Failed to create pixel shader.")?;
+        Ok(PixelShader {
+            generation: self.generation,
+            program: self
+                .pixel_programs
+                .borrow()
+                .get(byte_code)
+                .expect("No software pixel program has been registered for the shader.")
+                .clone(),
+        })
+    }
+
+    fn create_vertex_buffer<T: BufferElement>(&self, usage: BufferUsage<T>) -> Result<VertexBuffer<T>> {
+        self.check_lost("Failed to create vertex buffer.")?;
+        Ok(VertexBuffer {
+            buffer: Buffer::new(usage, self.generation),
+            element: PhantomData,
+        })
+    }
+
+    fn create_index_buffer<T: IndexElement>(&self, usage: BufferUsage<T>) -> Result<IndexBuffer<T>> {
+        self.check_lost("Failed to create index buffer.")?;
+        Ok(IndexBuffer {
+            buffer: Buffer::new(usage, self.generation),
+            element: PhantomData,
+        })
+    }
+
+    fn create_constant_buffer<T: BufferElement>(&self, usage: BufferUsage<T>) -> Result<ConstantBuffer<T>> {
+        self.check_lost("Failed to create constant buffer.")?;
+        Ok(ConstantBuffer {
+            buffer: Buffer::new(usage, self.generation),
+            element: PhantomData,
+        })
+    }
+
+    fn update_vertex_buffer<T: BufferElement>(
+        &self,
+        buffer: &VertexBuffer<T>,
+        _mode: MapMode,
+        offset: usize,
+        data: &[T],
+    ) -> Result<()> {
+        self.check_lost("Failed to update buffer.")?;
+        self.check_generation(buffer.buffer.generation);
+        buffer.buffer.update(offset, data);
+        Ok(())
+    }
+
+    fn update_index_buffer<T: IndexElement>(
+        &self,
+        buffer: &IndexBuffer<T>,
+        _mode: MapMode,
+        offset: usize,
+        data: &[T],
+    ) -> Result<()> {
+        self.check_lost("Failed to update buffer.")?;
+        self.check_generation(buffer.buffer.generation);
+        buffer.buffer.update(offset, data);
+        Ok(())
+    }
+
+    fn update_constant_buffer<T: BufferElement>(&self, buffer: &ConstantBuffer<T>, data: &T) -> Result<()> {
+        self.check_lost("Failed to update buffer.")?;
+        self.check_generation(buffer.buffer.generation);
+        buffer.buffer.update(0, &[*data]);
+        Ok(())
+    }
+
+    fn update_texture(&self, texture: &Texture2D, region: &Rectangle<u32>, data: &[u8]) {
+        self.check_generation(texture.generation);
+        if region.left + region.width > texture.width || region.top + region.height > texture.height {
+            panic!("The region exceeds the size of the texture.");
+        }
+
+        if data.len() != (region.width * region.height * texture.format.bytes_per_pixel()) as usize {
+            panic!("The size of the texture data does not match the region and the texture format.");
+        }
+
+        let data = to_rgba(texture.format, data);
+        let mut pixels = texture.pixels.borrow_mut();
+        for (y, row) in data.chunks_exact((region.width * 4) as usize).enumerate() {
+            let offset = (((region.top + y as u32) * texture.width + region.left) * 4) as usize;
+            pixels[offset..offset + row.len()].copy_from_slice(row);
+        }
+    }
+
+    fn generate_mipmaps(&self, texture: &Texture2D) {
+        self.check_generation(texture.generation);
+        let mut mipmaps = texture.mipmaps.borrow_mut();
+        let (mut pixels, mut width, mut height) = (texture.pixels.borrow().clone(), texture.width, texture.height);
+
+        for mipmap in mipmaps.iter_mut() {
+            (pixels, width, height) = downsample(&pixels, width, height);
+            mipmap.clone_from(&pixels);
+        }
+    }
+
+    fn set_render_target(&self, render_target: &RenderTarget) {
+        self.check_generation(render_target.generation);
+        self.state.borrow_mut().render_target = Some((render_target.pixels.clone(), render_target.width, render_target.height));
+    }
+
+    fn set_vertex_shader(&self, vertex_shader: &VertexShader) {
+        self.check_generation(vertex_shader.generation);
+        self.state.borrow_mut().vertex_shader = Some((vertex_shader.program.clone(), vertex_shader.attributes.clone()));
+    }
+
+    fn set_pixel_shader(&self, pixel_shader: &PixelShader) {
+        self.check_generation(pixel_shader.generation);
+        self.state.borrow_mut().pixel_program = Some(pixel_shader.program.clone());
+    }
+
+    fn set_vertex_buffer<T: BufferElement>(&self, vertex_buffer: &VertexBuffer<T>) {
+        self.check_generation(vertex_buffer.buffer.generation);
+        self.state.borrow_mut().vertex_buffer = Some((vertex_buffer.buffer.bytes.clone(), mem::size_of::<T>()));
+    }
+
+    fn set_instance_buffer<T: BufferElement>(&self, instance_buffer: &VertexBuffer<T>) {
+        self.check_generation(instance_buffer.buffer.generation);
+        self.state.borrow_mut().instance_buffer = Some((instance_buffer.buffer.bytes.clone(), mem::size_of::<T>()));
+    }
+
+    fn set_index_buffer<T: IndexElement>(&self, index_buffer: &IndexBuffer<T>) {
+        self.check_generation(index_buffer.buffer.generation);
+        self.state.borrow_mut().index_buffer = Some((index_buffer.buffer.bytes.clone(), T::FORMAT));
+    }
+
+    fn set_vertex_constant_buffer<T: BufferElement>(&self, slot: u32, constant_buffer: &ConstantBuffer<T>) {
+        self.check_generation(constant_buffer.buffer.generation);
+        self.state.borrow_mut().vertex_constant_buffers[slot as usize] = Some(constant_buffer.buffer.bytes.clone());
+    }
+
+    fn set_pixel_constant_buffer<T: BufferElement>(&self, slot: u32, constant_buffer: &ConstantBuffer<T>) {
+        self.check_generation(constant_buffer.buffer.generation);
+        self.state.borrow_mut().pixel_constant_buffers[slot as usize] = Some(constant_buffer.buffer.bytes.clone());
+    }
+
+    fn set_pixel_texture(&self, slot: u32, texture: &Texture2D) {
+        self.check_generation(texture.generation);
+        self.state.borrow_mut().pixel_textures[slot as usize] = Some((texture.pixels.clone(), texture.width, texture.height));
+    }
+
+    fn set_pixel_sampler_state(&self, slot: u32, sampler_state: &SamplerState) {
+        self.check_generation(sampler_state.generation);
+        if self.bound_states.samplers[slot as usize].bind(&sampler_state.description) {
+            self.state.borrow_mut().pixel_samplers[slot as usize] = Some(sampler_state.description);
+        }
+    }
+
+    fn set_blend_state(&self, blend_state: &BlendState) {
+        self.check_generation(blend_state.generation);
+        if self.bound_states.blend.bind(&blend_state.blend_mode) {
+            self.state.borrow_mut().blend_mode = blend_state.blend_mode;
+        }
+    }
+
+    fn set_depth_stencil_state(&self, depth_stencil_state: &DepthStencilState) {
+        self.check_generation(depth_stencil_state.generation);
+        self.bound_states.depth_stencil.bind(&depth_stencil_state.description);
+    }
+
+    fn set_rasterizer_state(&self, rasterizer_state: &RasterizerState) {
+        self.check_generation(rasterizer_state.generation);
+        if self.bound_states.rasterizer.bind(&rasterizer_state.description) {
+            self.state.borrow_mut().rasterizer = rasterizer_state.description;
+        }
+    }
+
+    fn set_viewport(&self, viewport: &Rectangle<u32>) {
+        self.state.borrow_mut().viewport = *viewport;
+    }
+
+    fn set_scissor_rect(&self, rectangle: &Rectangle<u32>) {
+        self.state.borrow_mut().scissor_rect = *rectangle;
+    }
+
+    fn set_primitive_type(&self, primitive_type: PrimitiveType) {
+        self.state.borrow_mut().primitive_type = Some(primitive_type);
+    }
+
+    fn clear(&self, render_target: &RenderTarget, color: Color) {
+        self.check_generation(render_target.generation);
+        for pixel in render_target.pixels.borrow_mut().chunks_exact_mut(4) {
+            pixel.copy_from_slice(&[color.r, color.g, color.b, color.a]);
+        }
+    }
+
+    fn draw(&self, vertex_count: u32, first_vertex: u32) {
+        self.draw_vertices(first_vertex..first_vertex + vertex_count, 0..1)
+    }
+
+    fn draw_indexed(&self, index_count: u32, first_index: u32, base_vertex: i32) {
+        let indices = self.indices(index_count, first_index);
+        self.draw_vertices(indices.iter().map(|index| index.wrapping_add_signed(base_vertex)), 0..1)
+    }
+
+    fn draw_instanced(&self, vertex_count: u32, instance_count: u32, first_vertex: u32, first_instance: u32) {
+        self.draw_vertices(
+            first_vertex..first_vertex + vertex_count,
+            first_instance..first_instance + instance_count,
+        )
+    }
+
+    fn draw_indexed_instanced(
+        &self,
+        index_count: u32,
+        instance_count: u32,
+        first_index: u32,
+        base_vertex: i32,
+        first_instance: u32,
+    ) {
+        let indices = self.indices(index_count, first_index);
+        self.draw_vertices(
+            indices.iter().map(|index| index.wrapping_add_signed(base_vertex)),
+            first_instance..first_instance + instance_count,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        assets::image,
+        platform::{
+            error::Error,
+            graphics::{
+                shader::{AttributeFormat, Semantic},
+                state::{AddressMode, CullMode, TextureFilter},
+            },
+        },
+    };
+    use std::{fs, path::Path};
+
+    const VERTEX_SHADER: &[u8] = b"positions";
+    const PIXEL_SHADER: &[u8] = b"white";
+    const WHITE: Color = Color {
+        r: 255,
+        g: 255,
+        b: 255,
+        a: 255,
+    };
+
+    fn device(positions: Vec<[f32; 2]>, primitive_type: PrimitiveType) -> SoftwareGraphicsDevice {
+        let device = SoftwareGraphicsDevice::new(8, 8);
+        device.register_vertex_program(VERTEX_SHADER, move |input| VertexOutput {
+            position: {
+                let [x, y] = positions[input.vertex_id as usize];
+                [x, y, 0., 1.]
+            },
+            varyings: vec![],
+        });
+        device.register_pixel_program(PIXEL_SHADER, |_| [1., 1., 1., 1.]);
+
+        let vertex_shader = device.create_vertex_shader(VERTEX_SHADER, &[]).unwrap();
+        let pixel_shader = device.create_pixel_shader(PIXEL_SHADER).unwrap();
+        device.set_render_target(device.back_buffer());
+        device.set_vertex_shader(&vertex_shader);
+        device.set_pixel_shader(&pixel_shader);
+        device.set_primitive_type(primitive_type);
+        device.set_viewport(&Rectangle {
+            left: 0,
+            top: 0,
+            width: 8,
+            height: 8,
+        });
+        device
+    }
+
+    /// Compares the frame with the reference image `tests/frames/<name>.png`. If they differ, the frame is written to
+    /// `target/frames/<name>.png`, so that it can be inspected and copied over the reference image if it is correct.
+    fn assert_matches_reference(frame: &Frame, name: &str) {
+        let directory = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let reference = fs::read(directory.join(format!("tests/frames/{name}.png")))
+            .ok()
+            .map(|png| image::decode(&png).unwrap());
+        let matches = reference.is_some_and(|image| {
+            let reference = Frame {
+                width: image.width,
+                height: image.height,
+                pixels: image.pixels,
+            };
+            reference.count_differences(frame, 1) == 0
+        });
+
+        if !matches {
+            let actual = directory.join(format!("target/frames/{name}.png"));
+            fs::create_dir_all(actual.parent().unwrap()).unwrap();
+            fs::write(&actual, frame.to_png()).unwrap();
+            panic!(
+                "The frame does not match 'tests/frames/{name}.png'; it has been written to '{}'.",
+                actual.display()
+            );
+        }
+    }
+
+    fn covered_pixels(frame: &Frame) -> Vec<(u32, u32)> {
+        (0..frame.height)
+            .flat_map(|y| (0..frame.width).map(move |x| (x, y)))
+            .filter(|&(x, y)| frame.pixel(x, y) == WHITE)
+            .collect()
+    }
+
+    #[test]
+    fn clear_fills_render_target() {
+        let device = SoftwareGraphicsDevice::new(2, 2);
+        device.clear(device.back_buffer(), Color::new(1, 2, 3, 4));
+        assert_eq!(device.back_buffer().capture().pixels, [1, 2, 3, 4].repeat(4));
+    }
+
+    #[test]
+    fn presenter_errors_are_returned() {
+        let mut device = SoftwareGraphicsDevice::new(2, 2);
+        device.present().unwrap();
+
+        device.set_presenter(|pixels, width, height| {
+            assert_eq!((pixels.len(), width, height), (16, 2, 2));
+            Err(Error::other("Failed to present frame.", "The window has been closed."))
+        });
+        assert_eq!(
+            device.present().unwrap_err().to_string(),
+            "Failed to present frame. The window has been closed."
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "The resource has been created by the device before it has been lost.")]
+    fn resources_of_lost_devices_cannot_be_used() {
+        let mut device = SoftwareGraphicsDevice::new(2, 2);
+        let buffer = device.create_vertex_buffer::<f32>(BufferUsage::Dynamic(1)).unwrap();
+
+        device.lose("The graphics driver has crashed.");
+        assert!(device.create_pixel_shader(PIXEL_SHADER).is_err_and(|e| e.is_device_lost()));
+        assert!(device.update_vertex_buffer(&buffer, MapMode::Discard, 0, &[1.]).is_err());
+        assert!(device.resize_back_buffer(4, 4).is_err());
+
+        device.recreate().unwrap();
+        device.present().unwrap();
+        device.set_vertex_buffer(&buffer);
+    }
+
+    #[test]
+    fn state_objects_are_recreated_along_with_the_device() {
+        let mut device = SoftwareGraphicsDevice::new(2, 2);
+        let sampler = SamplerDescription {
+            filter: TextureFilter::Nearest,
+            address_mode: AddressMode::Clamp,
+        };
+        device.create_blend_state(BlendMode::Alpha).unwrap();
+        device.create_sampler_state(&sampler).unwrap();
+
+        // The cached state objects of the lost device must not be returned.
+        device.lose("The graphics driver has crashed.");
+        device.recreate().unwrap();
+        device.set_blend_state(&device.create_blend_state(BlendMode::Alpha).unwrap());
+        device.set_pixel_sampler_state(0, &device.create_sampler_state(&sampler).unwrap());
+        device.set_rasterizer_state(&device.create_rasterizer_state(&RasterizerDescription::default()).unwrap());
+        device.set_depth_stencil_state(
+            &device
+                .create_depth_stencil_state(&DepthStencilDescription::default())
+                .unwrap(),
+        );
+    }
+
+    #[test]
+    fn triangles_follow_top_left_rule() {
+        // Two clockwise triangles covering the top left 4x4 pixels; the pixel centers on their shared diagonal edge
+        // must only be covered by the triangle for which the edge is a left edge.
+        let device = device(
+            vec![[-1., 1.], [0., 1.], [-1., 0.], [0., 1.], [0., 0.], [-1., 0.]],
+            PrimitiveType::Triangles,
+        );
+        device.draw(3, 0);
+        let first = covered_pixels(&device.back_buffer().capture());
+
+        device.clear(device.back_buffer(), Color::new(0, 0, 0, 255));
+        device.draw(3, 3);
+        let second = covered_pixels(&device.back_buffer().capture());
+
+        assert!(first.iter().all(|&(x, y)| x + y < 3));
+        assert!(second.iter().all(|&(x, y)| x + y >= 3 && x < 4 && y < 4));
+        assert_eq!(first.len() + second.len(), 16);
+    }
+
+    #[test]
+    fn counter_clockwise_triangles_are_culled() {
+        let device = device(vec![[-1., 1.], [-1., -1.], [1., 1.]], PrimitiveType::Triangles);
+        device.draw(3, 0);
+        assert!(covered_pixels(&device.back_buffer().capture()).is_empty());
+    }
+
+    #[test]
+    fn points_cover_single_pixels() {
+        let device = device(vec![[-0.75 + 0.125, 0.75 - 0.125], [0.125, -0.125]], PrimitiveType::Points);
+        device.draw(2, 0);
+        assert_eq!(covered_pixels(&device.back_buffer().capture()), [(1, 1), (4, 4)]);
+    }
+
+    #[test]
+    fn viewport_limits_rasterization() {
+        let device = device(vec![[-1., 1.], [3., 1.], [-1., -3.]], PrimitiveType::Triangles);
+        device.set_viewport(&Rectangle {
+            left: 2,
+            top: 2,
+            width: 2,
+            height: 2,
+        });
+        device.draw(3, 0);
+        assert_eq!(
+            covered_pixels(&device.back_buffer().capture()),
+            [(2, 2), (3, 2), (2, 3), (3, 3)]
+        );
+    }
+
+    #[test]
+    fn indexed_draws_fetch_vertices_from_buffers() {
+        let device = SoftwareGraphicsDevice::new(8, 8);
+        device.register_vertex_program(b"offset", |input| {
+            let [x, y, _, _] = input.attributes[0];
+            let offset = input.constants::<[f32; 4]>(0);
+            VertexOutput {
+                position: [x + offset[0], y + offset[1], 0., 1.],
+                varyings: input.attributes[1].to_vec(),
+            }
+        });
+        device.register_pixel_program(PIXEL_SHADER, |input| input.varyings.try_into().unwrap());
+
+        let vertex_shader = device
+            .create_vertex_shader(
+                b"offset",
+                &[
+                    VertexAttribute {
+                        semantic: Semantic::Position,
+                        semantic_index: 0,
+                        format: AttributeFormat::Float2,
+                        offset: 0,
+                        per_instance: false,
+                    },
+                    VertexAttribute {
+                        semantic: Semantic::Color,
+                        semantic_index: 0,
+                        format: AttributeFormat::UByte4Norm,
+                        offset: 8,
+                        per_instance: false,
+                    },
+                ],
+            )
+            .unwrap();
+        let pixel_shader = device.create_pixel_shader(PIXEL_SHADER).unwrap();
+
+        // The first vertex is never referenced; the base vertex skips it.
+        let white = f32::from_ne_bytes([255; 4]);
+        let vertex_buffer = device.create_vertex_buffer::<[f32; 3]>(BufferUsage::Dynamic(5)).unwrap();
+        device
+            .update_vertex_buffer(
+                &vertex_buffer,
+                MapMode::Discard,
+                1,
+                &[[-1., 1., white], [0., 1., white], [-1., 0., white], [0., 0., white]],
+            )
+            .unwrap();
+        let index_buffer = device
+            .create_index_buffer::<u16>(BufferUsage::Static(&[0, 1, 2, 2, 1, 3]))
+            .unwrap();
+        let constant_buffer = device
+            .create_constant_buffer(BufferUsage::Static(&[1., -1., 0., 0.]))
+            .unwrap();
+
+        device.set_render_target(device.back_buffer());
+        device.set_vertex_shader(&vertex_shader);
+        device.set_pixel_shader(&pixel_shader);
+        device.set_vertex_buffer(&vertex_buffer);
+        device.set_index_buffer(&index_buffer);
+        device.set_vertex_constant_buffer(0, &constant_buffer);
+        device.set_primitive_type(PrimitiveType::Triangles);
+        device.set_viewport(&Rectangle {
+            left: 0,
+            top: 0,
+            width: 8,
+            height: 8,
+        });
+        device.draw_indexed(6, 0, 1);
+
+        let covered = covered_pixels(&device.back_buffer().capture());
+        assert_eq!(covered.len(), 16);
+        assert!(covered.iter().all(|&(x, y)| (4..8).contains(&x) && (4..8).contains(&y)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Static buffers cannot be updated.")]
+    fn static_buffers_cannot_be_updated() {
+        let device = SoftwareGraphicsDevice::new(1, 1);
+        let vertex_buffer = device.create_vertex_buffer(BufferUsage::Static(&[0u32; 4])).unwrap();
+        device
+            .update_vertex_buffer(&vertex_buffer, MapMode::Discard, 0, &[1])
+            .unwrap();
+    }
+
+    #[test]
+    fn textures_are_converted_updated_and_mipmapped() {
+        let device = SoftwareGraphicsDevice::new(1, 1);
+        let description = |format| TextureDescription {
+            width: 2,
+            height: 2,
+            format,
+            mipmaps: true,
+            render_target: false,
+        };
+
+        let texture = device
+            .create_texture(&description(TextureFormat::Bgra8), Some(&[10, 20, 30, 40].repeat(4)))
+            .unwrap();
+        assert_eq!(texture.capture(0).pixels, [30, 20, 10, 40].repeat(4));
+
+        let texture = device
+            .create_texture(&description(TextureFormat::R8), Some(&[0, 100, 200, 0]))
+            .unwrap();
+        assert_eq!(texture.capture(1).pixel(0, 0), Color::new(75, 0, 0, 255));
+
+        device.update_texture(
+            &texture,
+            &Rectangle {
+                left: 1,
+                top: 1,
+                width: 1,
+                height: 1,
+            },
+            &[255],
+        );
+        assert_eq!(texture.capture(0).pixel(1, 1), Color::new(255, 0, 0, 255));
+        assert_eq!(texture.capture(1).pixel(0, 0), Color::new(75, 0, 0, 255));
+
+        device.generate_mipmaps(&texture);
+        assert_eq!(texture.capture(1).pixel(0, 0), Color::new(139, 0, 0, 255));
+    }
+
+    #[test]
+    fn invalid_textures_are_rejected() {
+        let device = SoftwareGraphicsDevice::new(1, 1);
+        let error = |width, height, mipmaps, data: Option<&[u8]>| {
+            let description = TextureDescription {
+                width,
+                height,
+                format: TextureFormat::Rgba8,
+                mipmaps,
+                render_target: false,
+            };
+            device.create_texture(&description, data).err().unwrap().to_string()
+        };
+
+        assert_eq!(
+            error(0, 0, true, None),
+            "Failed to create texture. The texture size 0x0 is empty."
+        );
+        assert_eq!(
+            error(65536, 65536, false, None),
+            "Failed to create texture. The texture size 65536x65536 exceeds the maximum of 16384x16384."
+        );
+        assert_eq!(
+            error(2, 1, false, Some(&[0; 4])),
+            "Failed to create texture. Expected 8 bytes of texture data, but got 4 bytes."
+        );
+    }
+
+    #[test]
+    fn textures_are_rendered_to_and_sampled() {
+        let device = device(vec![[-1., 1.], [3., 1.], [-1., -3.]], PrimitiveType::Triangles);
+        let texture = device
+            .create_texture(
+                &TextureDescription {
+                    width: 2,
+                    height: 1,
+                    format: TextureFormat::Rgba8,
+                    mipmaps: false,
+                    render_target: true,
+                },
+                None,
+            )
+            .unwrap();
+
+        let render_target = device.create_render_target(&texture).unwrap();
+        device.clear(&render_target, Color::new(255, 0, 0, 255));
+        device.update_texture(
+            &texture,
+            &Rectangle {
+                left: 1,
+                top: 0,
+                width: 1,
+                height: 1,
+            },
+            &[0, 0, 255, 255],
+        );
+
+        device.register_pixel_program(b"sample", |input| input.sample(0, 0, [0., 0.5]));
+        device.set_pixel_shader(&device.create_pixel_shader(b"sample").unwrap());
+        device.set_pixel_texture(0, &texture);
+
+        let mut colors = vec![];
+        for (filter, address_mode) in [
+            (TextureFilter::Nearest, AddressMode::Clamp),
+            (TextureFilter::Bilinear, AddressMode::Clamp),
+            (TextureFilter::Bilinear, AddressMode::Wrap),
+        ] {
+            device.set_pixel_sampler_state(
+                0,
+                &device
+                    .create_sampler_state(&SamplerDescription { filter, address_mode })
+                    .unwrap(),
+            );
+            device.draw(3, 0);
+            colors.push(device.back_buffer().capture().pixel(0, 0));
+        }
+
+        assert_eq!(
+            colors,
+            [
+                Color::new(255, 0, 0, 255),
+                Color::new(255, 0, 0, 255),
+                Color::new(128, 0, 128, 255)
+            ]
+        );
+    }
+
+    #[test]
+    fn blend_states_combine_colors_with_the_render_target() {
+        let device = device(vec![[-1., 1.], [3., 1.], [-1., -3.]], PrimitiveType::Triangles);
+        device.register_pixel_program(b"red", |_| [1., 0., 0., 0.5]);
+        device.set_pixel_shader(&device.create_pixel_shader(b"red").unwrap());
+
+        let mut colors = vec![];
+        for blend_mode in [
+            BlendMode::Opaque,
+            BlendMode::Alpha,
+            BlendMode::Additive,
+            BlendMode::Premultiplied,
+        ] {
+            device.clear(device.back_buffer(), Color::new(0, 0, 255, 255));
+            device.set_blend_state(&device.create_blend_state(blend_mode).unwrap());
+            device.draw(3, 0);
+            colors.push(device.back_buffer().capture().pixel(0, 0));
+        }
+
+        assert_eq!(
+            colors,
+            [
+                Color::new(255, 0, 0, 128),
+                Color::new(128, 0, 128, 255),
+                Color::new(128, 0, 255, 255),
+                Color::new(255, 0, 128, 255)
+            ]
+        );
+    }
+
+    #[test]
+    fn rasterizer_states_control_culling() {
+        let counter_clockwise = device(vec![[-1., 1.], [-1., -1.], [1., 1.]], PrimitiveType::Triangles);
+        let clockwise = device(vec![[-1., 1.], [1., 1.], [-1., -1.]], PrimitiveType::Triangles);
+
+        let mut covered = vec![];
+        for cull_mode in [CullMode::None, CullMode::Front] {
+            for device in [&counter_clockwise, &clockwise] {
+                device.clear(device.back_buffer(), Color::new(0, 0, 0, 255));
+                device.set_rasterizer_state(
+                    &device
+                        .create_rasterizer_state(&RasterizerDescription {
+                            cull_mode,
+                            ..Default::default()
+                        })
+                        .unwrap(),
+                );
+                device.draw(3, 0);
+                covered.push(covered_pixels(&device.back_buffer().capture()).len());
+            }
+        }
+
+        assert_eq!(covered, [28, 28, 28, 0]);
+    }
+
+    #[test]
+    fn scissor_rect_only_applies_when_enabled() {
+        let device = device(vec![[-1., 1.], [3., 1.], [-1., -3.]], PrimitiveType::Triangles);
+        device.set_scissor_rect(&Rectangle {
+            left: 2,
+            top: 3,
+            width: 2,
+            height: 1,
+        });
+        device.draw(3, 0);
+        assert_eq!(covered_pixels(&device.back_buffer().capture()).len(), 64);
+
+        device.clear(device.back_buffer(), Color::new(0, 0, 0, 255));
+        device.set_rasterizer_state(
+            &device
+                .create_rasterizer_state(&RasterizerDescription {
+                    scissor: true,
+                    ..Default::default()
+                })
+                .unwrap(),
+        );
+        device.draw(3, 0);
+        assert_eq!(covered_pixels(&device.back_buffer().capture()), [(2, 3), (3, 3)]);
+    }
+
+    #[test]
+    fn wireframes_cover_the_triangle_edges() {
+        let device = device(vec![[-1., 1.], [1., 1.], [-1., -1.]], PrimitiveType::Triangles);
+        device.set_rasterizer_state(
+            &device
+                .create_rasterizer_state(&RasterizerDescription {
+                    wireframe: true,
+                    ..Default::default()
+                })
+                .unwrap(),
+        );
+        device.draw(3, 0);
+
+        let covered = covered_pixels(&device.back_buffer().capture());
+        assert_eq!(covered.len(), 21);
+        assert!(covered.iter().all(|&(x, y)| x == 0 || y == 0 || x + y == 7));
+    }
+
+    #[test]
+    fn lines_omit_their_last_pixel() {
+        let lines = device(
+            vec![[-1., 0.875], [1., 0.875], [-0.875, 1.], [-0.875, -0.5]],
+            PrimitiveType::Lines,
+        );
+        lines.draw(4, 0);
+        let covered = covered_pixels(&lines.back_buffer().capture());
+        assert_eq!(covered.len(), 13);
+        assert!(covered.iter().all(|&(x, y)| y == 0 || (x == 0 && y < 6)));
+
+        // The second line of a strip starts where the first one ends.
+        let strip = device(vec![[-0.875, 1.], [-0.875, -1.], [1., -0.875]], PrimitiveType::LineStrip);
+        strip.draw(3, 0);
+        let covered = covered_pixels(&strip.back_buffer().capture());
+        assert_eq!(covered.len(), 15);
+        assert!(covered.iter().all(|&(x, y)| x == 0 || y == 7));
+    }
+
+    #[test]
+    fn triangle_strips_keep_their_winding() {
+        let device = device(vec![[-1., 1.], [1., 1.], [-1., -1.], [1., -1.]], PrimitiveType::TriangleStrip);
+        device.draw(4, 0);
+        assert_eq!(covered_pixels(&device.back_buffer().capture()).len(), 64);
+    }
+
+    #[test]
+    fn instanced_draws_read_per_instance_attributes() {
+        let device = SoftwareGraphicsDevice::new(8, 8);
+        device.register_vertex_program(b"instanced", |input| {
+            let ([x, y, _, _], [offset_x, offset_y, _, _]) = (input.attributes[0], input.attributes[1]);
+            VertexOutput {
+                position: [x + offset_x, y + offset_y, 0., 1.],
+                varyings: vec![input.instance_id as f32],
+            }
+        });
+        device.register_pixel_program(PIXEL_SHADER, |input| [1., 1., 1., input.varyings[0] / 2.]);
+
+        let attribute = |offset, per_instance| VertexAttribute {
+            semantic: Semantic::Position,
+            semantic_index: per_instance as u32,
+            format: AttributeFormat::Float2,
+            offset,
+            per_instance,
+        };
+        let vertex_shader = device
+            .create_vertex_shader(b"instanced", &[attribute(0, false), attribute(8, true)])
+            .unwrap();
+        let vertex_buffer = device
+            .create_vertex_buffer(BufferUsage::Static(&[[-0.875f32, 0.875]]))
+            .unwrap();
+        let instance_buffer = device
+            .create_vertex_buffer(BufferUsage::Static(&[
+                [0f32, 0., 0.5, 0.],
+                [0., 0., 0.25, 0.],
+                [0., 0., 0., -0.25],
+            ]))
+            .unwrap();
+        let index_buffer = device.create_index_buffer::<u16>(BufferUsage::Static(&[0])).unwrap();
+
+        device.set_render_target(device.back_buffer());
+        device.set_vertex_shader(&vertex_shader);
+        device.set_pixel_shader(&device.create_pixel_shader(PIXEL_SHADER).unwrap());
+        device.set_vertex_buffer(&vertex_buffer);
+        device.set_instance_buffer(&instance_buffer);
+        device.set_index_buffer(&index_buffer);
+        device.set_primitive_type(PrimitiveType::Points);
+        device.set_viewport(&Rectangle {
+            left: 0,
+            top: 0,
+            width: 8,
+            height: 8,
+        });
+
+        device.draw_instanced(1, 2, 0, 1);
+        let frame = device.back_buffer().capture();
+        assert_eq!(
+            (frame.pixel(1, 0), frame.pixel(0, 1)),
+            (Color::new(255, 255, 255, 128), WHITE)
+        );
+        assert_eq!(frame.pixel(2, 0), Color::new(0, 0, 0, 0));
+
+        device.clear(device.back_buffer(), Color::new(0, 0, 0, 0));
+        device.draw_indexed_instanced(1, 3, 0, 0, 0);
+        assert_eq!(covered_pixels(&device.back_buffer().capture()), [(0, 1)]);
+        assert_eq!(device.back_buffer().capture().pixel(2, 0), Color::new(255, 255, 255, 0));
+    }
+
+    #[test]
+    fn sprites_match_their_reference_frame() {
+        // A quad covering the center of the render target that is textured with a bilinearly filtered 2x2 texture
+        // and blended over the background.
+        let device = SoftwareGraphicsDevice::new(16, 16);
+        let corners = [[-0.5, 0.5], [0.5, 0.5], [-0.5, -0.5], [0.5, -0.5]];
+        device.register_vertex_program(b"sprite", move |input| {
+            let corner = [0, 1, 2, 1, 3, 2][input.vertex_id as usize];
+            let [x, y] = corners[corner];
+            VertexOutput {
+                position: [x, y, 0., 1.],
+                varyings: vec![x + 0.5, 0.5 - y],
+            }
+        });
+        device.register_pixel_program(b"sprite", |input| input.sample(0, 0, [input.varyings[0], input.varyings[1]]));
+
+        let texture = device
+            .create_texture(
+                &TextureDescription {
+                    width: 2,
+                    height: 2,
+                    format: TextureFormat::Rgba8,
+                    mipmaps: false,
+                    render_target: false,
+                },
+                Some(&[255, 0, 0, 255, 0, 255, 0, 255, 0, 0, 255, 128, 255, 255, 255, 0]),
+            )
+            .unwrap();
+
+        device.set_render_target(device.back_buffer());
+        device.set_viewport(&Rectangle {
+            left: 0,
+            top: 0,
+            width: 16,
+            height: 16,
+        });
+        device.set_vertex_shader(&device.create_vertex_shader(b"sprite", &[]).unwrap());
+        device.set_pixel_shader(&device.create_pixel_shader(b"sprite").unwrap());
+        device.set_pixel_texture(0, &texture);
+        device.set_pixel_sampler_state(
+            0,
+            &device
+                .create_sampler_state(&SamplerDescription {
+                    filter: TextureFilter::Bilinear,
+                    address_mode: AddressMode::Clamp,
+                })
+                .unwrap(),
+        );
+        device.set_blend_state(&device.create_blend_state(BlendMode::Alpha).unwrap());
+        device.set_primitive_type(PrimitiveType::Triangles);
+
+        device.clear(device.back_buffer(), Color::new(32, 32, 64, 255));
+        device.draw(6, 0);
+        assert_matches_reference(&device.back_buffer().capture(), "sprite");
+    }
+
+    #[test]
+    fn frames_are_encoded_as_png() {
+        let frame = Frame {
+            width: 1,
+            height: 1,
+            pixels: vec![255, 0, 0, 255],
+        };
+        let png = frame.to_png();
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        assert_eq!(&png[png.len() - 12..], b"\0\0\0\0IEND\xae\x42\x60\x82");
+    }
+}
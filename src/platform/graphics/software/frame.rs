@@ -0,0 +1,76 @@
+use crate::{
+    assets::checksum::{adler32, crc32},
+    primitives::Color,
+};
+
+/// A copy of the pixels of a render target, stored row by row as RGBA8.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl Frame {
+    pub fn pixel(&self, x: u32, y: u32) -> Color {
+        let offset = ((y * self.width + x) * 4) as usize;
+        let pixel = &self.pixels[offset..offset + 4];
+        Color::new(pixel[0], pixel[1], pixel[2], pixel[3])
+    }
+
+    /// Counts the pixels having a color component that differs by more than `tolerance` from the other frame's.
+    pub fn count_differences(&self, other: &Frame, tolerance: u8) -> usize {
+        if self.width != other.width || self.height != other.height {
+            return (self.width.max(other.width) * self.height.max(other.height)) as usize;
+        }
+
+        self.pixels
+            .chunks_exact(4)
+            .zip(other.pixels.chunks_exact(4))
+            .filter(|(a, b)| a.iter().zip(b.iter()).any(|(a, b)| a.abs_diff(*b) > tolerance))
+            .count()
+    }
+
+    /// Encodes the frame as an uncompressed PNG image, which is good enough for inspecting test results and golden
+    /// images.
+    pub fn to_png(&self) -> Vec<u8> {
+        let row_size = (self.width * 4) as usize;
+        let mut scanlines = Vec::with_capacity((row_size + 1) * self.height as usize);
+        for row in self.pixels.chunks_exact(row_size.max(1)) {
+            scanlines.push(0); // filter type 'None'
+            scanlines.extend_from_slice(row);
+        }
+
+        // A zlib stream consisting of deflate blocks that store the data without any compression.
+        let mut image_data = vec![0x78, 0x01];
+        let blocks = scanlines.chunks(u16::MAX as usize).collect::<Vec<_>>();
+        for (i, block) in blocks.iter().enumerate() {
+            image_data.push((i == blocks.len() - 1) as u8);
+            image_data.extend_from_slice(&(block.len() as u16).to_le_bytes());
+            image_data.extend_from_slice(&(!(block.len() as u16)).to_le_bytes());
+            image_data.extend_from_slice(block);
+        }
+        if blocks.is_empty() {
+            image_data.extend_from_slice(&[1, 0, 0, 0xFF, 0xFF]);
+        }
+        image_data.extend_from_slice(&adler32(&scanlines).to_be_bytes());
+
+        let mut header = Vec::with_capacity(13);
+        header.extend_from_slice(&self.width.to_be_bytes());
+        header.extend_from_slice(&self.height.to_be_bytes());
+        header.extend_from_slice(&[8, 6, 0, 0, 0]); // 8 bits per channel, RGBA, no interlacing
+
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        write_chunk(&mut png, b"IHDR", &header);
+        write_chunk(&mut png, b"IDAT", &image_data);
+        write_chunk(&mut png, b"IEND", &[]);
+        png
+    }
+}
+
+fn write_chunk(png: &mut Vec<u8>, chunk_type: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    png.extend_from_slice(chunk_type);
+    png.extend_from_slice(data);
+    png.extend_from_slice(&crc32(chunk_type.iter().chain(data)).to_be_bytes());
+}
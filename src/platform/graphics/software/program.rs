@@ -0,0 +1,92 @@
+use super::{
+    texture::{sample, SamplerSlots, TextureSlots},
+    Bytes,
+};
+use crate::platform::graphics::{
+    buffer::BufferElement,
+    shader::{AttributeFormat, VertexAttribute},
+};
+use std::{mem, ptr};
+
+/// The number of constant buffer slots of each shader stage, matching Direct3D 11.
+pub(super) const CONSTANT_BUFFER_SLOTS: usize = 14;
+
+pub(super) type ConstantBuffers = [Option<Bytes>; CONSTANT_BUFFER_SLOTS];
+
+pub struct VertexInput<'a> {
+    pub vertex_id: u32,
+    /// The index of the instance of an instanced draw, or 0 for other draws.
+    pub instance_id: u32,
+    /// The values of the vertex shader's attributes in the order in which they have been declared. Like on the GPU,
+    /// components missing from an attribute's format are filled in with (0, 0, 0, 1).
+    pub attributes: Vec<[f32; 4]>,
+    pub(super) constant_buffers: &'a ConstantBuffers,
+}
+
+pub struct PixelInput<'a> {
+    /// The varyings written by the vertex program, interpolated across the primitive.
+    pub varyings: &'a [f32],
+    pub(super) constant_buffers: &'a ConstantBuffers,
+    pub(super) textures: &'a TextureSlots,
+    pub(super) samplers: &'a SamplerSlots,
+}
+
+impl VertexInput<'_> {
+    /// Reads the contents of the constant buffer bound to the vertex shader stage at `slot`.
+    pub fn constants<T: BufferElement>(&self, slot: u32) -> T {
+        read_constants(self.constant_buffers, slot)
+    }
+}
+
+impl PixelInput<'_> {
+    /// Reads the contents of the constant buffer bound to the pixel shader stage at `slot`.
+    pub fn constants<T: BufferElement>(&self, slot: u32) -> T {
+        read_constants(self.constant_buffers, slot)
+    }
+
+    /// Samples the texture bound at `texture_slot` with the sampler state bound at `sampler_slot`, like HLSL's
+    /// `Texture2D.Sample`.
+    pub fn sample(&self, texture_slot: u32, sampler_slot: u32, tex_coords: [f32; 2]) -> [f32; 4] {
+        let (pixels, width, height) = self.textures[texture_slot as usize]
+            .as_ref()
+            .unwrap_or_else(|| panic!("No texture has been set for slot {texture_slot}."));
+        let sampler = self.samplers[sampler_slot as usize]
+            .as_ref()
+            .unwrap_or_else(|| panic!("No sampler state has been set for slot {sampler_slot}."));
+
+        sample(&pixels.borrow(), *width, *height, sampler, tex_coords)
+    }
+}
+
+fn read_constants<T: BufferElement>(constant_buffers: &ConstantBuffers, slot: u32) -> T {
+    let buffer = constant_buffers[slot as usize]
+        .as_ref()
+        .unwrap_or_else(|| panic!("No constant buffer has been set for slot {slot}."));
+    let bytes = buffer.borrow();
+
+    if bytes.len() < mem::size_of::<T>() {
+        panic!("The constant buffer in slot {slot} is smaller than the requested constants.");
+    }
+
+    unsafe { ptr::read_unaligned(bytes.as_ptr() as *const T) }
+}
+
+/// Reads the attributes from the bytes of the vertex or, for per-instance attributes, of the instance.
+pub(super) fn fetch_attributes(vertex: &[u8], instance: &[u8], attributes: &[VertexAttribute]) -> Vec<[f32; 4]> {
+    attributes
+        .iter()
+        .map(|attribute| {
+            let element = if attribute.per_instance { instance } else { vertex };
+            let bytes = &element[attribute.offset as usize..];
+            let float = |i: usize| f32::from_ne_bytes([0, 1, 2, 3].map(|j| bytes[i * 4 + j]));
+
+            match attribute.format {
+                AttributeFormat::Float => [float(0), 0., 0., 1.],
+                AttributeFormat::Float2 => [float(0), float(1), 0., 1.],
+                AttributeFormat::Float3 => [float(0), float(1), float(2), 1.],
+                AttributeFormat::Float4 => [float(0), float(1), float(2), float(3)],
+                AttributeFormat::UByte4Norm => [0, 1, 2, 3].map(|i| bytes[i] as f32 / 255.),
+            }
+        })
+        .collect()
+}
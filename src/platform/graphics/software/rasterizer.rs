@@ -0,0 +1,352 @@
+use crate::{
+    platform::graphics::state::{BlendMode, CullMode, PrimitiveType},
+    primitives::Rectangle,
+};
+
+// Vertex positions are snapped to 1/256th of a pixel, matching the 8 bits of subpixel precision of Direct3D 11.
+const SUBPIXEL_PRECISION: f32 = 256.;
+
+#[derive(Clone)]
+pub struct VertexOutput {
+    /// The clip space position, i.e. what an HLSL vertex shader writes to `SV_Position`.
+    pub position: [f32; 4],
+    /// Values that are interpolated across the primitive and passed to the pixel program.
+    pub varyings: Vec<f32>,
+}
+
+pub(super) struct Target<'a> {
+    pub pixels: &'a mut [u8],
+    pub width: u32,
+    pub height: u32,
+    pub viewport: Rectangle<u32>,
+    pub scissor: Option<Rectangle<u32>>,
+    pub cull_mode: CullMode,
+    pub wireframe: bool,
+    pub blend_mode: BlendMode,
+}
+
+struct ScreenVertex {
+    x: f32,
+    y: f32,
+    inv_w: f32,
+    // Premultiplied with `inv_w` for perspective-correct interpolation.
+    varyings: Vec<f32>,
+}
+
+impl Target<'_> {
+    pub fn draw(&mut self, primitive_type: PrimitiveType, vertices: &[VertexOutput], pixel_program: &dyn Fn(&[f32]) -> [f32; 4]) {
+        match primitive_type {
+            PrimitiveType::Points => self.draw_points(vertices, pixel_program),
+            PrimitiveType::Lines => {
+                for line in vertices.chunks_exact(2) {
+                    self.draw_line(&line[0], &line[1], pixel_program);
+                }
+            }
+            PrimitiveType::LineStrip => {
+                for line in vertices.windows(2) {
+                    self.draw_line(&line[0], &line[1], pixel_program);
+                }
+            }
+            PrimitiveType::Triangles => {
+                for triangle in vertices.chunks_exact(3) {
+                    self.draw_triangle([&triangle[0], &triangle[1], &triangle[2]], pixel_program);
+                }
+            }
+            PrimitiveType::TriangleStrip => {
+                for (i, triangle) in vertices.windows(3).enumerate() {
+                    match i % 2 {
+                        0 => self.draw_triangle([&triangle[0], &triangle[1], &triangle[2]], pixel_program),
+                        _ => self.draw_triangle([&triangle[1], &triangle[0], &triangle[2]], pixel_program),
+                    }
+                }
+            }
+        }
+    }
+
+    fn draw_points(&mut self, vertices: &[VertexOutput], pixel_program: &dyn Fn(&[f32]) -> [f32; 4]) {
+        for vertex in vertices {
+            let [x, y, z, w] = vertex.position;
+            if w <= 0. || z < 0. || z > w {
+                continue;
+            }
+
+            // A point covers the pixel whose center lies within the pixel-sized square around the point, with the
+            // top-left rule deciding about centers on the square's edges.
+            let (x, y) = self.to_screen(x / w, y / w);
+            let (x, y) = (x.ceil() - 1., y.ceil() - 1.);
+
+            if self.contains(x, y) {
+                self.write(x as u32, y as u32, pixel_program(&vertex.varyings));
+            }
+        }
+    }
+
+    fn draw_line(&mut self, a: &VertexOutput, b: &VertexOutput, pixel_program: &dyn Fn(&[f32]) -> [f32; 4]) {
+        if let Some((a, b)) = clip_line(a, b) {
+            self.rasterize_line(&self.project(&a), &self.project(&b), pixel_program);
+        }
+    }
+
+    fn draw_triangle(&mut self, triangle: [&VertexOutput; 3], pixel_program: &dyn Fn(&[f32]) -> [f32; 4]) {
+        let polygon = clip(triangle);
+        if polygon.len() < 3 {
+            return;
+        }
+
+        let polygon = polygon.iter().map(|vertex| self.project(vertex)).collect::<Vec<_>>();
+
+        // Clockwise triangles are front-facing; in screen space, where y points down, their area is positive.
+        let area = (0..polygon.len())
+            .map(|i| {
+                let (from, to) = (&polygon[i], &polygon[(i + 1) % polygon.len()]);
+                from.x * to.y - to.x * from.y
+            })
+            .sum::<f32>();
+        let culled = match self.cull_mode {
+            CullMode::None => area == 0.,
+            CullMode::Front => area >= 0.,
+            CullMode::Back => area <= 0.,
+        };
+
+        if culled {
+            return;
+        }
+
+        if self.wireframe {
+            for i in 0..polygon.len() {
+                self.rasterize_line(&polygon[i], &polygon[(i + 1) % polygon.len()], pixel_program);
+            }
+        } else {
+            for i in 1..polygon.len() - 1 {
+                let (b, c) = if area > 0. { (i, i + 1) } else { (i + 1, i) };
+                self.rasterize(&polygon[0], &polygon[b], &polygon[c], pixel_program);
+            }
+        }
+    }
+
+    /// Rasterizes a clockwise triangle.
+    fn rasterize(&mut self, a: &ScreenVertex, b: &ScreenVertex, c: &ScreenVertex, pixel_program: &dyn Fn(&[f32]) -> [f32; 4]) {
+        let area = edge(a, b, c.x, c.y);
+        if area <= 0. {
+            return;
+        }
+
+        let (left, top, right, bottom) = self.bounds();
+        let min_x = (a.x.min(b.x).min(c.x).floor().max(0.) as u32).max(left);
+        let min_y = (a.y.min(b.y).min(c.y).floor().max(0.) as u32).max(top);
+        let max_x = (a.x.max(b.x).max(c.x).ceil().max(0.) as u32).min(right);
+        let max_y = (a.y.max(b.y).max(c.y).ceil().max(0.) as u32).min(bottom);
+
+        let mut varyings = vec![0.; a.varyings.len()];
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                let (center_x, center_y) = (x as f32 + 0.5, y as f32 + 0.5);
+                let weight_a = edge(b, c, center_x, center_y);
+                let weight_b = edge(c, a, center_x, center_y);
+                let weight_c = edge(a, b, center_x, center_y);
+
+                if !covers(weight_a, b, c) || !covers(weight_b, c, a) || !covers(weight_c, a, b) {
+                    continue;
+                }
+
+                let (weight_a, weight_b, weight_c) = (weight_a / area, weight_b / area, weight_c / area);
+                let w = 1. / (weight_a * a.inv_w + weight_b * b.inv_w + weight_c * c.inv_w);
+                for (i, varying) in varyings.iter_mut().enumerate() {
+                    *varying = (weight_a * a.varyings[i] + weight_b * b.varyings[i] + weight_c * c.varyings[i]) * w;
+                }
+
+                self.write(x, y, pixel_program(&varyings));
+            }
+        }
+    }
+
+    /// Covers the pixels whose centers are closest to the line along its major axis, which approximates Direct3D's
+    /// line rasterization rules. The pixel at the end of the line is omitted, so that connected lines do not cover
+    /// their shared pixels twice.
+    fn rasterize_line(&mut self, a: &ScreenVertex, b: &ScreenVertex, pixel_program: &dyn Fn(&[f32]) -> [f32; 4]) {
+        let (dx, dy) = (b.x - a.x, b.y - a.y);
+        let x_major = dx.abs() >= dy.abs();
+        let (start, length) = if x_major { (a.x, dx) } else { (a.y, dy) };
+        if length == 0. {
+            return;
+        }
+
+        // The pixel centers k + 0.5 along the major axis that lie in [start, end) or (end, start].
+        let (first, last) = match length > 0. {
+            true => ((start - 0.5).ceil(), (start + length - 0.5).ceil()),
+            false => ((start - 0.5).floor(), (start + length - 0.5).floor()),
+        };
+
+        let mut varyings = vec![0.; a.varyings.len()];
+        let steps = (last - first).abs() as u32;
+        for step in 0..steps {
+            let center = first + (step as f32) * length.signum() + 0.5;
+            let t = (center - start) / length;
+            let (x, y) = match x_major {
+                true => (center - 0.5, (a.y + t * dy).floor()),
+                false => ((a.x + t * dx).floor(), center - 0.5),
+            };
+
+            if !self.contains(x, y) {
+                continue;
+            }
+
+            let w = 1. / (a.inv_w + t * (b.inv_w - a.inv_w));
+            for (i, varying) in varyings.iter_mut().enumerate() {
+                *varying = (a.varyings[i] + t * (b.varyings[i] - a.varyings[i])) * w;
+            }
+
+            self.write(x as u32, y as u32, pixel_program(&varyings));
+        }
+    }
+
+    fn project(&self, vertex: &VertexOutput) -> ScreenVertex {
+        let [x, y, _, w] = vertex.position;
+        let inv_w = 1. / w;
+        let (x, y) = self.to_screen(x * inv_w, y * inv_w);
+
+        ScreenVertex {
+            x: (x * SUBPIXEL_PRECISION).round() / SUBPIXEL_PRECISION,
+            y: (y * SUBPIXEL_PRECISION).round() / SUBPIXEL_PRECISION,
+            inv_w,
+            varyings: vertex.varyings.iter().map(|varying| varying * inv_w).collect(),
+        }
+    }
+
+    fn to_screen(&self, x: f32, y: f32) -> (f32, f32) {
+        (
+            self.viewport.left as f32 + (x + 1.) * 0.5 * self.viewport.width as f32,
+            self.viewport.top as f32 + (1. - y) * 0.5 * self.viewport.height as f32,
+        )
+    }
+
+    /// The left, top, right, and bottom edges of the region pixels can be written to, which is the intersection of the
+    /// viewport, the render target, and the scissor rectangle, if any.
+    fn bounds(&self) -> (u32, u32, u32, u32) {
+        let viewport = &self.viewport;
+        let mut bounds = (
+            viewport.left,
+            viewport.top,
+            (viewport.left + viewport.width).min(self.width),
+            (viewport.top + viewport.height).min(self.height),
+        );
+
+        if let Some(scissor) = &self.scissor {
+            bounds.0 = bounds.0.max(scissor.left);
+            bounds.1 = bounds.1.max(scissor.top);
+            bounds.2 = bounds.2.min(scissor.left + scissor.width);
+            bounds.3 = bounds.3.min(scissor.top + scissor.height);
+        }
+
+        bounds
+    }
+
+    fn contains(&self, x: f32, y: f32) -> bool {
+        let (left, top, right, bottom) = self.bounds();
+        x >= left as f32 && y >= top as f32 && x < right as f32 && y < bottom as f32
+    }
+
+    fn write(&mut self, x: u32, y: u32, color: [f32; 4]) {
+        let offset = ((y * self.width + x) * 4) as usize;
+        let pixel = &mut self.pixels[offset..offset + 4];
+        let target = [0, 1, 2, 3].map(|i| pixel[i] as f32 / 255.);
+        let color = blend(self.blend_mode, color.map(|component| component.clamp(0., 1.)), target);
+
+        for (target, component) in pixel.iter_mut().zip(color) {
+            *target = (component.clamp(0., 1.) * 255. + 0.5) as u8;
+        }
+    }
+}
+
+/// Combines the pixel shader's color with the render target's color like the corresponding Direct3D blend state.
+fn blend(blend_mode: BlendMode, [r, g, b, a]: [f32; 4], target: [f32; 4]) -> [f32; 4] {
+    let (source_factor, target_factor, target_alpha_factor) = match blend_mode {
+        BlendMode::Opaque => return [r, g, b, a],
+        BlendMode::Alpha => (a, 1. - a, 1. - a),
+        BlendMode::Additive => (a, 1., 1.),
+        BlendMode::Premultiplied => (1., 1. - a, 1. - a),
+    };
+
+    [
+        r * source_factor + target[0] * target_factor,
+        g * source_factor + target[1] * target_factor,
+        b * source_factor + target[2] * target_factor,
+        a + target[3] * target_alpha_factor,
+    ]
+}
+
+/// Clips the triangle against the near and far planes, i.e. `0 <= z <= w`; clipping against the other planes is
+/// unnecessary as the rasterizer never leaves the viewport.
+fn clip(triangle: [&VertexOutput; 3]) -> Vec<VertexOutput> {
+    let mut polygon = triangle.map(VertexOutput::clone).to_vec();
+
+    for distance in [near_distance, far_distance] {
+        let mut clipped = Vec::with_capacity(polygon.len() + 1);
+        for (i, current) in polygon.iter().enumerate() {
+            let next = &polygon[(i + 1) % polygon.len()];
+            let (current_distance, next_distance) = (distance(current.position), distance(next.position));
+
+            if current_distance >= 0. {
+                clipped.push(current.clone());
+            }
+
+            if (current_distance >= 0.) != (next_distance >= 0.) {
+                clipped.push(lerp(current, next, current_distance / (current_distance - next_distance)));
+            }
+        }
+
+        polygon = clipped;
+    }
+
+    polygon.retain(|vertex| vertex.position[3] > 0.);
+    polygon
+}
+
+/// Clips the line against the near and far planes like [`clip`] clips triangles.
+fn clip_line(a: &VertexOutput, b: &VertexOutput) -> Option<(VertexOutput, VertexOutput)> {
+    let (mut start, mut end) = (0., 1.);
+    for distance in [near_distance, far_distance] {
+        let (distance_a, distance_b) = (distance(a.position), distance(b.position));
+        match (distance_a >= 0., distance_b >= 0.) {
+            (false, false) => return None,
+            (false, true) => start = f32::max(start, distance_a / (distance_a - distance_b)),
+            (true, false) => end = f32::min(end, distance_a / (distance_a - distance_b)),
+            (true, true) => (),
+        }
+    }
+
+    let (a, b) = (lerp(a, b, start), lerp(a, b, end));
+    (start <= end && a.position[3] > 0. && b.position[3] > 0.).then_some((a, b))
+}
+
+fn near_distance([_, _, z, _]: [f32; 4]) -> f32 {
+    z
+}
+
+fn far_distance([_, _, z, w]: [f32; 4]) -> f32 {
+    w - z
+}
+
+fn lerp(from: &VertexOutput, to: &VertexOutput, t: f32) -> VertexOutput {
+    let lerp = |from: f32, to: f32| from + t * (to - from);
+    VertexOutput {
+        position: [0, 1, 2, 3].map(|i| lerp(from.position[i], to.position[i])),
+        varyings: from
+            .varyings
+            .iter()
+            .zip(&to.varyings)
+            .map(|(&from, &to)| lerp(from, to))
+            .collect(),
+    }
+}
+
+fn edge(from: &ScreenVertex, to: &ScreenVertex, x: f32, y: f32) -> f32 {
+    (to.x - from.x) * (y - from.y) - (to.y - from.y) * (x - from.x)
+}
+
+/// Applies the top-left rule: Pixel centers lying exactly on an edge are only covered if the edge is a top edge or a
+/// left edge of the triangle.
+fn covers(weight: f32, from: &ScreenVertex, to: &ScreenVertex) -> bool {
+    let (dx, dy) = (to.x - from.x, to.y - from.y);
+    weight > 0. || (weight == 0. && (dy < 0. || (dy == 0. && dx > 0.)))
+}
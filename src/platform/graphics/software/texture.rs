@@ -0,0 +1,102 @@
+use super::Pixels;
+use crate::platform::graphics::{
+    state::{AddressMode, SamplerDescription, TextureFilter},
+    texture::TextureFormat,
+};
+
+/// The number of texture and sampler slots of the pixel shader stage, matching the sampler slots of Direct3D 11.
+pub(super) const TEXTURE_SLOTS: usize = 16;
+
+pub(super) type TextureSlots = [Option<(Pixels, u32, u32)>; TEXTURE_SLOTS];
+pub(super) type SamplerSlots = [Option<SamplerDescription>; TEXTURE_SLOTS];
+
+/// Converts pixels of the given format to RGBA8, which is how textures are stored regardless of their format.
+pub(super) fn to_rgba(format: TextureFormat, data: &[u8]) -> Vec<u8> {
+    match format {
+        TextureFormat::Rgba8 => data.to_vec(),
+        TextureFormat::Bgra8 => data
+            .chunks_exact(4)
+            .flat_map(|pixel| [pixel[2], pixel[1], pixel[0], pixel[3]])
+            .collect(),
+        TextureFormat::R8 => data.iter().flat_map(|&red| [red, 0, 0, 255]).collect(),
+    }
+}
+
+/// Halves the size of the RGBA8 image by averaging blocks of 2x2 pixels. If a dimension is odd, the last row or
+/// column is merged into the previous block.
+pub(super) fn downsample(pixels: &[u8], width: u32, height: u32) -> (Vec<u8>, u32, u32) {
+    let (target_width, target_height) = ((width / 2).max(1), (height / 2).max(1));
+    let mut target = Vec::with_capacity((target_width * target_height * 4) as usize);
+
+    for y in 0..target_height {
+        for x in 0..target_width {
+            let source_xs = x * 2..if x == target_width - 1 { width } else { x * 2 + 2 };
+            let source_ys = y * 2..if y == target_height - 1 { height } else { y * 2 + 2 };
+
+            let mut sum = [0u32; 4];
+            let mut count = 0;
+            for source_y in source_ys {
+                for source_x in source_xs.clone() {
+                    let offset = ((source_y * width + source_x) * 4) as usize;
+                    for (component, &value) in sum.iter_mut().zip(&pixels[offset..offset + 4]) {
+                        *component += value as u32;
+                    }
+                    count += 1;
+                }
+            }
+
+            target.extend(sum.map(|component| ((component + count / 2) / count) as u8));
+        }
+    }
+
+    (target, target_width, target_height)
+}
+
+/// Samples the texture's largest mipmap; as the rasterizer does not compute screen-space derivatives, there is no
+/// way to select a smaller one, so trilinear filtering falls back to bilinear filtering.
+pub(super) fn sample(pixels: &[u8], width: u32, height: u32, sampler: &SamplerDescription, [u, v]: [f32; 2]) -> [f32; 4] {
+    let texel = |x: i64, y: i64| {
+        let x = address(x, width, sampler.address_mode);
+        let y = address(y, height, sampler.address_mode);
+        let offset = ((y * width + x) * 4) as usize;
+        [0, 1, 2, 3].map(|i| pixels[offset + i] as f32 / 255.)
+    };
+
+    let (x, y) = (u * width as f32, v * height as f32);
+    match sampler.filter {
+        TextureFilter::Nearest => texel(x.floor() as i64, y.floor() as i64),
+        TextureFilter::Bilinear | TextureFilter::Trilinear => {
+            let (x, y) = (x - 0.5, y - 0.5);
+            let (left, top) = (x.floor(), y.floor());
+            let (s, t) = (x - left, y - top);
+            let (left, top) = (left as i64, top as i64);
+
+            let [top_left, top_right, bottom_left, bottom_right] =
+                [(left, top), (left + 1, top), (left, top + 1), (left + 1, top + 1)].map(|(x, y)| texel(x, y));
+
+            [0, 1, 2, 3].map(|i| {
+                let upper = top_left[i] + s * (top_right[i] - top_left[i]);
+                let lower = bottom_left[i] + s * (bottom_right[i] - bottom_left[i]);
+                upper + t * (lower - upper)
+            })
+        }
+    }
+}
+
+fn address(coordinate: i64, size: u32, address_mode: AddressMode) -> u32 {
+    let size = size as i64;
+    let coordinate = match address_mode {
+        AddressMode::Wrap => coordinate.rem_euclid(size),
+        AddressMode::Clamp => coordinate.clamp(0, size - 1),
+        AddressMode::Mirror => {
+            let coordinate = coordinate.rem_euclid(size * 2);
+            if coordinate < size {
+                coordinate
+            } else {
+                size * 2 - 1 - coordinate
+            }
+        }
+    };
+
+    coordinate as u32
+}
@@ -1,50 +1,232 @@
-use super::GraphicsDevice;
-use crate::primitives::Rectangle;
-use winapi::um::{
-    d3d11::{D3D11_RECT, D3D11_VIEWPORT},
-    d3dcommon::{D3D11_PRIMITIVE_TOPOLOGY_POINTLIST, D3D11_PRIMITIVE_TOPOLOGY_TRIANGLELIST},
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    hash::Hash,
 };
 
-#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PrimitiveType {
-    Points = D3D11_PRIMITIVE_TOPOLOGY_POINTLIST,
-    Triangles = D3D11_PRIMITIVE_TOPOLOGY_TRIANGLELIST,
-}
-
-impl GraphicsDevice {
-    pub fn set_viewport(&self, viewport: &Rectangle<u32>) {
-        unsafe {
-            self.context.RSSetViewports(
-                1,
-                &D3D11_VIEWPORT {
-                    TopLeftX: viewport.left as f32,
-                    TopLeftY: viewport.top as f32,
-                    Width: viewport.width as f32,
-                    Height: viewport.height as f32,
-                    MaxDepth: 1.,
-                    MinDepth: 0.,
-                },
-            );
+    Points,
+    /// Draws a line between each pair of vertices.
+    Lines,
+    /// Draws a line from each vertex to the next one.
+    LineStrip,
+    Triangles,
+    /// Forms a triangle from each vertex and the two preceding ones; the winding of every other triangle is reversed,
+    /// so that all triangles of the strip face the same direction.
+    TriangleStrip,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TextureFilter {
+    Nearest,
+    Bilinear,
+    /// Bilinear filtering that additionally blends between the two closest mipmaps.
+    Trilinear,
+}
+
+/// Determines how texture coordinates outside of [0, 1] are resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AddressMode {
+    Wrap,
+    Clamp,
+    Mirror,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SamplerDescription {
+    pub filter: TextureFilter,
+    pub address_mode: AddressMode,
+}
+
+/// Determines how the colors written by the pixel shader are combined with the colors of the render target.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum BlendMode {
+    /// Replaces the render target's colors.
+    #[default]
+    Opaque,
+    /// Blends colors with straight alpha, i.e. `source * source_alpha + target * (1 - source_alpha)`.
+    Alpha,
+    /// Adds the colors weighted by their alpha to the render target's colors, e.g. for glows and explosions.
+    Additive,
+    /// Blends colors whose components have already been multiplied with their alpha, i.e.
+    /// `source + target * (1 - source_alpha)`.
+    Premultiplied,
+}
+
+/// The comparison of a new value with the value stored in the depth or stencil buffer that has to succeed for a pixel
+/// to be drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Comparison {
+    Never,
+    Less,
+    LessEqual,
+    Equal,
+    GreaterEqual,
+    Greater,
+    NotEqual,
+    Always,
+}
+
+/// Determines how the stencil buffer is updated for pixels that pass the stencil and depth tests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StencilOperation {
+    Keep,
+    Zero,
+    Replace,
+    Increment,
+    Decrement,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StencilDescription {
+    /// Compares the reference value with the value in the stencil buffer.
+    pub comparison: Comparison,
+    pub reference: u8,
+    pub operation: StencilOperation,
+}
+
+/// Depth and stencil testing only have an effect on render targets with a depth stencil buffer; the default
+/// description disables both.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct DepthStencilDescription {
+    /// Compares the pixel's depth with the depth in the depth buffer; depth testing is disabled if there is none.
+    pub depth_test: Option<Comparison>,
+    pub depth_write: bool,
+    pub stencil: Option<StencilDescription>,
+}
+
+/// Determines which triangles are culled; clockwise triangles are front-facing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CullMode {
+    None,
+    Front,
+    Back,
+}
+
+/// The default description matches Direct3D's default rasterizer state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RasterizerDescription {
+    pub cull_mode: CullMode,
+    /// Discards the pixels outside of the rectangle passed to `set_scissor_rect`.
+    pub scissor: bool,
+    /// Only draws the edges of triangles.
+    pub wireframe: bool,
+}
+
+impl Default for RasterizerDescription {
+    fn default() -> RasterizerDescription {
+        RasterizerDescription {
+            cull_mode: CullMode::Back,
+            scissor: false,
+            wireframe: false,
         }
     }
+}
+
+/// The number of sampler slots of the pixel shader stage, matching Direct3D 11.
+pub const SAMPLER_SLOTS: usize = 16;
 
-    pub fn set_scissor_rect(&self, rectangle: &Rectangle<u32>) {
-        unsafe {
-            self.context.RSSetScissorRects(
-                1,
-                &D3D11_RECT {
-                    left: rectangle.left as i32,
-                    top: rectangle.top as i32,
-                    right: (rectangle.left + rectangle.width) as i32,
-                    bottom: (rectangle.top + rectangle.height) as i32,
-                },
-            );
+/// Shares one state object between all identical descriptions, so that creating a state object for every draw does
+/// not create a new object every time. The cache has to be replaced when the device is recreated, as the cached
+/// objects belong to the lost device.
+pub(crate) struct StateCache<D, S> {
+    states: RefCell<HashMap<D, S>>,
+}
+
+impl<D: Copy + Eq + Hash, S: Clone> StateCache<D, S> {
+    pub fn get_or_create<E>(&self, description: &D, create: impl FnOnce() -> Result<S, E>) -> Result<S, E> {
+        if let Some(state) = self.states.borrow().get(description) {
+            return Ok(state.clone());
         }
+
+        // `create` must not hold the borrow, as it may create other state objects.
+        let state = create()?;
+        self.states.borrow_mut().insert(*description, state.clone());
+        Ok(state)
     }
+}
 
-    pub fn set_primitive_type(&self, primitive_type: PrimitiveType) {
-        unsafe {
-            self.context.IASetPrimitiveTopology(primitive_type as u32);
+impl<D, S> Default for StateCache<D, S> {
+    fn default() -> StateCache<D, S> {
+        StateCache {
+            states: RefCell::new(HashMap::new()),
         }
     }
 }
+
+/// Remembers the description of the state object bound to a slot of the pipeline. As state objects are shared between
+/// identical descriptions, comparing descriptions suffices to detect redundant binds.
+pub(crate) struct BoundState<D> {
+    description: Cell<Option<D>>,
+}
+
+impl<D: Copy + PartialEq> BoundState<D> {
+    /// Records that the state object with the given description is bound, returning `false` if it is bound already.
+    pub fn bind(&self, description: &D) -> bool {
+        self.description.replace(Some(*description)) != Some(*description)
+    }
+}
+
+impl<D> Default for BoundState<D> {
+    fn default() -> BoundState<D> {
+        BoundState {
+            description: Cell::new(None),
+        }
+    }
+}
+
+/// The state objects bound to the pipeline, which have to be forgotten whenever the backend resets its state.
+#[derive(Default)]
+pub(crate) struct BoundStates {
+    pub blend: BoundState<BlendMode>,
+    pub depth_stencil: BoundState<DepthStencilDescription>,
+    pub rasterizer: BoundState<RasterizerDescription>,
+    pub samplers: [BoundState<SamplerDescription>; SAMPLER_SLOTS],
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn identical_descriptions_share_state_objects() {
+        let cache = StateCache::default();
+        let created = Cell::new(0);
+        let create = |mode| {
+            cache
+                .get_or_create(&mode, || {
+                    created.set(created.get() + 1);
+                    Ok::<_, ()>(Rc::new(mode))
+                })
+                .unwrap()
+        };
+
+        let alpha = create(BlendMode::Alpha);
+        assert!(Rc::ptr_eq(&alpha, &create(BlendMode::Alpha)));
+        assert!(!Rc::ptr_eq(&alpha, &create(BlendMode::Additive)));
+        assert_eq!(created.get(), 2);
+    }
+
+    #[test]
+    fn redundant_binds_are_detected() {
+        let bound = BoundStates::default();
+        let scissor = RasterizerDescription {
+            scissor: true,
+            ..Default::default()
+        };
+
+        assert!(bound.rasterizer.bind(&RasterizerDescription::default()));
+        assert!(!bound.rasterizer.bind(&RasterizerDescription::default()));
+        assert!(bound.rasterizer.bind(&scissor));
+        assert!(bound.samplers[1].bind(&SamplerDescription {
+            filter: TextureFilter::Nearest,
+            address_mode: AddressMode::Wrap,
+        }));
+        assert!(!bound.samplers[1].bind(&SamplerDescription {
+            filter: TextureFilter::Nearest,
+            address_mode: AddressMode::Wrap,
+        }));
+    }
+}
@@ -1,3 +1,59 @@
-use super::GraphicsDevice;
+use crate::platform::error::{Error, Result};
 
-impl GraphicsDevice {}
+/// The maximum width and height of textures supported by Direct3D 11 hardware.
+pub const MAX_TEXTURE_SIZE: u32 = 16384;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextureFormat {
+    Rgba8,
+    /// A single channel; shaders sample it as (r, 0, 0, 1).
+    R8,
+    Bgra8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextureDescription {
+    pub width: u32,
+    pub height: u32,
+    pub format: TextureFormat,
+    /// Allocates the full chain of mipmaps down to 1x1 pixels, which can then be generated with `generate_mipmaps`.
+    pub mipmaps: bool,
+    /// Allows render targets to be created for the texture.
+    pub render_target: bool,
+}
+
+impl TextureFormat {
+    pub fn bytes_per_pixel(self) -> u32 {
+        match self {
+            TextureFormat::Rgba8 | TextureFormat::Bgra8 => 4,
+            TextureFormat::R8 => 1,
+        }
+    }
+}
+
+impl TextureDescription {
+    /// Checks that the size of the texture is supported and that the initial data, if any, covers the whole texture.
+    pub fn validate(&self, data: Option<&[u8]>) -> Result<()> {
+        let error = |message| Err(Error::other("Failed to create texture.", message));
+        if self.width == 0 || self.height == 0 {
+            return error(format!("The texture size {}x{} is empty.", self.width, self.height));
+        }
+
+        if self.width > MAX_TEXTURE_SIZE || self.height > MAX_TEXTURE_SIZE {
+            return error(format!(
+                "The texture size {}x{} exceeds the maximum of {MAX_TEXTURE_SIZE}x{MAX_TEXTURE_SIZE}.",
+                self.width, self.height
+            ));
+        }
+
+        // Cannot overflow, as the size of the largest texture is 1 GiB.
+        let size = self.width * self.height * self.format.bytes_per_pixel();
+        match data {
+            Some(data) if data.len() != size as usize => error(format!(
+                "Expected {size} bytes of texture data, but got {} bytes.",
+                data.len()
+            )),
+            _ => Ok(()),
+        }
+    }
+}
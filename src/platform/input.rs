@@ -1,7 +1,6 @@
 use num_enum::TryFromPrimitive;
-use winapi::um::winuser::*;
 
-#[derive(Debug, TryFromPrimitive)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, TryFromPrimitive)]
 #[repr(i32)]
 pub enum MouseButton {
     Left,
@@ -11,7 +10,8 @@ pub enum MouseButton {
     XButton2,
 }
 
-#[derive(Debug, TryFromPrimitive)]
+/// The discriminants are the corresponding Windows virtual key codes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, TryFromPrimitive)]
 #[repr(i32)]
 pub enum Key {
     NumpadEnter,
@@ -51,75 +51,370 @@ pub enum Key {
     X = 0x58,
     Y = 0x59,
     Z = 0x5A,
-    BackSlash2 = VK_OEM_102,
-    Scroll = VK_SCROLL,
-    Print = VK_SNAPSHOT,
-    NumLock = VK_NUMLOCK,
-    NumpadDecimal = VK_DECIMAL,
-    LeftShift = VK_LSHIFT,
-    RightShift = VK_RSHIFT,
-    LeftSystem = VK_LWIN,
-    RightSystem = VK_RWIN,
-    Menu = VK_APPS,
-    Semicolon = VK_OEM_1,
-    Slash = VK_OEM_2,
-    Equal = VK_OEM_PLUS,
-    Dash = VK_OEM_MINUS,
-    LeftBracket = VK_OEM_4,
-    RightBracket = VK_OEM_6,
-    Comma = VK_OEM_COMMA,
-    Period = VK_OEM_PERIOD,
-    Quote = VK_OEM_7,
-    BackSlash = VK_OEM_5,
-    Grave = VK_OEM_3,
-    Escape = VK_ESCAPE,
-    Space = VK_SPACE,
-    Return = VK_RETURN,
-    Back = VK_BACK,
-    Tab = VK_TAB,
-    PageUp = VK_PRIOR,
-    PageDown = VK_NEXT,
-    End = VK_END,
-    Home = VK_HOME,
-    Insert = VK_INSERT,
-    Delete = VK_DELETE,
-    Add = VK_ADD,
-    Subtract = VK_SUBTRACT,
-    Multiply = VK_MULTIPLY,
-    Divide = VK_DIVIDE,
-    Pause = VK_PAUSE,
-    F1 = VK_F1,
-    F2 = VK_F2,
-    F3 = VK_F3,
-    F4 = VK_F4,
-    F5 = VK_F5,
-    F6 = VK_F6,
-    F7 = VK_F7,
-    F8 = VK_F8,
-    F9 = VK_F9,
-    F10 = VK_F10,
-    F11 = VK_F11,
-    F12 = VK_F12,
-    F13 = VK_F13,
-    F14 = VK_F14,
-    F15 = VK_F15,
-    Left = VK_LEFT,
-    Right = VK_RIGHT,
-    Up = VK_UP,
-    Down = VK_DOWN,
-    CapsLock = VK_CAPITAL,
-    Numpad0 = VK_NUMPAD0,
-    Numpad1 = VK_NUMPAD1,
-    Numpad2 = VK_NUMPAD2,
-    Numpad3 = VK_NUMPAD3,
-    Numpad4 = VK_NUMPAD4,
-    Numpad5 = VK_NUMPAD5,
-    Numpad6 = VK_NUMPAD6,
-    Numpad7 = VK_NUMPAD7,
-    Numpad8 = VK_NUMPAD8,
-    Numpad9 = VK_NUMPAD9,
-    RightControl = VK_RCONTROL,
-    LeftControl = VK_LCONTROL,
-    RightAlt = VK_RMENU,
-    LeftAlt = VK_LMENU,
+    BackSlash2 = 0xE2,    // VK_OEM_102
+    Scroll = 0x91,        // VK_SCROLL
+    Print = 0x2C,         // VK_SNAPSHOT
+    NumLock = 0x90,       // VK_NUMLOCK
+    NumpadDecimal = 0x6E, // VK_DECIMAL
+    LeftShift = 0xA0,     // VK_LSHIFT
+    RightShift = 0xA1,    // VK_RSHIFT
+    LeftSystem = 0x5B,    // VK_LWIN
+    RightSystem = 0x5C,   // VK_RWIN
+    Menu = 0x5D,          // VK_APPS
+    Semicolon = 0xBA,     // VK_OEM_1
+    Slash = 0xBF,         // VK_OEM_2
+    Equal = 0xBB,         // VK_OEM_PLUS
+    Dash = 0xBD,          // VK_OEM_MINUS
+    LeftBracket = 0xDB,   // VK_OEM_4
+    RightBracket = 0xDD,  // VK_OEM_6
+    Comma = 0xBC,         // VK_OEM_COMMA
+    Period = 0xBE,        // VK_OEM_PERIOD
+    Quote = 0xDE,         // VK_OEM_7
+    BackSlash = 0xDC,     // VK_OEM_5
+    Grave = 0xC0,         // VK_OEM_3
+    Escape = 0x1B,        // VK_ESCAPE
+    Space = 0x20,         // VK_SPACE
+    Return = 0x0D,        // VK_RETURN
+    Back = 0x08,          // VK_BACK
+    Tab = 0x09,           // VK_TAB
+    PageUp = 0x21,        // VK_PRIOR
+    PageDown = 0x22,      // VK_NEXT
+    End = 0x23,           // VK_END
+    Home = 0x24,          // VK_HOME
+    Insert = 0x2D,        // VK_INSERT
+    Delete = 0x2E,        // VK_DELETE
+    Add = 0x6B,           // VK_ADD
+    Subtract = 0x6D,      // VK_SUBTRACT
+    Multiply = 0x6A,      // VK_MULTIPLY
+    Divide = 0x6F,        // VK_DIVIDE
+    Pause = 0x13,         // VK_PAUSE
+    F1 = 0x70,            // VK_F1
+    F2 = 0x71,            // VK_F2
+    F3 = 0x72,            // VK_F3
+    F4 = 0x73,            // VK_F4
+    F5 = 0x74,            // VK_F5
+    F6 = 0x75,            // VK_F6
+    F7 = 0x76,            // VK_F7
+    F8 = 0x77,            // VK_F8
+    F9 = 0x78,            // VK_F9
+    F10 = 0x79,           // VK_F10
+    F11 = 0x7A,           // VK_F11
+    F12 = 0x7B,           // VK_F12
+    F13 = 0x7C,           // VK_F13
+    F14 = 0x7D,           // VK_F14
+    F15 = 0x7E,           // VK_F15
+    Left = 0x25,          // VK_LEFT
+    Right = 0x27,         // VK_RIGHT
+    Up = 0x26,            // VK_UP
+    Down = 0x28,          // VK_DOWN
+    CapsLock = 0x14,      // VK_CAPITAL
+    Numpad0 = 0x60,       // VK_NUMPAD0
+    Numpad1 = 0x61,       // VK_NUMPAD1
+    Numpad2 = 0x62,       // VK_NUMPAD2
+    Numpad3 = 0x63,       // VK_NUMPAD3
+    Numpad4 = 0x64,       // VK_NUMPAD4
+    Numpad5 = 0x65,       // VK_NUMPAD5
+    Numpad6 = 0x66,       // VK_NUMPAD6
+    Numpad7 = 0x67,       // VK_NUMPAD7
+    Numpad8 = 0x68,       // VK_NUMPAD8
+    Numpad9 = 0x69,       // VK_NUMPAD9
+    RightControl = 0xA3,  // VK_RCONTROL
+    LeftControl = 0xA2,   // VK_LCONTROL
+    RightAlt = 0xA5,      // VK_RMENU
+    LeftAlt = 0xA4,       // VK_LMENU
+}
+
+const VK_CLEAR: i32 = 0x0C;
+const VK_CONTROL: i32 = 0x11;
+const VK_MENU: i32 = 0x12;
+
+/// Maps a Windows virtual key code to a key, using the E0 prefix of the key's scan code to tell apart keys that share
+/// a virtual key code: The navigation keys and the numpad keys with NumLock disabled, both Enter keys as well as the
+/// left and right Control and Alt keys.
+pub fn key_from_virtual_key(virtual_key: i32, is_e0: bool) -> Option<Key> {
+    let numpad_or = |numpad_key, key| Some(if is_e0 { key } else { numpad_key });
+
+    match virtual_key {
+        VK_CONTROL => Some(if is_e0 { Key::RightControl } else { Key::LeftControl }),
+        VK_MENU => Some(if is_e0 { Key::RightAlt } else { Key::LeftAlt }),
+        VK_CLEAR if !is_e0 => Some(Key::Numpad5),
+        _ => match Key::try_from(virtual_key).ok()? {
+            Key::Return if is_e0 => Some(Key::NumpadEnter),
+            Key::Insert => numpad_or(Key::Numpad0, Key::Insert),
+            Key::Delete => numpad_or(Key::NumpadDecimal, Key::Delete),
+            Key::Home => numpad_or(Key::Numpad7, Key::Home),
+            Key::End => numpad_or(Key::Numpad1, Key::End),
+            Key::PageUp => numpad_or(Key::Numpad9, Key::PageUp),
+            Key::PageDown => numpad_or(Key::Numpad3, Key::PageDown),
+            Key::Left => numpad_or(Key::Numpad4, Key::Left),
+            Key::Right => numpad_or(Key::Numpad6, Key::Right),
+            Key::Up => numpad_or(Key::Numpad8, Key::Up),
+            Key::Down => numpad_or(Key::Numpad2, Key::Down),
+            key => Some(key),
+        },
+    }
+}
+
+/// The physical location of a key, which does not depend on the keyboard layout, named after the key at that location
+/// on a US keyboard; `Key`, on the other hand, depends on the layout, so `Key::W` is located elsewhere on AZERTY or
+/// Dvorak keyboards. The discriminants are the set 1 scan codes, with 0x100 added for keys with an E0 prefix. Pause is
+/// the only key with an E1 prefix; as NumLock and Pause share the same scan code otherwise, NumLock is treated like a
+/// key with an E0 prefix instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, TryFromPrimitive)]
+#[repr(u32)]
+pub enum PhysicalKey {
+    Escape = 0x01,
+    Num1 = 0x02,
+    Num2 = 0x03,
+    Num3 = 0x04,
+    Num4 = 0x05,
+    Num5 = 0x06,
+    Num6 = 0x07,
+    Num7 = 0x08,
+    Num8 = 0x09,
+    Num9 = 0x0A,
+    Num0 = 0x0B,
+    Dash = 0x0C,
+    Equal = 0x0D,
+    Back = 0x0E,
+    Tab = 0x0F,
+    Q = 0x10,
+    W = 0x11,
+    E = 0x12,
+    R = 0x13,
+    T = 0x14,
+    Y = 0x15,
+    U = 0x16,
+    I = 0x17,
+    O = 0x18,
+    P = 0x19,
+    LeftBracket = 0x1A,
+    RightBracket = 0x1B,
+    Return = 0x1C,
+    LeftControl = 0x1D,
+    A = 0x1E,
+    S = 0x1F,
+    D = 0x20,
+    F = 0x21,
+    G = 0x22,
+    H = 0x23,
+    J = 0x24,
+    K = 0x25,
+    L = 0x26,
+    Semicolon = 0x27,
+    Quote = 0x28,
+    Grave = 0x29,
+    LeftShift = 0x2A,
+    BackSlash = 0x2B,
+    Z = 0x2C,
+    X = 0x2D,
+    C = 0x2E,
+    V = 0x2F,
+    B = 0x30,
+    N = 0x31,
+    M = 0x32,
+    Comma = 0x33,
+    Period = 0x34,
+    Slash = 0x35,
+    RightShift = 0x36,
+    NumpadMultiply = 0x37,
+    LeftAlt = 0x38,
+    Space = 0x39,
+    CapsLock = 0x3A,
+    F1 = 0x3B,
+    F2 = 0x3C,
+    F3 = 0x3D,
+    F4 = 0x3E,
+    F5 = 0x3F,
+    F6 = 0x40,
+    F7 = 0x41,
+    F8 = 0x42,
+    F9 = 0x43,
+    F10 = 0x44,
+    Pause = 0x45,
+    Scroll = 0x46,
+    Numpad7 = 0x47,
+    Numpad8 = 0x48,
+    Numpad9 = 0x49,
+    NumpadSubtract = 0x4A,
+    Numpad4 = 0x4B,
+    Numpad5 = 0x4C,
+    Numpad6 = 0x4D,
+    NumpadAdd = 0x4E,
+    Numpad1 = 0x4F,
+    Numpad2 = 0x50,
+    Numpad3 = 0x51,
+    Numpad0 = 0x52,
+    NumpadDecimal = 0x53,
+    BackSlash2 = 0x56,
+    F11 = 0x57,
+    F12 = 0x58,
+    F13 = 0x64,
+    F14 = 0x65,
+    F15 = 0x66,
+    NumpadEnter = 0x11C,
+    RightControl = 0x11D,
+    NumpadDivide = 0x135,
+    Print = 0x137,
+    RightAlt = 0x138,
+    NumLock = 0x145,
+    Home = 0x147,
+    Up = 0x148,
+    PageUp = 0x149,
+    Left = 0x14B,
+    Right = 0x14D,
+    End = 0x14F,
+    Down = 0x150,
+    PageDown = 0x151,
+    Insert = 0x152,
+    Delete = 0x153,
+    LeftSystem = 0x15B,
+    RightSystem = 0x15C,
+    Menu = 0x15D,
+}
+
+/// The Linux evdev codes of the keys whose codes differ from their scan codes; the codes of all other keys up to F12
+/// are their scan codes.
+const EVDEV_CODES: [(u32, PhysicalKey); 23] = [
+    (69, PhysicalKey::NumLock),
+    (96, PhysicalKey::NumpadEnter),
+    (97, PhysicalKey::RightControl),
+    (98, PhysicalKey::NumpadDivide),
+    (99, PhysicalKey::Print),
+    (100, PhysicalKey::RightAlt),
+    (102, PhysicalKey::Home),
+    (103, PhysicalKey::Up),
+    (104, PhysicalKey::PageUp),
+    (105, PhysicalKey::Left),
+    (106, PhysicalKey::Right),
+    (107, PhysicalKey::End),
+    (108, PhysicalKey::Down),
+    (109, PhysicalKey::PageDown),
+    (110, PhysicalKey::Insert),
+    (111, PhysicalKey::Delete),
+    (119, PhysicalKey::Pause),
+    (125, PhysicalKey::LeftSystem),
+    (126, PhysicalKey::RightSystem),
+    (127, PhysicalKey::Menu),
+    (183, PhysicalKey::F13),
+    (184, PhysicalKey::F14),
+    (185, PhysicalKey::F15),
+];
+
+impl PhysicalKey {
+    pub fn from_scan_code(scan_code: u32) -> Option<PhysicalKey> {
+        PhysicalKey::try_from(scan_code).ok()
+    }
+
+    pub fn scan_code(self) -> u32 {
+        self as u32
+    }
+
+    /// Maps a Linux evdev code, i.e. an X11 key code minus 8, to the key.
+    pub fn from_evdev_code(code: u32) -> Option<PhysicalKey> {
+        match EVDEV_CODES.iter().find(|(evdev_code, _)| *evdev_code == code) {
+            Some((_, key)) => Some(*key),
+            None if code <= PhysicalKey::F12 as u32 => PhysicalKey::from_scan_code(code),
+            None => None,
+        }
+    }
+
+    pub fn evdev_code(self) -> u32 {
+        match EVDEV_CODES.iter().find(|(_, key)| *key == self) {
+            Some((code, _)) => *code,
+            None => self as u32,
+        }
+    }
+
+    /// Gets the key at the physical location on US keyboards, which the physical keys are named after.
+    pub fn us_layout_key(self) -> Option<Key> {
+        match self {
+            PhysicalKey::NumpadMultiply => Some(Key::Multiply),
+            PhysicalKey::NumpadSubtract => Some(Key::Subtract),
+            PhysicalKey::NumpadAdd => Some(Key::Add),
+            PhysicalKey::NumpadDivide => Some(Key::Divide),
+            _ => {
+                let name = format!("{self:?}");
+                (0..=0xFF)
+                    .filter_map(|code| Key::try_from(code).ok())
+                    .find(|key| format!("{key:?}") == name)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VK_INSERT: i32 = Key::Insert as i32;
+    const VK_RETURN: i32 = Key::Return as i32;
+    const VK_LEFT: i32 = Key::Left as i32;
+
+    #[test]
+    fn physical_keys_are_mapped_to_the_keys_of_us_keyboards() {
+        assert_eq!(PhysicalKey::W.us_layout_key(), Some(Key::W));
+        assert_eq!(PhysicalKey::Num1.us_layout_key(), Some(Key::Num1));
+        assert_eq!(PhysicalKey::LeftControl.us_layout_key(), Some(Key::LeftControl));
+        assert_eq!(PhysicalKey::NumpadAdd.us_layout_key(), Some(Key::Add));
+    }
+
+    #[test]
+    fn navigation_keys_are_distinguished_from_numpad_keys() {
+        assert_eq!(key_from_virtual_key(VK_INSERT, true), Some(Key::Insert));
+        assert_eq!(key_from_virtual_key(VK_INSERT, false), Some(Key::Numpad0));
+        assert_eq!(key_from_virtual_key(VK_LEFT, true), Some(Key::Left));
+        assert_eq!(key_from_virtual_key(VK_LEFT, false), Some(Key::Numpad4));
+        assert_eq!(key_from_virtual_key(Key::Delete as i32, false), Some(Key::NumpadDecimal));
+        assert_eq!(key_from_virtual_key(VK_CLEAR, false), Some(Key::Numpad5));
+        assert_eq!(key_from_virtual_key(VK_CLEAR, true), None);
+        assert_eq!(key_from_virtual_key(VK_RETURN, false), Some(Key::Return));
+        assert_eq!(key_from_virtual_key(VK_RETURN, true), Some(Key::NumpadEnter));
+        assert_eq!(key_from_virtual_key(VK_CONTROL, true), Some(Key::RightControl));
+        assert_eq!(key_from_virtual_key(VK_MENU, false), Some(Key::LeftAlt));
+
+        // Keys with NumLock enabled have their own virtual key codes.
+        assert_eq!(key_from_virtual_key(Key::Numpad7 as i32, false), Some(Key::Numpad7));
+        assert_eq!(key_from_virtual_key(Key::W as i32, false), Some(Key::W));
+        assert_eq!(key_from_virtual_key(0xFF, false), None);
+    }
+
+    #[test]
+    fn physical_keys_are_identified_by_scan_codes() {
+        let key = PhysicalKey::from_scan_code;
+        assert_eq!(key(0x11), Some(PhysicalKey::W));
+        assert_eq!(key(0x52), Some(PhysicalKey::Numpad0));
+        assert_eq!(key(0x152), Some(PhysicalKey::Insert));
+        assert_eq!(key(0x1C), Some(PhysicalKey::Return));
+        assert_eq!(key(0x11C), Some(PhysicalKey::NumpadEnter));
+        assert_eq!(key(0x35), Some(PhysicalKey::Slash));
+        assert_eq!(key(0x135), Some(PhysicalKey::NumpadDivide));
+        assert_eq!(key(0x37), Some(PhysicalKey::NumpadMultiply));
+        assert_eq!(key(0x137), Some(PhysicalKey::Print));
+        assert_eq!(key(0x45), Some(PhysicalKey::Pause));
+        assert_eq!(key(0x145), Some(PhysicalKey::NumLock));
+        assert_eq!(key(0x111), None);
+        assert_eq!(PhysicalKey::Home.scan_code(), 0x147);
+    }
+
+    #[test]
+    fn evdev_codes_are_mapped_to_physical_keys() {
+        let key = PhysicalKey::from_evdev_code;
+        assert_eq!(key(17), Some(PhysicalKey::W));
+        assert_eq!(key(71), Some(PhysicalKey::Numpad7));
+        assert_eq!(key(102), Some(PhysicalKey::Home));
+        assert_eq!(key(28), Some(PhysicalKey::Return));
+        assert_eq!(key(96), Some(PhysicalKey::NumpadEnter));
+        assert_eq!(key(69), Some(PhysicalKey::NumLock));
+        assert_eq!(key(119), Some(PhysicalKey::Pause));
+        assert_eq!(key(185), Some(PhysicalKey::F15));
+        assert_eq!(key(0x45 + 0x100), None);
+        assert_eq!(key(101), None);
+
+        for code in 0..=0x200 {
+            if let Some(key) = PhysicalKey::from_scan_code(code) {
+                assert_eq!(PhysicalKey::from_evdev_code(key.evdev_code()), Some(key));
+            }
+        }
+    }
 }
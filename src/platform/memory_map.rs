@@ -0,0 +1,132 @@
+use std::{fs::File, io, ops::Deref, path::Path, ptr::NonNull, slice};
+
+/// A read-only view of a file whose contents are paged in by the operating system on demand. The file should not
+/// be modified while it is mapped; writers are expected to replace it instead.
+pub struct MemoryMap {
+    /// The start of the mapped view, or a dangling pointer for empty files, which cannot be mapped.
+    address: NonNull<u8>,
+    size: usize,
+}
+
+impl MemoryMap {
+    pub fn open(path: &Path) -> io::Result<MemoryMap> {
+        let file = File::open(path)?;
+        let size = usize::try_from(file.metadata()?.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "The file is too large to be mapped."))?;
+
+        if size == 0 {
+            return Ok(MemoryMap {
+                address: NonNull::dangling(),
+                size,
+            });
+        }
+
+        let address = unsafe { map(&file, size)? };
+        Ok(MemoryMap { address, size })
+    }
+}
+
+impl Deref for MemoryMap {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.address.as_ptr(), self.size) }
+    }
+}
+
+impl AsRef<[u8]> for MemoryMap {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+impl Drop for MemoryMap {
+    fn drop(&mut self) {
+        if self.size > 0 {
+            unsafe { unmap(self.address, self.size) };
+        }
+    }
+}
+
+#[cfg(unix)]
+mod unix {
+    use std::ffi::{c_int, c_long, c_void};
+
+    pub const PROT_READ: c_int = 1;
+    pub const MAP_PRIVATE: c_int = 2;
+    pub const MAP_FAILED: *mut c_void = !0 as *mut c_void;
+
+    extern "C" {
+        pub fn mmap(address: *mut c_void, size: usize, protection: c_int, flags: c_int, fd: c_int, offset: c_long)
+            -> *mut c_void;
+        pub fn munmap(address: *mut c_void, size: usize) -> c_int;
+    }
+}
+
+#[cfg(unix)]
+unsafe fn map(file: &File, size: usize) -> io::Result<NonNull<u8>> {
+    use std::{os::unix::io::AsRawFd, ptr::null_mut};
+    use unix::*;
+
+    // The mapping remains valid after the file has been closed.
+    let address = mmap(null_mut(), size, PROT_READ, MAP_PRIVATE, file.as_raw_fd(), 0);
+    if address == MAP_FAILED {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(NonNull::new_unchecked(address as *mut u8))
+}
+
+#[cfg(unix)]
+unsafe fn unmap(address: NonNull<u8>, size: usize) {
+    unix::munmap(address.as_ptr() as _, size);
+}
+
+#[cfg(windows)]
+unsafe fn map(file: &File, _size: usize) -> io::Result<NonNull<u8>> {
+    use std::{os::windows::io::AsRawHandle, ptr::null_mut};
+    use winapi::um::{
+        handleapi::CloseHandle,
+        memoryapi::{CreateFileMappingW, MapViewOfFile, FILE_MAP_READ},
+        winnt::PAGE_READONLY,
+    };
+
+    let mapping = CreateFileMappingW(file.as_raw_handle() as _, null_mut(), PAGE_READONLY, 0, 0, null_mut());
+    if mapping.is_null() {
+        return Err(io::Error::last_os_error());
+    }
+
+    // The view keeps the mapping and the file open until it is unmapped.
+    let address = MapViewOfFile(mapping, FILE_MAP_READ, 0, 0, 0);
+    let error = io::Error::last_os_error();
+    CloseHandle(mapping);
+
+    NonNull::new(address as *mut u8).ok_or(error)
+}
+
+#[cfg(windows)]
+unsafe fn unmap(address: NonNull<u8>, _size: usize) {
+    winapi::um::memoryapi::UnmapViewOfFile(address.as_ptr() as _);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::directory;
+    use std::fs;
+
+    #[test]
+    fn files_are_mapped() {
+        let directory = directory("memory-map");
+        let path = directory.join("mapped");
+        fs::write(&path, b"mapped").unwrap();
+        assert_eq!(&*MemoryMap::open(&path).unwrap(), b"mapped");
+
+        fs::write(&path, b"").unwrap();
+        assert_eq!(&*MemoryMap::open(&path).unwrap(), b"");
+
+        fs::remove_file(&path).unwrap();
+        assert!(MemoryMap::open(&path).is_err());
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
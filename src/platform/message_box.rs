@@ -0,0 +1,99 @@
+use std::fmt;
+
+pub mod recording;
+pub mod terminal;
+#[cfg(windows)]
+pub mod win32;
+#[cfg(target_os = "linux")]
+pub mod xmessage;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Info => "Information",
+            Severity::Warning => "Warning",
+            Severity::Error => "Error",
+        })
+    }
+}
+
+/// The buttons a message box offers to the user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Buttons {
+    Ok,
+    OkCancel,
+    YesNo,
+}
+
+impl Buttons {
+    pub fn choices(self) -> &'static [Choice] {
+        match self {
+            Buttons::Ok => &[Choice::Ok],
+            Buttons::OkCancel => &[Choice::Ok, Choice::Cancel],
+            Buttons::YesNo => &[Choice::Yes, Choice::No],
+        }
+    }
+
+    /// The choice assumed if the message box is closed without choosing a button or cannot be shown at all, which is
+    /// the one that does not confirm anything.
+    pub fn dismissed(self) -> Choice {
+        match self {
+            Buttons::Ok => Choice::Ok,
+            Buttons::OkCancel => Choice::Cancel,
+            Buttons::YesNo => Choice::No,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Choice {
+    Ok,
+    Cancel,
+    Yes,
+    No,
+}
+
+impl fmt::Display for Choice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Choice::Ok => "OK",
+            Choice::Cancel => "Cancel",
+            Choice::Yes => "Yes",
+            Choice::No => "No",
+        })
+    }
+}
+
+pub trait MessageBox {
+    /// Shows the message and blocks until the user has chosen one of the buttons, which is returned.
+    fn show(&mut self, severity: Severity, buttons: Buttons, message: &str) -> Choice;
+}
+
+/// The message box of the platform, which is a native one on Windows.
+#[cfg(windows)]
+pub fn platform_message_box() -> Box<dyn MessageBox> {
+    Box::new(win32::Win32MessageBox)
+}
+
+/// The message box of the platform, which is shown by `xmessage` if there is an X server and on the terminal otherwise.
+#[cfg(not(windows))]
+pub fn platform_message_box() -> Box<dyn MessageBox> {
+    #[cfg(target_os = "linux")]
+    if std::env::var_os("DISPLAY").is_some() {
+        return Box::new(xmessage::XMessageBox);
+    }
+
+    Box::new(terminal::TerminalMessageBox::stdio())
+}
+
+/// Shows the message with the platform's message box.
+pub fn show_message_box(severity: Severity, buttons: Buttons, message: &str) -> Choice {
+    platform_message_box().show(severity, buttons, message)
+}
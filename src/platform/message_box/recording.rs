@@ -0,0 +1,79 @@
+use super::{Buttons, Choice, MessageBox, Severity};
+use std::collections::VecDeque;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShownMessage {
+    pub severity: Severity,
+    pub buttons: Buttons,
+    pub message: String,
+}
+
+/// Records the messages instead of showing them and answers them with scripted choices, so that code asking the user
+/// can be tested. Messages without a scripted choice are dismissed.
+pub struct RecordingMessageBox {
+    pub shown: Vec<ShownMessage>,
+    choices: VecDeque<Choice>,
+}
+
+impl RecordingMessageBox {
+    pub fn new(choices: impl IntoIterator<Item = Choice>) -> RecordingMessageBox {
+        RecordingMessageBox {
+            shown: vec![],
+            choices: choices.into_iter().collect(),
+        }
+    }
+}
+
+impl MessageBox for RecordingMessageBox {
+    fn show(&mut self, severity: Severity, buttons: Buttons, message: &str) -> Choice {
+        self.shown.push(ShownMessage {
+            severity,
+            buttons,
+            message: message.to_string(),
+        });
+
+        match self.choices.pop_front() {
+            Some(choice) => {
+                assert!(
+                    buttons.choices().contains(&choice),
+                    "Cannot choose {choice} for a message box with the buttons {buttons:?}."
+                );
+                choice
+            }
+            None => buttons.dismissed(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn messages_are_recorded_and_answered_with_the_scripted_choices() {
+        let mut message_box = RecordingMessageBox::new([Choice::Yes]);
+        assert_eq!(message_box.show(Severity::Warning, Buttons::YesNo, "Quit?"), Choice::Yes);
+        assert_eq!(message_box.show(Severity::Info, Buttons::OkCancel, "Retry?"), Choice::Cancel);
+        assert_eq!(
+            message_box.shown,
+            [
+                ShownMessage {
+                    severity: Severity::Warning,
+                    buttons: Buttons::YesNo,
+                    message: "Quit?".to_string(),
+                },
+                ShownMessage {
+                    severity: Severity::Info,
+                    buttons: Buttons::OkCancel,
+                    message: "Retry?".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot choose Cancel for a message box with the buttons YesNo.")]
+    fn scripted_choices_must_match_the_buttons() {
+        RecordingMessageBox::new([Choice::Cancel]).show(Severity::Error, Buttons::YesNo, "Quit?");
+    }
+}
@@ -0,0 +1,98 @@
+use super::{Buttons, Choice, MessageBox, Severity};
+use std::io::{self, BufRead, IsTerminal, Stderr, StdinLock, Write};
+
+/// Writes messages to the terminal and reads the user's choice from its input, e.g. when there is no graphical
+/// environment. Messages are dismissed if there is no input to read the choice from.
+pub struct TerminalMessageBox<R, W> {
+    input: Option<R>,
+    output: W,
+}
+
+impl TerminalMessageBox<StdinLock<'static>, Stderr> {
+    /// Writes to stderr and reads from stdin, unless stdin is not a terminal, so that the game does not wait for a
+    /// choice that no one can make.
+    pub fn stdio() -> TerminalMessageBox<StdinLock<'static>, Stderr> {
+        let stdin = io::stdin();
+        TerminalMessageBox::new(stdin.is_terminal().then(|| stdin.lock()), io::stderr())
+    }
+}
+
+impl<R: BufRead, W: Write> TerminalMessageBox<R, W> {
+    pub fn new(input: Option<R>, output: W) -> TerminalMessageBox<R, W> {
+        TerminalMessageBox { input, output }
+    }
+
+    /// Prompts for one of the choices until a valid one has been entered, i.e. the name of a choice or its first
+    /// letter. Returns `None` once the input has ended.
+    fn read_choice(&mut self, choices: &[Choice]) -> Option<Choice> {
+        let input = self.input.as_mut()?;
+        let prompt = choices.iter().map(Choice::to_string).collect::<Vec<_>>().join("/");
+
+        loop {
+            write!(self.output, "({prompt}) ").ok()?;
+            self.output.flush().ok()?;
+
+            let mut line = String::new();
+            if input.read_line(&mut line).ok()? == 0 {
+                return None;
+            }
+
+            let answer = line.trim().to_lowercase();
+            let matches = |choice: &&Choice| {
+                let name = choice.to_string().to_lowercase();
+                answer == name || (answer.len() == 1 && name.starts_with(&answer))
+            };
+
+            if let Some(choice) = choices.iter().find(matches) {
+                return Some(*choice);
+            }
+        }
+    }
+}
+
+impl<R: BufRead, W: Write> MessageBox for TerminalMessageBox<R, W> {
+    fn show(&mut self, severity: Severity, buttons: Buttons, message: &str) -> Choice {
+        // There is nowhere else to show the message if it cannot be written, so the failure is ignored.
+        let _ = writeln!(self.output, "{severity}: {message}");
+
+        // Messages that only have to be acknowledged do not wait for the user.
+        match buttons {
+            Buttons::Ok => Choice::Ok,
+            _ => self.read_choice(buttons.choices()).unwrap_or(buttons.dismissed()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn show(input: Option<&[u8]>, buttons: Buttons) -> (Choice, String) {
+        let mut output = vec![];
+        let choice = TerminalMessageBox::new(input, &mut output).show(Severity::Warning, buttons, "Quit the game?");
+        (choice, String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn choices_are_read_from_the_input() {
+        let (choice, output) = show(Some(b"maybe\nY\n"), Buttons::YesNo);
+        assert_eq!(choice, Choice::Yes);
+        assert_eq!(output, "Warning: Quit the game?\n(Yes/No) (Yes/No) ");
+
+        assert_eq!(show(Some(b" cancel \n"), Buttons::OkCancel).0, Choice::Cancel);
+        assert_eq!(show(Some(b"o\n"), Buttons::OkCancel).0, Choice::Ok);
+    }
+
+    #[test]
+    fn messages_are_dismissed_without_input() {
+        assert_eq!(show(Some(b"maybe\n"), Buttons::YesNo).0, Choice::No);
+        assert_eq!(
+            show(None, Buttons::OkCancel),
+            (Choice::Cancel, "Warning: Quit the game?\n".to_string())
+        );
+        assert_eq!(
+            show(Some(b""), Buttons::Ok),
+            (Choice::Ok, "Warning: Quit the game?\n".to_string())
+        );
+    }
+}
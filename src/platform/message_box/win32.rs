@@ -0,0 +1,46 @@
+use super::{Buttons, Choice, MessageBox, Severity};
+use std::{ffi::CString, ptr::null_mut};
+use winapi::um::winuser::*;
+
+const TITLE: &[u8] = b"lwar\0";
+
+/// Shows messages with `MessageBoxA`. The message box is task-modal and stays on top, as it is usually shown while
+/// the game's window is still open, e.g. after a fatal error.
+pub struct Win32MessageBox;
+
+impl MessageBox for Win32MessageBox {
+    fn show(&mut self, severity: Severity, buttons: Buttons, message: &str) -> Choice {
+        // Messages cannot contain nul characters, as they would end the message early.
+        let message = CString::new(message.replace('\0', " ")).unwrap();
+
+        let icon = match severity {
+            Severity::Info => MB_ICONINFORMATION,
+            Severity::Warning => MB_ICONWARNING,
+            Severity::Error => MB_ICONERROR,
+        };
+
+        let button_set = match buttons {
+            Buttons::Ok => MB_OK,
+            Buttons::OkCancel => MB_OKCANCEL,
+            Buttons::YesNo => MB_YESNO,
+        };
+
+        let result = unsafe {
+            MessageBoxA(
+                null_mut(),
+                message.as_ptr(),
+                TITLE.as_ptr() as *const i8,
+                icon | button_set | MB_TASKMODAL | MB_TOPMOST,
+            )
+        };
+
+        // `MessageBoxA` returns 0 if the message box could not be shown.
+        match result {
+            IDOK => Choice::Ok,
+            IDCANCEL => Choice::Cancel,
+            IDYES => Choice::Yes,
+            IDNO => Choice::No,
+            _ => buttons.dismissed(),
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use super::{terminal::TerminalMessageBox, Buttons, Choice, MessageBox, Severity};
+use std::{
+    io::{self, Write},
+    process::{Command, ExitStatus, Stdio},
+};
+
+/// The exit code of `xmessage` for the first button; the other buttons follow in order.
+const FIRST_BUTTON_CODE: i32 = 100;
+
+/// Shows messages with `xmessage`, which is part of the X.Org distribution, so that no toolkit is needed. Messages are
+/// written to the terminal instead if `xmessage` cannot be started.
+pub struct XMessageBox;
+
+impl MessageBox for XMessageBox {
+    fn show(&mut self, severity: Severity, buttons: Buttons, message: &str) -> Choice {
+        // `xmessage` exits with the code given for the chosen button; the codes start at 100, as `xmessage` itself
+        // exits with 1 if the window is closed.
+        let labels = buttons
+            .choices()
+            .iter()
+            .enumerate()
+            .map(|(index, choice)| format!("{choice}:{}", FIRST_BUTTON_CODE + index as i32))
+            .collect::<Vec<_>>()
+            .join(",");
+
+        match run(&format!("lwar - {severity}"), &labels, message) {
+            Ok(status) => choice(buttons, status.code()),
+            // The message is written to the terminal if `xmessage` is not installed, so that it is not lost.
+            Err(_) => TerminalMessageBox::stdio().show(severity, buttons, message),
+        }
+    }
+}
+
+/// Gets the choice of the button `xmessage` has exited with. Any other exit, e.g. because the window has been closed,
+/// dismisses the message box.
+fn choice(buttons: Buttons, exit_code: Option<i32>) -> Choice {
+    exit_code
+        .and_then(|code| code.checked_sub(FIRST_BUTTON_CODE))
+        .and_then(|index| usize::try_from(index).ok())
+        .and_then(|index| buttons.choices().get(index).copied())
+        .unwrap_or_else(|| buttons.dismissed())
+}
+
+/// Runs `xmessage`, passing the message through stdin, so that it cannot be mistaken for an option.
+fn run(title: &str, buttons: &str, message: &str) -> io::Result<ExitStatus> {
+    let mut child = Command::new("xmessage")
+        .args(["-center", "-title", title, "-buttons", buttons, "-file", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()?;
+
+    // Dropping stdin closes it, which ends the message.
+    let written = child
+        .stdin
+        .take()
+        .map_or(Ok(()), |mut stdin| stdin.write_all(message.as_bytes()));
+    let status = child.wait()?;
+    written.map(|()| status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exit_codes_are_mapped_to_choices() {
+        assert_eq!(choice(Buttons::YesNo, Some(100)), Choice::Yes);
+        assert_eq!(choice(Buttons::YesNo, Some(101)), Choice::No);
+        assert_eq!(choice(Buttons::OkCancel, Some(100)), Choice::Ok);
+
+        // Closing the window or being killed dismisses the message box instead of asking again on the terminal.
+        assert_eq!(choice(Buttons::OkCancel, Some(1)), Choice::Cancel);
+        assert_eq!(choice(Buttons::OkCancel, Some(102)), Choice::Cancel);
+        assert_eq!(choice(Buttons::YesNo, None), Choice::No);
+        assert_eq!(choice(Buttons::Ok, Some(1)), Choice::Ok);
+    }
+}
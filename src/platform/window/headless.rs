@@ -0,0 +1,246 @@
+use super::{Event, Window};
+use crate::platform::{
+    error::{self, Error},
+    input::{Key, MouseButton},
+};
+use std::{collections::VecDeque, fmt::Debug, fs, path::Path, str::FromStr};
+
+/// A window without an operating system counterpart that replays scripted events, one frame per call to
+/// `handle_events`. Once all frames have been replayed, the window requests to be closed.
+pub struct HeadlessWindow {
+    size: (u32, u32),
+    frames: VecDeque<Vec<Event>>,
+}
+
+impl HeadlessWindow {
+    pub fn new(width: u32, height: u32) -> HeadlessWindow {
+        HeadlessWindow {
+            size: (width, height),
+            frames: VecDeque::new(),
+        }
+    }
+
+    pub fn load(width: u32, height: u32, path: impl AsRef<Path>) -> error::Result<HeadlessWindow> {
+        let path = path.as_ref();
+        let script = fs::read_to_string(path)
+            .map_err(|e| Error::other(format!("Failed to read event script '{}'.", path.display()), e.to_string()))?;
+
+        HeadlessWindow::from_script(width, height, &script)
+    }
+
+    /// Parses an event script consisting of one event per line, written like the `Event` variants, e.g.
+    /// `KeyPressed W 17`, `MouseMoved 10 20` or `CharacterEntered 'a'`. A `Frame` line starts a new frame; it can
+    /// optionally be followed by the number of frames to start, all but the last of which remain empty. Empty lines
+    /// and lines starting with `#` are ignored.
+    pub fn from_script(width: u32, height: u32, script: &str) -> error::Result<HeadlessWindow> {
+        let mut window = HeadlessWindow::new(width, height);
+
+        for (line_number, line) in script.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (name, arguments) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+            let result = if name == "Frame" {
+                parse_arguments::<u32>(arguments).map(|count| {
+                    let count = count.first().copied().unwrap_or(1);
+                    window.frames.extend((0..count).map(|_| vec![]));
+                })
+            } else {
+                parse_event(name, arguments.trim()).map(|event| window.push_event(event))
+            };
+
+            result.map_err(|e| {
+                Error::other(
+                    "Failed to parse event script.",
+                    format!("Invalid event in line {}: {e}", line_number + 1),
+                )
+            })?;
+        }
+
+        Ok(window)
+    }
+
+    /// Appends a frame during which the given events occur.
+    pub fn push_frame(&mut self, events: impl IntoIterator<Item = Event>) {
+        self.frames.push_back(events.into_iter().collect());
+    }
+
+    /// Adds the event to the last frame that has been pushed.
+    pub fn push_event(&mut self, event: Event) {
+        match self.frames.back_mut() {
+            Some(frame) => frame.push(event),
+            None => self.frames.push_back(vec![event]),
+        }
+    }
+}
+
+impl Window for HeadlessWindow {
+    fn handle_events(&mut self, mut handle_event: impl FnMut(Event)) {
+        match self.frames.pop_front() {
+            Some(events) => {
+                for event in events {
+                    if let Event::Resized(width, height) = event {
+                        self.size = (width, height);
+                    }
+
+                    handle_event(event);
+                }
+            }
+            None => handle_event(Event::CloseRequested),
+        }
+    }
+
+    fn size(&self) -> (u32, u32) {
+        self.size
+    }
+}
+
+fn parse_event(name: &str, arguments: &str) -> Result<Event, String> {
+    Ok(match name {
+        "CloseRequested" => Event::CloseRequested,
+        "Resized" => {
+            let [width, height] = parse_exactly(arguments)?;
+            Event::Resized(width, height)
+        }
+        "KeyPressed" | "KeyReleased" => {
+            let [key, scan_code] = parse_exactly::<String, 2>(arguments)?;
+            let key = parse_name(&key, (0..=u8::MAX as i32).filter_map(|code| Key::try_from(code).ok()))?;
+            let scan_code = scan_code.parse().map_err(|_| format!("Invalid scan code '{scan_code}'."))?;
+
+            if name == "KeyPressed" {
+                Event::KeyPressed(key, scan_code)
+            } else {
+                Event::KeyReleased(key, scan_code)
+            }
+        }
+        "CharacterEntered" => {
+            let mut characters = arguments
+                .strip_prefix('\'')
+                .and_then(|character| character.strip_suffix('\''))
+                .ok_or_else(|| format!("Expected a quoted character, but found '{arguments}'."))?
+                .chars();
+
+            match (characters.next(), characters.next()) {
+                (Some(character), None) => Event::CharacterEntered(character),
+                _ => return Err(format!("Expected a single character, but found {arguments}.")),
+            }
+        }
+        "MouseMoved" => {
+            let [x, y] = parse_exactly(arguments)?;
+            Event::MouseMoved(x, y)
+        }
+        "MousePressed" | "MouseReleased" => {
+            let button = parse_name(arguments, (0..5).filter_map(|code| MouseButton::try_from(code).ok()))?;
+            if name == "MousePressed" {
+                Event::MousePressed(button)
+            } else {
+                Event::MouseReleased(button)
+            }
+        }
+        "MouseWheel" => {
+            let [delta] = parse_exactly(arguments)?;
+            Event::MouseWheel(delta)
+        }
+        "FocusGained" => Event::FocusGained,
+        "FocusLost" => Event::FocusLost,
+        _ => return Err(format!("Unknown event '{name}'.")),
+    })
+}
+
+fn parse_arguments<T: FromStr>(arguments: &str) -> Result<Vec<T>, String> {
+    arguments
+        .split_whitespace()
+        .map(|argument| argument.parse().map_err(|_| format!("Invalid argument '{argument}'.")))
+        .collect()
+}
+
+fn parse_exactly<T: FromStr, const N: usize>(arguments: &str) -> Result<[T; N], String> {
+    parse_arguments(arguments)?
+        .try_into()
+        .map_err(|arguments: Vec<T>| format!("Expected {N} argument(s), but found {}.", arguments.len()))
+}
+
+fn parse_name<T: Debug>(name: &str, mut values: impl Iterator<Item = T>) -> Result<T, String> {
+    values
+        .find(|value| format!("{value:?}") == name)
+        .ok_or_else(|| format!("Unknown key or mouse button '{name}'."))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn replay(window: &mut HeadlessWindow) -> Vec<Vec<Event>> {
+        let mut frames = vec![];
+        loop {
+            let mut events = vec![];
+            window.handle_events(|event| events.push(event));
+            if events == [Event::CloseRequested] {
+                return frames;
+            }
+
+            frames.push(events);
+        }
+    }
+
+    #[test]
+    fn scripts_are_replayed_frame_by_frame() {
+        let mut window = HeadlessWindow::from_script(
+            640,
+            480,
+            "# The first frame starts implicitly.
+            KeyPressed W 17
+            MouseMoved 10 20
+
+            Frame 3
+            Resized 800 600
+            CharacterEntered ' '
+            Frame
+            MousePressed Left
+            FocusLost",
+        )
+        .unwrap();
+
+        assert_eq!(
+            replay(&mut window),
+            [
+                vec![Event::KeyPressed(Key::W, 17), Event::MouseMoved(10, 20)],
+                vec![],
+                vec![],
+                vec![Event::Resized(800, 600), Event::CharacterEntered(' ')],
+                vec![Event::MousePressed(MouseButton::Left), Event::FocusLost],
+            ]
+        );
+        assert_eq!(window.size(), (800, 600));
+    }
+
+    #[test]
+    fn invalid_scripts_are_reported() {
+        let error = |script| HeadlessWindow::from_script(1, 1, script).err().unwrap();
+        assert_eq!(
+            error("Frame\n\nResized 800"),
+            Error::other(
+                "Failed to parse event script.",
+                "Invalid event in line 3: Expected 2 argument(s), but found 1."
+            )
+        );
+        assert_eq!(
+            error("Jump").to_string(),
+            "Failed to parse event script. Invalid event in line 1: Unknown event 'Jump'."
+        );
+        assert_eq!(
+            error("KeyPressed Super 91").to_string(),
+            "Failed to parse event script. Invalid event in line 1: Unknown key or mouse button 'Super'."
+        );
+        assert_eq!(
+            error("CharacterEntered 'ab'").to_string(),
+            "Failed to parse event script. Invalid event in line 1: Expected a single character, but found 'ab'."
+        );
+        assert_eq!(
+            HeadlessWindow::load(1, 1, "missing.events").err().unwrap().operation(),
+            "Failed to read event script 'missing.events'."
+        );
+    }
+}
@@ -0,0 +1,302 @@
+use super::{Event, Window};
+use crate::{
+    debug, error,
+    log::Category,
+    platform::{
+        error::{Error, Result},
+        input::{key_from_virtual_key, Key, MouseButton, PhysicalKey},
+    },
+};
+use core::{mem::size_of, ptr};
+use std::ptr::{null, null_mut};
+use winapi::{
+    shared::{minwindef::*, windef::*},
+    um::{libloaderapi::GetModuleHandleA, winuser::*},
+};
+
+const WINDOW_TITLE: *const i8 = b"lwar\0".as_ptr() as *const i8;
+
+pub struct Win32Window {
+    hwnd: HWND,
+}
+
+impl Win32Window {
+    pub fn new() -> Result<Win32Window> {
+        unsafe {
+            let wnd_class = WNDCLASSA {
+                lpfnWndProc: Some(wnd_proc),
+                lpszClassName: WINDOW_TITLE,
+                hInstance: GetModuleHandleA(ptr::null()),
+                ..Default::default()
+            };
+
+            if RegisterClassA(&wnd_class) == 0 {
+                return Err(Error::last_os_error("Failed to register window class."));
+            }
+
+            let device = RAWINPUTDEVICE {
+                usUsagePage: 0x01, // keyboard
+                usUsage: 0x06,     // keyboard
+                ..Default::default()
+            };
+
+            if RegisterRawInputDevices(&device, 1, size_of::<RAWINPUTDEVICE>() as u32) == 0 {
+                return Err(Error::last_os_error("Failed to register raw input device."));
+            };
+
+            let hwnd = CreateWindowExA(
+                0,
+                WINDOW_TITLE,
+                WINDOW_TITLE,
+                WS_OVERLAPPEDWINDOW | WS_VISIBLE,
+                CW_USEDEFAULT,
+                CW_USEDEFAULT,
+                CW_USEDEFAULT,
+                CW_USEDEFAULT,
+                ptr::null_mut(),
+                ptr::null_mut(),
+                GetModuleHandleA(ptr::null()),
+                null_mut(),
+            );
+
+            if hwnd.is_null() {
+                return Err(Error::last_os_error("Failed to create window."));
+            }
+
+            if !cfg!(debug_assertions) {
+                toggle_fullscreen(hwnd)?;
+            }
+
+            Ok(Win32Window { hwnd })
+        }
+    }
+
+    pub fn hwnd(&self) -> HWND {
+        self.hwnd
+    }
+}
+
+impl Window for Win32Window {
+    fn handle_events(&mut self, mut handle_event: impl FnMut(Event)) {
+        let old_size = self.size();
+
+        unsafe {
+            let mut handler: &mut dyn FnMut(Event) = &mut handle_event;
+            SetWindowLongPtrA(self.hwnd, GWLP_USERDATA, &mut handler as *mut _ as isize);
+
+            let mut msg: MSG = Default::default();
+            while PeekMessageA(&mut msg, null_mut(), 0, 0, PM_REMOVE) != 0 {
+                TranslateMessage(&msg);
+                DispatchMessageA(&msg);
+            }
+
+            SetWindowLongPtrA(self.hwnd, GWLP_USERDATA, 0);
+        }
+
+        let new_size = self.size();
+        if old_size != new_size && unsafe { IsIconic(self.hwnd) } == 0 {
+            handle_event(Event::Resized(new_size.0, new_size.1));
+        }
+    }
+
+    fn size(&self) -> (u32, u32) {
+        let mut rect = RECT::default();
+        if unsafe { GetClientRect(self.hwnd, &mut rect) } == 0 {
+            panic!("{}", Error::last_os_error("Failed to retrieve window size."));
+        }
+
+        (rect.right as u32 - rect.left as u32, rect.bottom as u32 - rect.top as u32)
+    }
+
+    fn key_label(&self, key: PhysicalKey) -> String {
+        // The scan code is passed in bits 16 to 23 and the E0 prefix in bit 24.
+        let scan_code = key.scan_code();
+        let lparam = (((scan_code & 0xFF) << 16) | ((scan_code & 0x100) << 16)) as i32;
+
+        let mut buffer = [0u16; 64];
+        let length = unsafe { GetKeyNameTextW(lparam, buffer.as_mut_ptr(), buffer.len() as i32) };
+        if length <= 0 {
+            return format!("{key:?}");
+        }
+
+        String::from_utf16_lossy(&buffer[..length as usize])
+    }
+
+    fn key_at(&self, key: PhysicalKey) -> Option<Key> {
+        // Scan codes with an E0 prefix are passed with 0xE0 in the high byte.
+        let scan_code = key.scan_code();
+        let is_e0 = scan_code & 0x100 != 0;
+        let virtual_key = unsafe { MapVirtualKeyW((scan_code & 0xFF) | if is_e0 { 0xE000 } else { 0 }, MAPVK_VSC_TO_VK_EX) };
+        key_from_virtual_key(virtual_key as i32, is_e0)
+    }
+}
+
+impl Drop for Win32Window {
+    fn drop(&mut self) {
+        unsafe {
+            SetWindowLongPtrA(self.hwnd, GWLP_USERDATA, 0);
+            CloseWindow(self.hwnd);
+            UnregisterClassA(WINDOW_TITLE, GetModuleHandleA(null()));
+        };
+    }
+}
+
+unsafe fn toggle_fullscreen(hwnd: HWND) -> Result<()> {
+    let style = GetWindowLongPtrA(hwnd, GWL_STYLE);
+    if style == 0 {
+        return Err(Error::last_os_error("Failed to retrieve window style."));
+    }
+
+    let is_fullscreen = (style & WS_THICKFRAME as isize) != WS_THICKFRAME as isize;
+
+    if is_fullscreen {
+        let style = style | WS_OVERLAPPEDWINDOW as isize;
+        if SetWindowLongPtrA(hwnd, GWL_STYLE, style) == 0 {
+            return Err(Error::last_os_error("Failed to set new window style."));
+        }
+
+        ShowWindow(hwnd, SW_RESTORE);
+    } else {
+        let style = style & !WS_OVERLAPPEDWINDOW as isize;
+        if SetWindowLongPtrA(hwnd, GWL_STYLE, style) == 0 {
+            return Err(Error::last_os_error("Failed to set fullscreen window style."));
+        }
+
+        if IsZoomed(hwnd) != 0 {
+            // Necessary to get rid of the taskbar.
+            ShowWindow(hwnd, SW_RESTORE);
+        }
+
+        ShowWindow(hwnd, SW_SHOWMAXIMIZED);
+    }
+
+    Ok(())
+}
+
+unsafe extern "system" fn wnd_proc(hwnd: HWND, msg: UINT, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    let event_ptr = if msg == WM_CREATE {
+        (*(lparam as *const CREATESTRUCTA)).lpCreateParams
+    } else {
+        GetWindowLongPtrA(hwnd, GWLP_USERDATA) as *mut _
+    };
+
+    if event_ptr.is_null() {
+        return DefWindowProcA(hwnd, msg, wparam, lparam);
+    }
+
+    let handle_event: &mut &mut dyn FnMut(Event) = &mut *(event_ptr as *mut _);
+
+    match msg {
+        WM_INPUT => handle_keyboard_input(lparam, handle_event),
+        WM_SYSKEYDOWN if wparam == VK_RETURN as usize && (lparam & 0x60000000) == 0x20000000 => {
+            // The window simply keeps its current style if it cannot be changed.
+            if let Err(e) = toggle_fullscreen(hwnd) {
+                error!(Category::Platform, "{e}");
+            }
+        }
+        WM_SETFOCUS => handle_event(Event::FocusGained),
+        WM_KILLFOCUS => handle_event(Event::FocusLost),
+        WM_CLOSE => {
+            handle_event(Event::CloseRequested);
+            return 0;
+        }
+        WM_GETMINMAXINFO => {
+            let info = &mut *(lparam as *mut MINMAXINFO);
+            info.ptMinTrackSize.x = 640;
+            info.ptMinTrackSize.y = 480;
+        }
+        WM_MOUSEMOVE => handle_event(Event::MouseMoved(LOWORD(lparam as u32) as u32, HIWORD(lparam as u32) as u32)),
+        WM_LBUTTONDOWN => handle_event(Event::MousePressed(MouseButton::Left)),
+        WM_LBUTTONUP => handle_event(Event::MouseReleased(MouseButton::Left)),
+        WM_RBUTTONDOWN => handle_event(Event::MousePressed(MouseButton::Right)),
+        WM_RBUTTONUP => handle_event(Event::MouseReleased(MouseButton::Right)),
+        WM_MBUTTONDOWN => handle_event(Event::MousePressed(MouseButton::Middle)),
+        WM_MBUTTONUP => handle_event(Event::MouseReleased(MouseButton::Middle)),
+        WM_XBUTTONDOWN if HIWORD(wparam as u32) == XBUTTON1 => handle_event(Event::MousePressed(MouseButton::XButton1)),
+        WM_XBUTTONDOWN if HIWORD(wparam as u32) == XBUTTON2 => handle_event(Event::MousePressed(MouseButton::XButton2)),
+        WM_XBUTTONUP if HIWORD(wparam as u32) == XBUTTON1 => handle_event(Event::MouseReleased(MouseButton::XButton1)),
+        WM_XBUTTONUP if HIWORD(wparam as u32) == XBUTTON2 => handle_event(Event::MouseReleased(MouseButton::XButton2)),
+        WM_MOUSEWHEEL => handle_event(Event::MouseWheel((GET_WHEEL_DELTA_WPARAM(wparam) / WHEEL_DELTA) as i32)),
+        WM_CHAR => {
+            if let Some(character) = char::from_u32(wparam as u32) {
+                handle_event(Event::CharacterEntered(character))
+            }
+        }
+        _ => (),
+    };
+
+    DefWindowProcA(hwnd, msg, wparam, lparam)
+}
+
+unsafe fn handle_keyboard_input(lparam: LPARAM, handle_event: &mut dyn FnMut(Event)) {
+    let mut input = RAWINPUT::default();
+    let mut size = size_of::<RAWINPUT>() as u32;
+    let success = GetRawInputData(
+        lparam as HRAWINPUT,
+        RID_INPUT,
+        &mut input as *mut _ as *mut _,
+        &mut size as *mut _,
+        size_of::<RAWINPUTHEADER>() as u32,
+    );
+
+    if success == u32::MAX {
+        // Dropping a single input event is preferable to terminating the game.
+        error!(
+            Category::Input,
+            "{}",
+            Error::last_os_error("Failed to read raw keyboard input.")
+        );
+        return;
+    }
+
+    // Extract keyboard raw input data; see http://molecularmusings.wordpress.com/2011/09/05/properly-handling-keyboard-input/.
+    if input.header.dwType == RIM_TYPEKEYBOARD {
+        let mut virtual_key = input.data.keyboard().VKey as i32;
+        let mut scan_code = input.data.keyboard().MakeCode as u32;
+        let flags = input.data.keyboard().Flags as u32;
+
+        let released = (flags & RI_KEY_BREAK) != 0;
+
+        if virtual_key == 255 {
+            return;
+        }
+
+        if virtual_key == VK_SHIFT {
+            virtual_key = MapVirtualKeyA(scan_code, MAPVK_VSC_TO_VK_EX) as i32;
+        } else if virtual_key == VK_NUMLOCK {
+            scan_code = MapVirtualKeyA(virtual_key as u32, MAPVK_VK_TO_VSC) | 0x100;
+        }
+
+        let is_e0 = (flags & RI_KEY_E0) != 0;
+        let is_e1 = (flags & RI_KEY_E1) != 0;
+
+        if is_e1 {
+            if virtual_key == VK_PAUSE {
+                scan_code = 0x45;
+            } else {
+                scan_code = MapVirtualKeyA(virtual_key as u32, MAPVK_VK_TO_VSC);
+            }
+        }
+
+        // The E0 prefix is kept in the scan code, as it distinguishes the navigation keys from the numpad keys.
+        if is_e0 {
+            scan_code |= 0x100;
+        }
+
+        let key = key_from_virtual_key(virtual_key, is_e0);
+        if key.is_none() {
+            debug!(
+                Category::Input,
+                "An unknown key was pressed. Virtual key code: '{virtual_key}'."
+            );
+        }
+
+        if let Some(key) = key {
+            if released {
+                handle_event(Event::KeyReleased(key, scan_code));
+            } else {
+                handle_event(Event::KeyPressed(key, scan_code));
+            }
+        }
+    }
+}
@@ -0,0 +1,712 @@
+use super::{Event, Window};
+use crate::{
+    debug, error,
+    log::Category,
+    platform::{
+        error::{Error, Result},
+        input::{Key, MouseButton, PhysicalKey},
+    },
+};
+use std::{
+    ffi::{c_char, c_int, c_long, c_uint, c_ulong, c_void, CStr},
+    mem::{transmute, zeroed},
+    ptr::{null, null_mut},
+    rc::Rc,
+};
+
+const WINDOW_TITLE: &[u8] = b"lwar\0";
+const DEFAULT_SIZE: (u32, u32) = (1280, 720);
+const MIN_SIZE: (c_int, c_int) = (640, 480);
+
+type XWindow = c_ulong;
+type Atom = c_ulong;
+type KeySym = c_ulong;
+type Bool = c_int;
+
+const KEY_PRESS: c_int = 2;
+const KEY_RELEASE: c_int = 3;
+const BUTTON_PRESS: c_int = 4;
+const BUTTON_RELEASE: c_int = 5;
+const MOTION_NOTIFY: c_int = 6;
+const FOCUS_IN: c_int = 9;
+const FOCUS_OUT: c_int = 10;
+const CLIENT_MESSAGE: c_int = 33;
+
+const KEY_PRESS_MASK: c_long = 1 << 0;
+const KEY_RELEASE_MASK: c_long = 1 << 1;
+const BUTTON_PRESS_MASK: c_long = 1 << 2;
+const BUTTON_RELEASE_MASK: c_long = 1 << 3;
+const POINTER_MOTION_MASK: c_long = 1 << 6;
+const STRUCTURE_NOTIFY_MASK: c_long = 1 << 17;
+const SUBSTRUCTURE_NOTIFY_MASK: c_long = 1 << 19;
+const SUBSTRUCTURE_REDIRECT_MASK: c_long = 1 << 20;
+const FOCUS_CHANGE_MASK: c_long = 1 << 21;
+
+const MOD1_MASK: c_uint = 1 << 3; // usually the Alt key
+const P_MIN_SIZE: c_long = 1 << 4;
+const XA_ATOM: Atom = 4;
+const PROP_MODE_REPLACE: c_int = 0;
+const Z_PIXMAP: c_int = 2;
+const LSB_FIRST: c_int = 0;
+const NET_WM_STATE_TOGGLE: c_long = 2;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct XKeyEvent {
+    kind: c_int,
+    serial: c_ulong,
+    send_event: Bool,
+    display: *mut c_void,
+    window: XWindow,
+    root: XWindow,
+    subwindow: XWindow,
+    time: c_ulong,
+    x: c_int,
+    y: c_int,
+    x_root: c_int,
+    y_root: c_int,
+    state: c_uint,
+    // The button for button events, the key code for key events.
+    detail: c_uint,
+    same_screen: Bool,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct XClientMessageEvent {
+    kind: c_int,
+    serial: c_ulong,
+    send_event: Bool,
+    display: *mut c_void,
+    window: XWindow,
+    message_type: Atom,
+    format: c_int,
+    data: [c_long; 5],
+}
+
+#[repr(C)]
+union XEvent {
+    kind: c_int,
+    // Key, button, and motion events share the same layout up to the fields that are used.
+    key: XKeyEvent,
+    client_message: XClientMessageEvent,
+    pad: [c_long; 24],
+}
+
+#[repr(C)]
+struct XSizeHints {
+    flags: c_long,
+    x: c_int,
+    y: c_int,
+    width: c_int,
+    height: c_int,
+    min_width: c_int,
+    min_height: c_int,
+    max_width: c_int,
+    max_height: c_int,
+    width_inc: c_int,
+    height_inc: c_int,
+    min_aspect: [c_int; 2],
+    max_aspect: [c_int; 2],
+    base_width: c_int,
+    base_height: c_int,
+    win_gravity: c_int,
+}
+
+#[repr(C)]
+struct XImage {
+    width: c_int,
+    height: c_int,
+    xoffset: c_int,
+    format: c_int,
+    data: *mut c_char,
+    byte_order: c_int,
+    bitmap_unit: c_int,
+    bitmap_bit_order: c_int,
+    bitmap_pad: c_int,
+    depth: c_int,
+    bytes_per_line: c_int,
+    bits_per_pixel: c_int,
+    red_mask: c_ulong,
+    green_mask: c_ulong,
+    blue_mask: c_ulong,
+    obdata: *mut c_char,
+    functions: [*mut c_void; 6],
+}
+
+extern "C" {
+    fn dlopen(filename: *const c_char, flags: c_int) -> *mut c_void;
+    fn dlsym(handle: *mut c_void, symbol: *const c_char) -> *mut c_void;
+    fn dlclose(handle: *mut c_void) -> c_int;
+}
+
+const RTLD_NOW: c_int = 2;
+
+// Xlib is loaded at runtime so that the game can be built without the X11 development files being installed.
+macro_rules! xlib {
+    ($($name:ident: fn($($argument:ty),*) -> $result:ty;)*) => {
+        #[allow(non_snake_case)]
+        struct Xlib {
+            library: *mut c_void,
+            $($name: unsafe extern "C" fn($($argument),*) -> $result,)*
+        }
+
+        impl Xlib {
+            fn load() -> Result<Xlib> {
+                unsafe {
+                    let library = dlopen(b"libX11.so.6\0".as_ptr() as _, RTLD_NOW);
+                    if library.is_null() {
+                        return Err(Error::other("Failed to load the X11 client library 'libX11.so.6'.", ""));
+                    }
+
+                    Ok(Xlib {
+                        library,
+                        $($name: {
+                            let symbol = dlsym(library, concat!(stringify!($name), "\0").as_ptr() as _);
+                            if symbol.is_null() {
+                                dlclose(library);
+                                return Err(Error::other(
+                                    format!("Failed to load X11 function '{}'.", stringify!($name)),
+                                    "",
+                                ));
+                            }
+                            transmute::<*mut c_void, unsafe extern "C" fn($($argument),*) -> $result>(symbol)
+                        },)*
+                    })
+                }
+            }
+        }
+    };
+}
+
+xlib! {
+    XOpenDisplay: fn(*const c_char) -> *mut c_void;
+    XCloseDisplay: fn(*mut c_void) -> c_int;
+    XDefaultScreen: fn(*mut c_void) -> c_int;
+    XRootWindow: fn(*mut c_void, c_int) -> XWindow;
+    XDefaultDepth: fn(*mut c_void, c_int) -> c_int;
+    XDefaultGC: fn(*mut c_void, c_int) -> *mut c_void;
+    XBlackPixel: fn(*mut c_void, c_int) -> c_ulong;
+    XCreateSimpleWindow: fn(*mut c_void, XWindow, c_int, c_int, c_uint, c_uint, c_uint, c_ulong, c_ulong) -> XWindow;
+    XDestroyWindow: fn(*mut c_void, XWindow) -> c_int;
+    XStoreName: fn(*mut c_void, XWindow, *const c_char) -> c_int;
+    XSelectInput: fn(*mut c_void, XWindow, c_long) -> c_int;
+    XMapWindow: fn(*mut c_void, XWindow) -> c_int;
+    XInternAtom: fn(*mut c_void, *const c_char, Bool) -> Atom;
+    XSetWMProtocols: fn(*mut c_void, XWindow, *mut Atom, c_int) -> c_int;
+    XSetWMNormalHints: fn(*mut c_void, XWindow, *mut XSizeHints) -> ();
+    XChangeProperty: fn(*mut c_void, XWindow, Atom, Atom, c_int, c_int, *const u8, c_int) -> c_int;
+    XSendEvent: fn(*mut c_void, XWindow, Bool, c_long, *mut XEvent) -> c_int;
+    XPending: fn(*mut c_void) -> c_int;
+    XNextEvent: fn(*mut c_void, *mut XEvent) -> c_int;
+    XLookupKeysym: fn(*mut XKeyEvent, c_int) -> KeySym;
+    XLookupString: fn(*mut XKeyEvent, *mut c_char, c_int, *mut KeySym, *mut c_void) -> c_int;
+    XkbSetDetectableAutoRepeat: fn(*mut c_void, Bool, *mut Bool) -> Bool;
+    XkbKeycodeToKeysym: fn(*mut c_void, u8, c_int, c_int) -> KeySym;
+    XKeysymToString: fn(KeySym) -> *mut c_char;
+    XGetGeometry: fn(
+        *mut c_void,
+        XWindow,
+        *mut XWindow,
+        *mut c_int,
+        *mut c_int,
+        *mut c_uint,
+        *mut c_uint,
+        *mut c_uint,
+        *mut c_uint
+    ) -> c_int;
+    XInitImage: fn(*mut XImage) -> c_int;
+    XPutImage: fn(*mut c_void, XWindow, *mut c_void, *mut XImage, c_int, c_int, c_int, c_int, c_uint, c_uint) -> c_int;
+    XFlush: fn(*mut c_void) -> c_int;
+}
+
+/// The connection to the X server, shared by the window and its presenter.
+struct Connection {
+    xlib: Xlib,
+    display: *mut c_void,
+    screen: c_int,
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        unsafe {
+            (self.xlib.XCloseDisplay)(self.display);
+            dlclose(self.xlib.library);
+        }
+    }
+}
+
+pub struct X11Window {
+    connection: Rc<Connection>,
+    window: XWindow,
+    size: (u32, u32),
+    wm_delete_window: Atom,
+    net_wm_state: Atom,
+    net_wm_state_fullscreen: Atom,
+}
+
+/// Copies the pixels of a software-rendered frame into the window.
+pub struct X11Presenter {
+    connection: Rc<Connection>,
+    window: XWindow,
+}
+
+impl X11Window {
+    pub fn new() -> Result<X11Window> {
+        unsafe {
+            let xlib = Xlib::load()?;
+            let display = (xlib.XOpenDisplay)(null());
+            if display.is_null() {
+                dlclose(xlib.library);
+                return Err(Error::other(
+                    "Failed to connect to the X server.",
+                    "Is the DISPLAY environment variable set?",
+                ));
+            }
+
+            let screen = (xlib.XDefaultScreen)(display);
+            let connection = Rc::new(Connection { xlib, display, screen });
+            let xlib = &connection.xlib;
+
+            let window = (xlib.XCreateSimpleWindow)(
+                display,
+                (xlib.XRootWindow)(display, screen),
+                0,
+                0,
+                DEFAULT_SIZE.0,
+                DEFAULT_SIZE.1,
+                0,
+                (xlib.XBlackPixel)(display, screen),
+                (xlib.XBlackPixel)(display, screen),
+            );
+
+            if window == 0 {
+                return Err(Error::other("Failed to create window.", ""));
+            }
+
+            (xlib.XStoreName)(display, window, WINDOW_TITLE.as_ptr() as _);
+            (xlib.XSelectInput)(
+                display,
+                window,
+                KEY_PRESS_MASK
+                    | KEY_RELEASE_MASK
+                    | BUTTON_PRESS_MASK
+                    | BUTTON_RELEASE_MASK
+                    | POINTER_MOTION_MASK
+                    | STRUCTURE_NOTIFY_MASK
+                    | FOCUS_CHANGE_MASK,
+            );
+
+            let mut size_hints: XSizeHints = zeroed();
+            size_hints.flags = P_MIN_SIZE;
+            size_hints.min_width = MIN_SIZE.0;
+            size_hints.min_height = MIN_SIZE.1;
+            (xlib.XSetWMNormalHints)(display, window, &mut size_hints);
+
+            // Key repeats should only generate additional key presses like on Windows, not releases in between.
+            (xlib.XkbSetDetectableAutoRepeat)(display, 1, null_mut());
+
+            let intern_atom = |name: &[u8]| (xlib.XInternAtom)(display, name.as_ptr() as _, 0);
+            let mut wm_delete_window = intern_atom(b"WM_DELETE_WINDOW\0");
+            (xlib.XSetWMProtocols)(display, window, &mut wm_delete_window, 1);
+
+            let window = X11Window {
+                window,
+                size: DEFAULT_SIZE,
+                wm_delete_window,
+                net_wm_state: intern_atom(b"_NET_WM_STATE\0"),
+                net_wm_state_fullscreen: intern_atom(b"_NET_WM_STATE_FULLSCREEN\0"),
+                connection: connection.clone(),
+            };
+
+            // The window manager ignores state change requests for windows that are not mapped yet, so the initial
+            // fullscreen state has to be set directly.
+            if !cfg!(debug_assertions) {
+                (xlib.XChangeProperty)(
+                    display,
+                    window.window,
+                    window.net_wm_state,
+                    XA_ATOM,
+                    32,
+                    PROP_MODE_REPLACE,
+                    &window.net_wm_state_fullscreen as *const Atom as *const u8,
+                    1,
+                );
+            }
+
+            (xlib.XMapWindow)(display, window.window);
+            (xlib.XFlush)(display);
+            Ok(window)
+        }
+    }
+
+    pub fn presenter(&self) -> X11Presenter {
+        X11Presenter {
+            connection: self.connection.clone(),
+            window: self.window,
+        }
+    }
+
+    /// Asks the window manager to toggle between a borderless fullscreen window and a regular one.
+    fn toggle_fullscreen(&self) {
+        let connection = &self.connection;
+        unsafe {
+            let mut event = XEvent {
+                client_message: XClientMessageEvent {
+                    kind: CLIENT_MESSAGE,
+                    serial: 0,
+                    send_event: 1,
+                    display: connection.display,
+                    window: self.window,
+                    message_type: self.net_wm_state,
+                    format: 32,
+                    data: [NET_WM_STATE_TOGGLE, self.net_wm_state_fullscreen as c_long, 0, 1, 0],
+                },
+            };
+
+            (connection.xlib.XSendEvent)(
+                connection.display,
+                (connection.xlib.XRootWindow)(connection.display, connection.screen),
+                0,
+                SUBSTRUCTURE_REDIRECT_MASK | SUBSTRUCTURE_NOTIFY_MASK,
+                &mut event,
+            );
+        }
+    }
+
+    unsafe fn handle_key(&self, event: &mut XKeyEvent, handle_event: &mut dyn FnMut(Event)) {
+        let xlib = &self.connection.xlib;
+        let pressed = event.kind == KEY_PRESS;
+
+        // Look up the unshifted key symbol first, falling back to the one of the keypad keys with NumLock enabled.
+        let key = key_from_keysym((xlib.XLookupKeysym)(event, 0)).or_else(|| key_from_keysym((xlib.XLookupKeysym)(event, 1)));
+
+        // X11 key codes are offset by 8 from the Linux evdev codes, which are mapped to the scan codes that are
+        // reported on Windows. Keys without a scan code are reported with a scan code of 0.
+        let scan_code = PhysicalKey::from_evdev_code(event.detail.saturating_sub(8)).map_or(0, PhysicalKey::scan_code);
+
+        if pressed && key == Some(Key::Return) && event.state & MOD1_MASK != 0 {
+            self.toggle_fullscreen();
+        }
+
+        match key {
+            Some(key) if pressed => handle_event(Event::KeyPressed(key, scan_code)),
+            Some(key) => handle_event(Event::KeyReleased(key, scan_code)),
+            None => debug!(Category::Input, "An unknown key was pressed. Key code: '{}'.", event.detail),
+        }
+
+        if pressed {
+            // `XLookupString` only supports Latin-1, whose code points coincide with the first Unicode code points.
+            let mut buffer = [0u8; 8];
+            let length = (xlib.XLookupString)(event, buffer.as_mut_ptr() as _, buffer.len() as c_int, null_mut(), null_mut());
+            for &character in &buffer[..length.clamp(0, buffer.len() as c_int) as usize] {
+                handle_event(Event::CharacterEntered(character as char));
+            }
+        }
+    }
+}
+
+impl Window for X11Window {
+    fn handle_events(&mut self, mut handle_event: impl FnMut(Event)) {
+        let old_size = self.size;
+        let connection = self.connection.clone();
+        let xlib = &connection.xlib;
+
+        unsafe {
+            while (xlib.XPending)(connection.display) > 0 {
+                let mut event: XEvent = zeroed();
+                (xlib.XNextEvent)(connection.display, &mut event);
+
+                match event.kind {
+                    KEY_PRESS | KEY_RELEASE => self.handle_key(&mut event.key, &mut handle_event),
+                    BUTTON_PRESS | BUTTON_RELEASE => {
+                        let pressed = event.kind == BUTTON_PRESS;
+                        let button = match event.key.detail {
+                            1 => Some(MouseButton::Left),
+                            2 => Some(MouseButton::Middle),
+                            3 => Some(MouseButton::Right),
+                            8 => Some(MouseButton::XButton1),
+                            9 => Some(MouseButton::XButton2),
+                            // X11 reports each step of the mouse wheel as a press and release of buttons 4 and 5.
+                            4 if pressed => {
+                                handle_event(Event::MouseWheel(1));
+                                None
+                            }
+                            5 if pressed => {
+                                handle_event(Event::MouseWheel(-1));
+                                None
+                            }
+                            _ => None,
+                        };
+
+                        match button {
+                            Some(button) if pressed => handle_event(Event::MousePressed(button)),
+                            Some(button) => handle_event(Event::MouseReleased(button)),
+                            None => (),
+                        }
+                    }
+                    FOCUS_IN => handle_event(Event::FocusGained),
+                    FOCUS_OUT => handle_event(Event::FocusLost),
+                    MOTION_NOTIFY => handle_event(Event::MouseMoved(event.key.x.max(0) as u32, event.key.y.max(0) as u32)),
+                    CLIENT_MESSAGE if event.client_message.data[0] as Atom == self.wm_delete_window => {
+                        handle_event(Event::CloseRequested)
+                    }
+                    _ => (),
+                }
+            }
+        }
+
+        self.size = self.size();
+        if old_size != self.size && self.size.0 != 0 && self.size.1 != 0 {
+            handle_event(Event::Resized(self.size.0, self.size.1));
+        }
+    }
+
+    fn size(&self) -> (u32, u32) {
+        let connection = &self.connection;
+        let (mut root, mut x, mut y, mut width, mut height, mut border, mut depth) = (0, 0, 0, 0, 0, 0, 0);
+
+        let success = unsafe {
+            (connection.xlib.XGetGeometry)(
+                connection.display,
+                self.window,
+                &mut root,
+                &mut x,
+                &mut y,
+                &mut width,
+                &mut height,
+                &mut border,
+                &mut depth,
+            )
+        };
+
+        // The size is queried every frame, so a failure is reported and the last known size is used instead of
+        // aborting the game.
+        if success == 0 {
+            error!(Category::Platform, "Failed to retrieve window size.");
+            return self.size;
+        }
+
+        (width, height)
+    }
+
+    fn key_label(&self, key: PhysicalKey) -> String {
+        let xlib = &self.connection.xlib;
+        unsafe {
+            // The key symbol of the first group and shift level is the unshifted symbol of the current layout.
+            let keysym = (xlib.XkbKeycodeToKeysym)(self.connection.display, (key.evdev_code() + 8) as u8, 0, 0);
+
+            // Latin-1 and Unicode key symbols are shown as characters; the names of other key symbols, such as
+            // `Shift_L`, are shown as is.
+            let character = match keysym {
+                0x21..=0x7E | 0xA1..=0xFF => char::from_u32(keysym as u32),
+                0x1000000..=0x110FFFF => char::from_u32(keysym as u32 - 0x1000000),
+                _ => None,
+            };
+
+            if let Some(character) = character {
+                return character.to_uppercase().to_string();
+            }
+
+            let name = (xlib.XKeysymToString)(keysym);
+            if keysym == 0 || name.is_null() {
+                return format!("{key:?}");
+            }
+
+            CStr::from_ptr(name).to_string_lossy().into_owned()
+        }
+    }
+
+    fn key_at(&self, key: PhysicalKey) -> Option<Key> {
+        let xlib = &self.connection.xlib;
+        let keysym = unsafe { (xlib.XkbKeycodeToKeysym)(self.connection.display, (key.evdev_code() + 8) as u8, 0, 0) };
+        key_from_keysym(keysym)
+    }
+}
+
+impl Drop for X11Window {
+    fn drop(&mut self) {
+        unsafe {
+            (self.connection.xlib.XDestroyWindow)(self.connection.display, self.window);
+            (self.connection.xlib.XFlush)(self.connection.display);
+        }
+    }
+}
+
+impl X11Presenter {
+    /// Shows the pixels in the window. The pixels are in the RGBA format, four bytes per pixel, row by row.
+    pub fn present(&self, pixels: &[u8], width: u32, height: u32) -> Result<()> {
+        let connection = &self.connection;
+        let xlib = &connection.xlib;
+
+        // Xlib reads the whole image, so a short slice would be read out of bounds.
+        let expected_len = width as usize * height as usize * 4;
+        if pixels.len() != expected_len {
+            return Err(Error::other(
+                "Failed to present frame.",
+                format!(
+                    "Expected {expected_len} bytes for a {width}x{height} frame, but got {} bytes.",
+                    pixels.len()
+                ),
+            ));
+        }
+
+        unsafe {
+            if (xlib.XDefaultDepth)(connection.display, connection.screen) != 24 {
+                return Err(Error::other(
+                    "Failed to present frame.",
+                    "Presenting requires a 24 bit true color display.",
+                ));
+            }
+
+            // Convert from RGBA to the little endian 0x00RRGGBB format of 24 bit true color displays.
+            let mut data = pixels
+                .chunks_exact(4)
+                .flat_map(|pixel| [pixel[2], pixel[1], pixel[0], 0])
+                .collect::<Vec<_>>();
+
+            let mut image = XImage {
+                width: width as c_int,
+                height: height as c_int,
+                xoffset: 0,
+                format: Z_PIXMAP,
+                data: data.as_mut_ptr() as _,
+                byte_order: LSB_FIRST,
+                bitmap_unit: 32,
+                bitmap_bit_order: LSB_FIRST,
+                bitmap_pad: 32,
+                depth: 24,
+                bytes_per_line: (width * 4) as c_int,
+                bits_per_pixel: 32,
+                red_mask: 0xFF0000,
+                green_mask: 0x00FF00,
+                blue_mask: 0x0000FF,
+                obdata: null_mut(),
+                functions: [null_mut(); 6],
+            };
+
+            if (xlib.XInitImage)(&mut image) == 0 {
+                return Err(Error::other("Failed to initialize X11 image.", ""));
+            }
+
+            (xlib.XPutImage)(
+                connection.display,
+                self.window,
+                (xlib.XDefaultGC)(connection.display, connection.screen),
+                &mut image,
+                0,
+                0,
+                0,
+                0,
+                width,
+                height,
+            );
+            (xlib.XFlush)(connection.display);
+        }
+
+        Ok(())
+    }
+}
+
+fn key_from_keysym(keysym: KeySym) -> Option<Key> {
+    Some(match keysym {
+        0x30..=0x39 => Key::try_from(keysym as i32).ok()?, // XK_0 to XK_9 match the virtual key codes
+        0x61..=0x7A => Key::try_from(keysym as i32 - 0x20).ok()?, // XK_a to XK_z match the virtual key codes of A to Z
+        0xFFBE..=0xFFCC => Key::try_from(keysym as i32 - 0xFFBE + 0x70).ok()?, // XK_F1 to XK_F15
+        0xFFB0..=0xFFB9 => Key::try_from(keysym as i32 - 0xFFB0 + 0x60).ok()?, // XK_KP_0 to XK_KP_9
+        0x20 => Key::Space,
+        0x27 => Key::Quote,
+        0x2C => Key::Comma,
+        0x2D => Key::Dash,
+        0x2E => Key::Period,
+        0x2F => Key::Slash,
+        0x3B => Key::Semicolon,
+        0x3C => Key::BackSlash2,
+        0x3D => Key::Equal,
+        0x5B => Key::LeftBracket,
+        0x5C => Key::BackSlash,
+        0x5D => Key::RightBracket,
+        0x60 => Key::Grave,
+        0xFF08 => Key::Back,
+        0xFF09 => Key::Tab,
+        0xFF0D => Key::Return,
+        0xFF13 => Key::Pause,
+        0xFF14 => Key::Scroll,
+        0xFF1B => Key::Escape,
+        0xFF50 => Key::Home,
+        0xFF51 => Key::Left,
+        0xFF52 => Key::Up,
+        0xFF53 => Key::Right,
+        0xFF54 => Key::Down,
+        0xFF55 => Key::PageUp,
+        0xFF56 => Key::PageDown,
+        0xFF57 => Key::End,
+        0xFF61 => Key::Print,
+        0xFF63 => Key::Insert,
+        0xFF67 => Key::Menu,
+        0xFF7F => Key::NumLock,
+        0xFF8D => Key::NumpadEnter,
+        0xFF95 => Key::Numpad7,
+        0xFF96 => Key::Numpad4,
+        0xFF97 => Key::Numpad8,
+        0xFF98 => Key::Numpad6,
+        0xFF99 => Key::Numpad2,
+        0xFF9A => Key::Numpad9,
+        0xFF9B => Key::Numpad3,
+        0xFF9C => Key::Numpad1,
+        0xFF9D => Key::Numpad5,
+        0xFF9E => Key::Numpad0,
+        0xFF9F => Key::NumpadDecimal,
+        0xFFAA => Key::Multiply,
+        0xFFAB => Key::Add,
+        0xFFAD => Key::Subtract,
+        0xFFAE => Key::NumpadDecimal,
+        0xFFAF => Key::Divide,
+        0xFFE1 => Key::LeftShift,
+        0xFFE2 => Key::RightShift,
+        0xFFE3 => Key::LeftControl,
+        0xFFE4 => Key::RightControl,
+        0xFFE5 => Key::CapsLock,
+        0xFFE9 => Key::LeftAlt,
+        0xFFEA => Key::RightAlt,
+        0xFFEB => Key::LeftSystem,
+        0xFFEC => Key::RightSystem,
+        0xFFFF => Key::Delete,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keysyms_are_mapped_to_keys() {
+        let keys = [
+            (0x61, Some(Key::A)),         // XK_a
+            (0x7A, Some(Key::Z)),         // XK_z
+            (0x30, Some(Key::Num0)),      // XK_0
+            (0x39, Some(Key::Num9)),      // XK_9
+            (0xFFBE, Some(Key::F1)),      // XK_F1
+            (0xFFC9, Some(Key::F12)),     // XK_F12
+            (0xFFCC, Some(Key::F15)),     // XK_F15
+            (0xFFB0, Some(Key::Numpad0)), // XK_KP_0
+            (0xFFB9, Some(Key::Numpad9)), // XK_KP_9
+            (0xFF9C, Some(Key::Numpad1)), // XK_KP_End
+            (0xFFE1, Some(Key::LeftShift)),
+            (0xFFE4, Some(Key::RightControl)),
+            (0xFFE9, Some(Key::LeftAlt)),
+            (0xFFEC, Some(Key::RightSystem)),
+            (0xFF52, Some(Key::Up)),
+            (0xFF8D, Some(Key::NumpadEnter)),
+            (0x41, None),   // XK_A; keys are looked up by their unshifted keysyms first
+            (0xFFCD, None), // XK_F16
+            (0x20AC, None), // XK_EuroSign
+            (0, None),      // NoSymbol
+        ];
+
+        for (keysym, key) in keys {
+            assert_eq!(key_from_keysym(keysym), key, "keysym {keysym:#X}");
+        }
+    }
+}
@@ -1,4 +1,6 @@
-#[derive(Debug)]
+use crate::platform::graphics::buffer::BufferElement;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Color {
     pub r: u8,
     pub g: u8,
@@ -12,10 +14,55 @@ impl Color {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub struct Rectangle<T> {
     pub left: T,
     pub top: T,
     pub width: T,
     pub height: T,
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Vector2 {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Vector2 {
+    pub fn new(x: f32, y: f32) -> Vector2 {
+        Vector2 { x, y }
+    }
+}
+
+/// A 4x4 matrix stored in column-major order, the layout HLSL expects for `column_major` matrices.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Matrix {
+    pub columns: [[f32; 4]; 4],
+}
+
+unsafe impl BufferElement for Matrix {}
+
+impl Matrix {
+    pub fn identity() -> Matrix {
+        Matrix {
+            columns: [[1., 0., 0., 0.], [0., 1., 0., 0.], [0., 0., 1., 0.], [0., 0., 0., 1.]],
+        }
+    }
+
+    /// Maps pixel coordinates with the origin in the top left corner and the y-axis pointing down to clip space.
+    pub fn orthographic(width: f32, height: f32) -> Matrix {
+        Matrix {
+            columns: [
+                [2. / width, 0., 0., 0.],
+                [0., -2. / height, 0., 0.],
+                [0., 0., 1., 0.],
+                [-1., 1., 0., 1.],
+            ],
+        }
+    }
+
+    pub fn transform(&self, vector: [f32; 4]) -> [f32; 4] {
+        [0, 1, 2, 3].map(|row| (0..4).map(|column| self.columns[column][row] * vector[column]).sum())
+    }
+}
@@ -0,0 +1,2 @@
+pub mod sprite_batch;
+pub mod sprite_renderer;
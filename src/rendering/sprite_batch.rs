@@ -0,0 +1,234 @@
+use crate::{
+    platform::graphics::{
+        buffer::BufferElement,
+        shader::{vertex_attribute, Semantic, Vertex, VertexAttribute},
+    },
+    primitives::{Color, Rectangle, Vector2},
+};
+
+/// The number of sprites that can be drawn with a single draw call using 16 bit indices.
+pub const MAX_SPRITES_PER_BATCH: usize = (u16::MAX as usize + 1) / 4;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpriteVertex {
+    pub position: [f32; 2],
+    pub tex_coords: [f32; 2],
+    pub color: [u8; 4],
+}
+
+unsafe impl BufferElement for SpriteVertex {}
+
+impl Vertex for SpriteVertex {
+    const ATTRIBUTES: &'static [VertexAttribute] = &[
+        vertex_attribute!(SpriteVertex, position, Semantic::Position, 0),
+        vertex_attribute!(SpriteVertex, tex_coords, Semantic::TexCoord, 0),
+        vertex_attribute!(SpriteVertex, color, Semantic::Color, 0),
+    ];
+}
+
+/// A textured, tinted and rotated quad. `T` identifies the sprite's texture.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sprite<T> {
+    pub texture: T,
+    /// Sprites on lower layers are drawn first.
+    pub layer: i32,
+    /// The position of the sprite's origin in pixels.
+    pub position: Vector2,
+    pub size: Vector2,
+    /// The point the sprite is positioned and rotated around, relative to its size, i.e. (0.5, 0.5) is the center.
+    pub origin: Vector2,
+    /// The clockwise rotation around the origin in radians.
+    pub rotation: f32,
+    /// The area of the texture that is drawn, in texture coordinates.
+    pub source: Rectangle<f32>,
+    pub color: Color,
+}
+
+/// A range of consecutive sprites that share the same texture and can thus be drawn with a single draw call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Batch<T> {
+    pub texture: T,
+    /// The index of the batch's first vertex.
+    pub first_vertex: u32,
+    pub sprite_count: u32,
+}
+
+/// Collects sprites and turns them into vertices grouped into as few batches as possible. The vertices of each
+/// batch are drawn with the index pattern returned by `sprite_indices`, offset by the batch's first vertex.
+pub struct SpriteBatch<T> {
+    sprites: Vec<Sprite<T>>,
+    vertices: Vec<SpriteVertex>,
+    batches: Vec<Batch<T>>,
+}
+
+impl<T: Copy + Ord> SpriteBatch<T> {
+    pub fn new() -> SpriteBatch<T> {
+        SpriteBatch {
+            sprites: Vec::new(),
+            vertices: Vec::new(),
+            batches: Vec::new(),
+        }
+    }
+
+    pub fn draw(&mut self, sprite: Sprite<T>) {
+        self.sprites.push(sprite);
+    }
+
+    /// Sorts the sprites drawn since the last call by layer and texture and generates their vertices and batches.
+    /// Sprites with the same layer and texture retain the order in which they were drawn.
+    pub fn build(&mut self) -> (&[SpriteVertex], &[Batch<T>]) {
+        self.vertices.clear();
+        self.batches.clear();
+        self.sprites.sort_by_key(|sprite| (sprite.layer, sprite.texture));
+
+        for sprite in self.sprites.drain(..) {
+            match self.batches.last_mut() {
+                Some(batch) if batch.texture == sprite.texture && (batch.sprite_count as usize) < MAX_SPRITES_PER_BATCH => {
+                    batch.sprite_count += 1
+                }
+                _ => self.batches.push(Batch {
+                    texture: sprite.texture,
+                    first_vertex: self.vertices.len() as u32,
+                    sprite_count: 1,
+                }),
+            }
+
+            let (sin, cos) = sprite.rotation.sin_cos();
+            let color = [sprite.color.r, sprite.color.g, sprite.color.b, sprite.color.a];
+
+            // The corners in the order top left, top right, bottom left, bottom right.
+            for (x, y) in [(0., 0.), (1., 0.), (0., 1.), (1., 1.)] {
+                let local_x = (x - sprite.origin.x) * sprite.size.x;
+                let local_y = (y - sprite.origin.y) * sprite.size.y;
+
+                self.vertices.push(SpriteVertex {
+                    position: [
+                        sprite.position.x + local_x * cos - local_y * sin,
+                        sprite.position.y + local_x * sin + local_y * cos,
+                    ],
+                    tex_coords: [
+                        sprite.source.left + x * sprite.source.width,
+                        sprite.source.top + y * sprite.source.height,
+                    ],
+                    color,
+                });
+            }
+        }
+
+        (&self.vertices, &self.batches)
+    }
+}
+
+/// Gets the indices of the two clockwise triangles of each of `MAX_SPRITES_PER_BATCH` sprites.
+pub fn sprite_indices() -> Vec<u16> {
+    (0..MAX_SPRITES_PER_BATCH as u16)
+        .flat_map(|sprite| [0, 1, 2, 2, 1, 3].map(|corner| sprite * 4 + corner))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sprite(texture: u32, layer: i32) -> Sprite<u32> {
+        Sprite {
+            texture,
+            layer,
+            position: Vector2::new(10., 20.),
+            size: Vector2::new(4., 2.),
+            origin: Vector2::new(0., 0.),
+            rotation: 0.,
+            source: Rectangle {
+                left: 0.,
+                top: 0.,
+                width: 1.,
+                height: 1.,
+            },
+            color: Color::new(255, 128, 0, 255),
+        }
+    }
+
+    #[test]
+    fn sprites_are_sorted_by_layer_and_texture() {
+        let mut sprite_batch = SpriteBatch::new();
+        sprite_batch.draw(sprite(2, 1));
+        sprite_batch.draw(sprite(1, 1));
+        sprite_batch.draw(sprite(2, 0));
+        sprite_batch.draw(sprite(1, 1));
+
+        let (vertices, batches) = sprite_batch.build();
+        assert_eq!(vertices.len(), 16);
+        assert_eq!(
+            batches,
+            [
+                Batch {
+                    texture: 2,
+                    first_vertex: 0,
+                    sprite_count: 1
+                },
+                Batch {
+                    texture: 1,
+                    first_vertex: 4,
+                    sprite_count: 2
+                },
+                Batch {
+                    texture: 2,
+                    first_vertex: 12,
+                    sprite_count: 1
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn batches_are_split_when_exceeding_index_range() {
+        let mut sprite_batch = SpriteBatch::new();
+        for _ in 0..MAX_SPRITES_PER_BATCH + 1 {
+            sprite_batch.draw(sprite(0, 0));
+        }
+
+        let (_, batches) = sprite_batch.build();
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[1].first_vertex, MAX_SPRITES_PER_BATCH as u32 * 4);
+        assert_eq!(batches[1].sprite_count, 1);
+    }
+
+    #[test]
+    fn building_consumes_sprites() {
+        let mut sprite_batch = SpriteBatch::new();
+        sprite_batch.draw(sprite(0, 0));
+        sprite_batch.build();
+
+        let (vertices, batches) = sprite_batch.build();
+        assert!(vertices.is_empty());
+        assert!(batches.is_empty());
+    }
+
+    #[test]
+    fn vertices_are_rotated_around_origin() {
+        let mut sprite_batch = SpriteBatch::new();
+        sprite_batch.draw(Sprite {
+            origin: Vector2::new(0.5, 0.5),
+            rotation: std::f32::consts::FRAC_PI_2,
+            ..sprite(0, 0)
+        });
+
+        let (vertices, _) = sprite_batch.build();
+        let positions: Vec<[f32; 2]> = vertices
+            .iter()
+            .map(|vertex| vertex.position.map(|coordinate| coordinate.round()))
+            .collect();
+        assert_eq!(positions, [[11., 18.], [11., 22.], [9., 18.], [9., 22.]]);
+        assert_eq!(vertices[3].tex_coords, [1., 1.]);
+        assert_eq!(vertices[0].color, [255, 128, 0, 255]);
+    }
+
+    #[test]
+    fn indices_form_two_triangles_per_sprite() {
+        let indices = sprite_indices();
+        assert_eq!(indices.len(), MAX_SPRITES_PER_BATCH * 6);
+        assert_eq!(indices[..12], [0, 1, 2, 2, 1, 3, 4, 5, 6, 6, 5, 7]);
+        assert_eq!(indices.last(), Some(&u16::MAX));
+    }
+}
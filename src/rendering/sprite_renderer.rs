@@ -0,0 +1,292 @@
+use super::sprite_batch::{sprite_indices, SpriteBatch, SpriteVertex, MAX_SPRITES_PER_BATCH};
+use crate::{
+    assets::shader,
+    platform::{
+        error::{Error, Result},
+        graphics::{
+            buffer::{BufferUsage, MapMode},
+            registry::{Resource, ResourceRegistry},
+            shader::input_layout,
+            state::{
+                AddressMode, BlendMode, CullMode, DepthStencilDescription, PrimitiveType, RasterizerDescription,
+                SamplerDescription, TextureFilter,
+            },
+            GraphicsDevice,
+        },
+    },
+    primitives::Matrix,
+};
+use std::{cell::Cell, mem::size_of, rc::Rc};
+
+const VERTEX_CAPACITY: usize = MAX_SPRITES_PER_BATCH * 4;
+
+/// Draws sprite batches with the sprite shaders. The vertices are streamed into a dynamic vertex buffer that is only
+/// discarded once it is full, so that the GPU can keep reading the vertices of previous draws in the meantime. All
+/// resources are created through a registry, so that they are recreated after the device has been lost.
+pub struct SpriteRenderer<G: GraphicsDevice> {
+    vertex_shader: VertexShader<G>,
+    pixel_shader: PixelShader<G>,
+    vertex_buffer: Resource<G::VertexBuffer<SpriteVertex>>,
+    index_buffer: Resource<G::IndexBuffer<u16>>,
+    projection: Resource<G::ConstantBuffer<Matrix>>,
+    world: Resource<G::ConstantBuffer<Matrix>>,
+    sampler_state: Resource<G::SamplerState>,
+    blend_state: Resource<G::BlendState>,
+    depth_stencil_state: Resource<G::DepthStencilState>,
+    rasterizer_state: Resource<G::RasterizerState>,
+    /// The index of the first vertex of the vertex buffer that has not been written since it was last discarded.
+    next_vertex: Rc<Cell<usize>>,
+}
+
+/// The vertex shader along with the slots of the constant buffers it reads, as reflected from the shader.
+struct VertexShader<G: GraphicsDevice> {
+    shader: Resource<G::VertexShader>,
+    projection_slot: u32,
+    world_slot: u32,
+}
+
+impl<G: GraphicsDevice + 'static> VertexShader<G> {
+    fn new(graphics_device: &G, registry: &ResourceRegistry<G>, data: &[u8]) -> Result<VertexShader<G>> {
+        let invalid = |e: String| Error::other("Failed to create the sprite vertex shader.", e);
+        let shader = shader::load(data).map_err(|e| invalid(e.to_string()))?;
+        let reflection = &shader.reflection;
+        Ok(VertexShader {
+            projection_slot: reflection
+                .constant_buffer_slot("PerFrameConstants", size_of::<Matrix>())
+                .map_err(invalid)?,
+            world_slot: reflection
+                .constant_buffer_slot("PerObjectConstants", size_of::<Matrix>())
+                .map_err(invalid)?,
+            shader: registry.create_vertex_shader(
+                graphics_device,
+                shader.byte_code,
+                &input_layout::<SpriteVertex>(&reflection.inputs).map_err(invalid)?,
+            )?,
+        })
+    }
+}
+
+/// The pixel shader along with the slots of the texture and sampler it reads, as reflected from the shader.
+struct PixelShader<G: GraphicsDevice> {
+    shader: Resource<G::PixelShader>,
+    texture_slot: u32,
+    sampler_slot: u32,
+}
+
+impl<G: GraphicsDevice + 'static> PixelShader<G> {
+    fn new(graphics_device: &G, registry: &ResourceRegistry<G>, data: &[u8]) -> Result<PixelShader<G>> {
+        let invalid = |e: String| Error::other("Failed to create the sprite pixel shader.", e);
+        let shader = shader::load(data).map_err(|e| invalid(e.to_string()))?;
+        Ok(PixelShader {
+            texture_slot: shader.reflection.texture_slot("Tex").map_err(invalid)?,
+            sampler_slot: shader.reflection.sampler_slot("TexSampler").map_err(invalid)?,
+            shader: registry.create_pixel_shader(graphics_device, shader.byte_code)?,
+        })
+    }
+}
+
+impl<G: GraphicsDevice + 'static> SpriteRenderer<G> {
+    /// Creates the renderer from the compiled sprite shaders; fails if the shaders do not match the sprite vertex
+    /// format and the renderer's resources.
+    pub fn new(
+        graphics_device: &G,
+        registry: &ResourceRegistry<G>,
+        vertex_shader: &[u8],
+        pixel_shader: &[u8],
+    ) -> Result<SpriteRenderer<G>> {
+        // The contents of the vertex buffer are unspecified after it has been recreated, so it has to be discarded
+        // before it is written again.
+        let next_vertex = Rc::new(Cell::new(VERTEX_CAPACITY));
+        let vertex_buffer = registry.register(graphics_device, {
+            let next_vertex = next_vertex.clone();
+            move |graphics_device| {
+                next_vertex.set(VERTEX_CAPACITY);
+                graphics_device.create_vertex_buffer(BufferUsage::Dynamic(VERTEX_CAPACITY))
+            }
+        })?;
+
+        Ok(SpriteRenderer {
+            vertex_shader: VertexShader::new(graphics_device, registry, vertex_shader)?,
+            pixel_shader: PixelShader::new(graphics_device, registry, pixel_shader)?,
+            vertex_buffer,
+            index_buffer: registry.create_index_buffer(graphics_device, BufferUsage::Static(&sprite_indices()))?,
+            projection: registry.create_constant_buffer(graphics_device, BufferUsage::Dynamic(1))?,
+            world: registry.create_constant_buffer(graphics_device, BufferUsage::Static(&[Matrix::identity()]))?,
+            sampler_state: registry.create_sampler_state(
+                graphics_device,
+                &SamplerDescription {
+                    filter: TextureFilter::Trilinear,
+                    address_mode: AddressMode::Clamp,
+                },
+            )?,
+            blend_state: registry.create_blend_state(graphics_device, BlendMode::Alpha)?,
+            depth_stencil_state: registry.create_depth_stencil_state(graphics_device, &DepthStencilDescription::default())?,
+            // Sprites are not culled, so that they can be mirrored by flipping their corners.
+            rasterizer_state: registry.create_rasterizer_state(
+                graphics_device,
+                &RasterizerDescription {
+                    cull_mode: CullMode::None,
+                    ..Default::default()
+                },
+            )?,
+            next_vertex,
+        })
+    }
+
+    /// Replaces the vertex shader, for instance after it has been recompiled during development. The previous shader
+    /// is kept if the new one is invalid.
+    pub fn set_vertex_shader(&mut self, graphics_device: &G, registry: &ResourceRegistry<G>, data: &[u8]) -> Result<()> {
+        self.vertex_shader = VertexShader::new(graphics_device, registry, data)?;
+        Ok(())
+    }
+
+    /// Replaces the pixel shader, for instance after it has been recompiled during development. The previous shader
+    /// is kept if the new one is invalid.
+    pub fn set_pixel_shader(&mut self, graphics_device: &G, registry: &ResourceRegistry<G>, data: &[u8]) -> Result<()> {
+        self.pixel_shader = PixelShader::new(graphics_device, registry, data)?;
+        Ok(())
+    }
+
+    /// Draws the sprites of the batch into the current render target, issuing one draw call per batch. `texture` maps
+    /// the sprites' texture identifiers to the textures that are drawn.
+    pub fn draw<'t, T: Copy + Ord>(
+        &self,
+        graphics_device: &G,
+        sprite_batch: &mut SpriteBatch<T>,
+        projection: &Matrix,
+        texture: impl Fn(T) -> &'t Resource<G::Texture2D>,
+    ) -> Result<()>
+    where
+        G::Texture2D: 't,
+    {
+        let (vertices, batches) = sprite_batch.build();
+        if batches.is_empty() {
+            return Ok(());
+        }
+
+        graphics_device.update_constant_buffer(&self.projection.get(), projection)?;
+        graphics_device.set_vertex_shader(&self.vertex_shader.shader.get());
+        graphics_device.set_pixel_shader(&self.pixel_shader.shader.get());
+        graphics_device.set_vertex_buffer(&self.vertex_buffer.get());
+        graphics_device.set_index_buffer(&self.index_buffer.get());
+        graphics_device.set_vertex_constant_buffer(self.vertex_shader.projection_slot, &self.projection.get());
+        graphics_device.set_vertex_constant_buffer(self.vertex_shader.world_slot, &self.world.get());
+        graphics_device.set_pixel_sampler_state(self.pixel_shader.sampler_slot, &self.sampler_state.get());
+        graphics_device.set_blend_state(&self.blend_state.get());
+        graphics_device.set_depth_stencil_state(&self.depth_stencil_state.get());
+        graphics_device.set_rasterizer_state(&self.rasterizer_state.get());
+        graphics_device.set_primitive_type(PrimitiveType::Triangles);
+
+        for batch in batches {
+            let first_vertex = batch.first_vertex as usize;
+            let vertices = &vertices[first_vertex..first_vertex + batch.sprite_count as usize * 4];
+
+            let mode = if self.next_vertex.get() + vertices.len() > VERTEX_CAPACITY {
+                self.next_vertex.set(0);
+                MapMode::Discard
+            } else {
+                MapMode::NoOverwrite
+            };
+
+            graphics_device.set_pixel_texture(self.pixel_shader.texture_slot, &texture(batch.texture).get());
+            graphics_device.update_vertex_buffer(&self.vertex_buffer.get(), mode, self.next_vertex.get(), vertices)?;
+            graphics_device.draw_indexed(batch.sprite_count * 6, 0, self.next_vertex.get() as i32);
+            self.next_vertex.set(self.next_vertex.get() + vertices.len());
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        platform::graphics::{
+            software::SoftwareGraphicsDevice,
+            texture::{TextureDescription, TextureFormat},
+        },
+        primitives::{Color, Rectangle, Vector2},
+        rendering::sprite_batch::Sprite,
+        software_sprite_shaders,
+    };
+
+    const BACKGROUND: Color = Color {
+        r: 0,
+        g: 0,
+        b: 0,
+        a: 255,
+    };
+
+    #[test]
+    fn sprites_are_drawn_with_their_textures() {
+        let mut device = SoftwareGraphicsDevice::new(8, 8);
+        let (vertex_shader, pixel_shader) = software_sprite_shaders(&device).unwrap();
+        let registry = ResourceRegistry::new();
+        let renderer = SpriteRenderer::new(&device, &registry, &vertex_shader, &pixel_shader).unwrap();
+        let texture = registry
+            .create_texture(
+                &device,
+                &TextureDescription {
+                    width: 2,
+                    height: 1,
+                    format: TextureFormat::Rgba8,
+                    mipmaps: false,
+                    render_target: false,
+                },
+                Some(&[255, 0, 0, 255, 0, 0, 255, 255]),
+            )
+            .unwrap();
+
+        let mut sprite_batch = SpriteBatch::new();
+        let mut draw = |device: &SoftwareGraphicsDevice, position| {
+            sprite_batch.draw(Sprite {
+                texture: 0,
+                layer: 0,
+                position,
+                size: Vector2 { x: 4., y: 2. },
+                origin: Vector2 { x: 0., y: 0. },
+                rotation: 0.,
+                source: Rectangle {
+                    left: 0.,
+                    top: 0.,
+                    width: 1.,
+                    height: 1.,
+                },
+                color: Color::new(255, 255, 255, 255),
+            });
+
+            device.set_render_target(device.back_buffer());
+            device.set_viewport(&Rectangle {
+                left: 0,
+                top: 0,
+                width: 8,
+                height: 8,
+            });
+            device.clear(device.back_buffer(), BACKGROUND);
+            renderer
+                .draw(device, &mut sprite_batch, &Matrix::orthographic(8., 8.), |_| &texture)
+                .unwrap();
+            device.back_buffer().capture()
+        };
+
+        let frame = draw(&device, Vector2 { x: 2., y: 3. });
+        assert_eq!(frame.pixel(2, 3), Color::new(255, 0, 0, 255));
+        assert_eq!(frame.pixel(5, 4), Color::new(0, 0, 255, 255));
+        assert_eq!(frame.pixel(1, 3), BACKGROUND);
+        assert_eq!(frame.pixel(6, 4), BACKGROUND);
+        assert_eq!(frame.pixel(2, 5), BACKGROUND);
+        assert_eq!(renderer.next_vertex.get(), 4);
+
+        // The vertex buffer is discarded when it is first written after it has been recreated.
+        device.lose("The graphics driver has been updated.");
+        device.recreate().unwrap();
+        registry.recreate(&device).unwrap();
+        assert_eq!(renderer.next_vertex.get(), VERTEX_CAPACITY);
+
+        let frame = draw(&device, Vector2 { x: 0., y: 0. });
+        assert_eq!(frame.pixel(0, 0), Color::new(255, 0, 0, 255));
+        assert_eq!(frame.pixel(3, 1), Color::new(0, 0, 255, 255));
+        assert_eq!(renderer.next_vertex.get(), 4);
+    }
+}
@@ -0,0 +1,12 @@
+//! Helpers shared by the tests of several modules.
+
+use std::{env, fs, path::PathBuf, process};
+
+/// Creates an empty directory for a test, removing the files left behind by previous runs. As tests run in parallel,
+/// the name has to be unique among all tests.
+pub fn directory(name: &str) -> PathBuf {
+    let directory = env::temp_dir().join(format!("lwar-{name}-{}", process::id()));
+    let _ = fs::remove_dir_all(&directory);
+    fs::create_dir_all(&directory).unwrap();
+    directory
+}